@@ -0,0 +1,298 @@
+//! Compiles `catalog/policies.toml` into a static policy table, adding every
+//! other policy in Microsoft's administrative templates when they're
+//! available.
+//!
+//! The templates are `msedge.admx` and `en-US/msedge.adml` from the
+//! `windows/admx` folder of MicrosoftEdgePolicyTemplates.zip. They're read
+//! from the directory named by `EDGE_POLICY_TEMPLATES`, or from
+//! `catalog/templates`. Entries in `policies.toml` win over generated ones,
+//! which lack value formats and carry the templates' own names for
+//! enumerated values.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+const CATALOG_PATH: &str = "catalog/policies.toml";
+const TEMPLATES_ENV: &str = "EDGE_POLICY_TEMPLATES";
+const TEMPLATES_DIR: &str = "catalog/templates";
+
+/// ADMX categories that match a curated one; the rest are snake-cased.
+const CATEGORIES: &[(&str, &str)] = &[
+    ("microsoft_edge", "other"),
+    ("ContentSettings", "content"),
+    ("DefaultSearchProvider", "search"),
+    ("Extensions", "extensions"),
+    ("Identity", "account"),
+    ("PasswordManager", "privacy"),
+    ("Performance", "performance"),
+    ("Proxy", "network"),
+    ("SmartScreen", "security"),
+    ("Startup", "browser"),
+];
+
+#[derive(Deserialize)]
+struct Catalog {
+    policy: Vec<Policy>,
+}
+
+#[derive(Deserialize)]
+struct Policy {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    summary: String,
+    category: String,
+    since: u32,
+    removed: Option<u32>,
+    format: Option<String>,
+    replaced_by: Option<String>,
+    scope: Option<String>,
+    #[serde(default)]
+    values: BTreeMap<String, String>,
+    min: Option<u32>,
+    max: Option<u32>,
+    #[serde(default)]
+    allowed: Vec<String>,
+}
+
+fn main() {
+    // The whole directory, so templates dropped into it are picked up.
+    println!("cargo:rerun-if-changed=catalog");
+    println!("cargo:rerun-if-env-changed={TEMPLATES_ENV}");
+
+    let text = std::fs::read_to_string(CATALOG_PATH).expect("read policy catalog");
+    let mut catalog: Catalog = toml::from_str(&text).expect("parse policy catalog");
+
+    let templates: PathBuf =
+        std::env::var_os(TEMPLATES_ENV).map_or_else(|| TEMPLATES_DIR.into(), Into::into);
+    let generated = read_templates(&templates);
+    let from_templates = generated.is_some();
+    if let Some(generated) = generated {
+        for p in generated {
+            if !catalog
+                .policy
+                .iter()
+                .any(|q| q.name.eq_ignore_ascii_case(&p.name))
+            {
+                catalog.policy.push(p);
+            }
+        }
+    }
+
+    for p in &catalog.policy {
+        if let Some(ref new) = p.replaced_by {
+            assert!(
+                catalog.policy.iter().any(|q| &q.name == new),
+                "{}: replacement {new} is not in the catalog",
+                p.name
+            );
+        }
+    }
+
+    let mut out = format!("pub const FROM_TEMPLATES: bool = {from_templates};\n\n");
+    out.push_str("pub static POLICIES: &[PolicyDef] = &[\n");
+    for p in &catalog.policy {
+        let kind = match p.kind.as_str() {
+            "boolean" => "Boolean",
+            "integer" => "Integer",
+            "string" => "String",
+            "list" => "List",
+            "dictionary" => "Dictionary",
+            other => panic!("{}: unknown policy type {other:?}", p.name),
+        };
+
+        let format = match p.format.as_deref() {
+            None => "None",
+            Some("url") => "Some(ValueFormat::Url)",
+            Some("url_pattern") => "Some(ValueFormat::UrlPattern)",
+            Some("forcelist") => "Some(ValueFormat::Forcelist)",
+            Some(other) => panic!("{}: unknown value format {other:?}", p.name),
+        };
+
+        let machine_only = match p.scope.as_deref() {
+            None | Some("user") => false,
+            Some("machine") => true,
+            Some(other) => panic!("{}: unknown scope {other:?}", p.name),
+        };
+
+        let mut values: Vec<(u32, &str)> = p
+            .values
+            .iter()
+            .map(|(k, v)| {
+                let k = k
+                    .parse()
+                    .unwrap_or_else(|_| panic!("{}: value key {k:?} is not a DWORD", p.name));
+                (k, v.as_str())
+            })
+            .collect();
+        values.sort();
+
+        let range = match (p.min, p.max) {
+            (Some(lo), Some(hi)) => format!("Some(({lo}, {hi}))"),
+            (None, None) => "None".to_owned(),
+            _ => panic!("{}: min and max must be given together", p.name),
+        };
+
+        writeln!(out, "    PolicyDef {{").unwrap();
+        writeln!(out, "        name: {:?},", p.name).unwrap();
+        writeln!(out, "        kind: PolicyKind::{kind},").unwrap();
+        writeln!(out, "        summary: {:?},", p.summary).unwrap();
+        writeln!(out, "        category: {:?},", p.category).unwrap();
+        writeln!(out, "        since: {},", p.since).unwrap();
+        writeln!(out, "        removed: {:?},", p.removed).unwrap();
+        writeln!(out, "        format: {format},").unwrap();
+        writeln!(out, "        replaced_by: {:?},", p.replaced_by).unwrap();
+        writeln!(out, "        machine_only: {machine_only},").unwrap();
+        writeln!(out, "        values: &{values:?},").unwrap();
+        writeln!(out, "        range: {range},").unwrap();
+        writeln!(out, "        allowed: &{:?},", p.allowed).unwrap();
+        writeln!(out, "    }},").unwrap();
+    }
+    out.push_str("];\n");
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR");
+    std::fs::write(Path::new(&out_dir).join("catalog.rs"), out).expect("write catalog.rs");
+}
+
+/// Every mandatory policy in the templates under `dir`, or `None` if they
+/// aren't there.
+fn read_templates(dir: &Path) -> Option<Vec<Policy>> {
+    let admx_path = dir.join("msedge.admx");
+    let adml_path = dir.join("en-US").join("msedge.adml");
+    let admx = std::fs::read_to_string(&admx_path).ok()?;
+    println!("cargo:rerun-if-changed={}", admx_path.display());
+    println!("cargo:rerun-if-changed={}", adml_path.display());
+    let adml = std::fs::read_to_string(&adml_path)
+        .unwrap_or_else(|e| panic!("read {}: {e}", adml_path.display()));
+
+    let adml = roxmltree::Document::parse(&adml).expect("parse msedge.adml");
+    let strings: BTreeMap<&str, &str> = adml
+        .descendants()
+        .filter(|n| n.has_tag_name("string"))
+        .filter_map(|n| Some((n.attribute("id")?, n.text().unwrap_or_default())))
+        .collect();
+    let text = |reference: &str| {
+        let id = reference
+            .strip_prefix("$(string.")
+            .and_then(|r| r.strip_suffix(')'))
+            .unwrap_or(reference);
+        strings.get(id).map_or(id, |s| s.trim()).to_owned()
+    };
+
+    let admx = roxmltree::Document::parse(&admx).expect("parse msedge.admx");
+    let mut policies = Vec::new();
+    for node in admx.descendants().filter(|n| n.has_tag_name("policy")) {
+        let (Some(name), Some(key)) = (node.attribute("name"), node.attribute("key")) else {
+            continue;
+        };
+        // Recommended policies live under their own key and have the same
+        // names as the mandatory ones.
+        if key.ends_with(r"\Recommended") || name.ends_with("_recommended") {
+            continue;
+        }
+        let child = |tag: &str| node.children().find(|n| n.has_tag_name(tag));
+        let category = child("parentCategory")
+            .and_then(|n| n.attribute("ref"))
+            .unwrap_or("microsoft_edge");
+        let category = category.rsplit(':').next().unwrap_or(category);
+        let since = child("supportedOn")
+            .and_then(|n| n.attribute("ref"))
+            .and_then(|r| r.rsplit_once("_V"))
+            .and_then(|(_, v)| v.parse().ok())
+            .unwrap_or(77);
+
+        let mut policy = Policy {
+            name: name.to_owned(),
+            kind: "boolean".to_owned(),
+            summary: text(node.attribute("displayName").unwrap_or(name)),
+            category: CATEGORIES
+                .iter()
+                .find(|(admx, _)| *admx == category)
+                .map_or_else(|| snake_case(category), |(_, ours)| (*ours).to_owned()),
+            since,
+            removed: None,
+            format: None,
+            replaced_by: None,
+            scope: (node.attribute("class") == Some("Machine")).then(|| "machine".to_owned()),
+            values: BTreeMap::new(),
+            min: None,
+            max: None,
+            allowed: Vec::new(),
+        };
+
+        let element = child("elements").and_then(|e| e.children().find(|n| n.is_element()));
+        match element.as_ref().map(|e| e.tag_name().name()) {
+            None | Some("boolean") => {}
+            Some("list") => policy.kind = "list".to_owned(),
+            Some("text" | "multiText") => policy.kind = "string".to_owned(),
+            Some("decimal") => {
+                let element = element.expect("matched an element");
+                policy.kind = "integer".to_owned();
+                let bound = |attr| element.attribute(attr).and_then(|v| v.parse().ok());
+                if let (Some(lo), Some(hi)) = (bound("minValue"), bound("maxValue")) {
+                    (policy.min, policy.max) = (Some(lo), Some(hi));
+                }
+            }
+            Some("enum") => {
+                let element = element.expect("matched an element");
+                policy.kind = "integer".to_owned();
+                for item in element.children().filter(|n| n.has_tag_name("item")) {
+                    let Some(value) = item.descendants().find(|n| n.has_tag_name("value")) else {
+                        continue;
+                    };
+                    let label = item.attribute("displayName").unwrap_or_default();
+                    let label = value_label(name, label);
+                    let data = value.children().find(|n| n.is_element());
+                    match data.as_ref().map(|d| (d.tag_name().name(), d)) {
+                        Some(("decimal", d)) => {
+                            if let Some(v) = d.attribute("value") {
+                                policy.values.insert(v.to_owned(), label);
+                            }
+                        }
+                        Some(("string", d)) => {
+                            policy.kind = "string".to_owned();
+                            policy.allowed.push(d.text().unwrap_or_default().to_owned());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Some(other) => panic!("{name}: unknown ADMX element {other:?}"),
+        }
+        policies.push(policy);
+    }
+    Some(policies)
+}
+
+/// A short name for an enumerated value from its ADML string ID, e.g.
+/// `$(string.DefaultCookiesSetting_AllowCookies_DisplayName)` ->
+/// `allow_cookies`.
+fn value_label(policy: &str, reference: &str) -> String {
+    let id = reference
+        .strip_prefix("$(string.")
+        .and_then(|r| r.strip_suffix(')'))
+        .unwrap_or(reference);
+    let id = id.strip_suffix("_DisplayName").unwrap_or(id);
+    let id = id
+        .strip_prefix(policy)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .unwrap_or(id);
+    snake_case(id)
+}
+
+/// `ContentSettings` -> `content_settings`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
@@ -0,0 +1,818 @@
+//! Build a policy set in code instead of writing config TOML.
+//!
+//! ```
+//! use edge_profile_core::PolicySet;
+//!
+//! let entries = PolicySet::new()
+//!     .search("DuckDuckGo", "https://duckduckgo.com/?q={searchTerms}")
+//!     .extension("edge:odfafepnkmbhccpbejgmiehpchacaeak")
+//!     .tracking_prevention("strict")
+//!     .raw("EdgeShoppingAssistantEnabled", false)
+//!     .entries();
+//! assert!(entries.iter().any(|e| e.name == "DefaultSearchProviderName"));
+//! ```
+
+use crate::config::{
+    AccountConfig, AdvancedConfig, AppearanceConfig, BrowserConfig, Config, ContentConfig,
+    DownloadsConfig, ExtensionSetting, Favorite, FavoritesConfig, FeaturesConfig, IeModeConfig,
+    ImportConfig, LanguageConfig, PerformanceConfig, PrivacyConfig, SearchConfig, SecurityConfig,
+    SiteLists, TelemetryConfig, UrlsConfig,
+};
+use crate::error::Result;
+use crate::policy::{self, PolicyEntry};
+use crate::validate::{self, Issue};
+use crate::{config, Scope};
+
+/// A [`Config`] assembled one setting at a time. Each method mirrors a
+/// config key, so the result validates and serializes like a loaded file.
+#[derive(Debug, Default)]
+pub struct PolicySet {
+    config: Config,
+    /// Keys that named no setting, reported by [`PolicySet::check`].
+    unknown_keys: Vec<String>,
+}
+
+impl PolicySet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `[search]`: the default search provider and its query URL, with
+    /// `{searchTerms}` where the query goes.
+    pub fn search(mut self, provider: impl Into<String>, search_url: impl Into<String>) -> Self {
+        let search = self.config.search.get_or_insert_with(SearchConfig::default);
+        search.provider = Some(provider.into());
+        search.search_url = Some(search_url.into());
+        self
+    }
+
+    /// `[search] suggest_url`.
+    pub fn suggest_url(mut self, url: impl Into<String>) -> Self {
+        let search = self.config.search.get_or_insert_with(SearchConfig::default);
+        search.suggest_url = Some(url.into());
+        self
+    }
+
+    /// `[search] suggestions`: suggestions as you type in the address bar.
+    pub fn search_suggestions(mut self, enabled: bool) -> Self {
+        let search = self.config.search.get_or_insert_with(SearchConfig::default);
+        search.suggestions = Some(enabled);
+        self
+    }
+
+    pub fn bing_suggestions(mut self, enabled: bool) -> Self {
+        let search = self.config.search.get_or_insert_with(SearchConfig::default);
+        search.bing_suggestions = Some(enabled);
+        self
+    }
+
+    /// `[search] sidebar_search`, e.g. `"disabled"`.
+    pub fn sidebar_search(mut self, mode: impl Into<String>) -> Self {
+        let search = self.config.search.get_or_insert_with(SearchConfig::default);
+        search.sidebar_search = Some(mode.into());
+        self
+    }
+
+    /// Force-install an extension: `edge:ID`, `chrome:ID`, or a bare Edge
+    /// Add-ons ID.
+    pub fn extension(mut self, spec: impl Into<String>) -> Self {
+        let spec = spec.into();
+        self.config
+            .extensions
+            .get_or_insert_with(Default::default)
+            .insert(spec.clone(), toml::Value::String(spec));
+        self
+    }
+
+    /// `[extensions] blocklist`: IDs that may not be installed, or `"*"`
+    /// for all but the allowlist and forcelist.
+    pub fn extension_blocklist<I, S>(self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extension_list("blocklist", ids)
+    }
+
+    /// `[extensions] allowlist`: exceptions to a `"*"` blocklist.
+    pub fn extension_allowlist<I, S>(self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extension_list("allowlist", ids)
+    }
+
+    /// `[extensions] sources`: URL patterns Edge may install extensions
+    /// and user scripts from, e.g. `"https://extensions.example/*"`.
+    pub fn extension_sources<I, S>(self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extension_list("sources", patterns)
+    }
+
+    fn extension_list<I, S>(mut self, key: &str, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let ids: Vec<toml::Value> = ids
+            .into_iter()
+            .map(|id| toml::Value::String(id.into()))
+            .collect();
+        self.config
+            .extensions
+            .get_or_insert_with(Default::default)
+            .insert(key.to_owned(), toml::Value::Array(ids));
+        self
+    }
+
+    /// `[extension_settings]` for an extension spec, or `"*"` for the
+    /// default.
+    pub fn extension_setting(mut self, spec: impl Into<String>, setting: ExtensionSetting) -> Self {
+        self.config
+            .extension_settings
+            .get_or_insert_with(Default::default)
+            .insert(spec.into(), setting);
+        self
+    }
+
+    /// `[browser] restore_on_startup`, e.g. `"previous_session"`.
+    pub fn restore_on_startup(mut self, mode: impl Into<String>) -> Self {
+        self.browser().restore_on_startup = Some(mode.into());
+        self
+    }
+
+    /// `[browser] startup_urls`, opened when `restore_on_startup` is `"urls"`.
+    pub fn startup_urls<I, S>(mut self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.browser().startup_urls = Some(urls.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// `[browser] inprivate`: `"enabled"`, `"disabled"` or `"forced"`.
+    pub fn inprivate(mut self, availability: impl Into<String>) -> Self {
+        self.browser().inprivate = Some(availability.into());
+        self
+    }
+
+    /// `[browser] default_browser_prompt`; `false` stops Edge asking to be
+    /// the default browser.
+    pub fn default_browser_prompt(mut self, enabled: bool) -> Self {
+        self.browser().default_browser_prompt = Some(enabled);
+        self
+    }
+
+    pub fn show_home_button(mut self, enabled: bool) -> Self {
+        self.browser().show_home_button = Some(enabled);
+        self
+    }
+
+    /// `[browser] homepage`, the URL the home button opens.
+    pub fn homepage(mut self, url: impl Into<String>) -> Self {
+        self.browser().homepage = Some(url.into());
+        self
+    }
+
+    pub fn homepage_is_new_tab(mut self, enabled: bool) -> Self {
+        self.browser().homepage_is_new_tab = Some(enabled);
+        self
+    }
+
+    /// `[browser] new_tab_page`, a URL to show in new tabs.
+    pub fn new_tab_page(mut self, url: impl Into<String>) -> Self {
+        self.browser().new_tab_page = Some(url.into());
+        self
+    }
+
+    pub fn favorites_bar(mut self, enabled: bool) -> Self {
+        self.browser().favorites_bar = Some(enabled);
+        self
+    }
+
+    pub fn hide_first_run(mut self, hide: bool) -> Self {
+        self.browser().hide_first_run = Some(hide);
+        self
+    }
+
+    /// `[browser] always_open_pdf_externally`; `true` leaves PDFs to the
+    /// system's PDF app.
+    pub fn always_open_pdf_externally(mut self, enabled: bool) -> Self {
+        self.browser().always_open_pdf_externally = Some(enabled);
+        self
+    }
+
+    pub fn restore_pdf_view(mut self, enabled: bool) -> Self {
+        self.browser().restore_pdf_view = Some(enabled);
+        self
+    }
+
+    /// `[browser] relaunch_notification`: `"recommended"` or `"required"`.
+    pub fn relaunch_notification(mut self, level: impl Into<String>) -> Self {
+        self.browser().relaunch_notification = Some(level.into());
+        self
+    }
+
+    /// `[browser] relaunch_notification_period`, in hours.
+    pub fn relaunch_notification_period(mut self, hours: u32) -> Self {
+        self.browser().relaunch_notification_period = Some(hours);
+        self
+    }
+
+    /// `[privacy] tracking_prevention`, e.g. `"strict"`.
+    pub fn tracking_prevention(mut self, level: impl Into<String>) -> Self {
+        self.privacy().tracking_prevention = Some(level.into());
+        self
+    }
+
+    pub fn password_manager(mut self, enabled: bool) -> Self {
+        self.privacy().password_manager = Some(enabled);
+        self
+    }
+
+    pub fn autofill_credit_card(mut self, enabled: bool) -> Self {
+        self.privacy().autofill_credit_card = Some(enabled);
+        self
+    }
+
+    pub fn autofill_address(mut self, enabled: bool) -> Self {
+        self.privacy().autofill_address = Some(enabled);
+        self
+    }
+
+    /// `[downloads] default_directory`; `%VAR%`s Edge has a `${...}`
+    /// variable for are rewritten to it.
+    pub fn download_directory(mut self, dir: impl Into<String>) -> Self {
+        self.downloads().default_directory = Some(dir.into());
+        self
+    }
+
+    pub fn prompt_for_download_location(mut self, enabled: bool) -> Self {
+        self.downloads().prompt_for_location = Some(enabled);
+        self
+    }
+
+    /// `[downloads] restrictions`, e.g. `"block_dangerous"`.
+    pub fn download_restrictions(mut self, level: impl Into<String>) -> Self {
+        self.downloads().restrictions = Some(level.into());
+        self
+    }
+
+    pub fn smartscreen(mut self, enabled: bool) -> Self {
+        self.security().smartscreen = Some(enabled);
+        self
+    }
+
+    pub fn smartscreen_pua(mut self, enabled: bool) -> Self {
+        self.security().smartscreen_pua = Some(enabled);
+        self
+    }
+
+    pub fn prevent_smartscreen_override(mut self, enabled: bool) -> Self {
+        self.security().prevent_smartscreen_override = Some(enabled);
+        self
+    }
+
+    pub fn site_per_process(mut self, enabled: bool) -> Self {
+        self.security().site_per_process = Some(enabled);
+        self
+    }
+
+    /// `[security] min_tls_version`, e.g. `"tls1.2"`.
+    pub fn min_tls_version(mut self, version: impl Into<String>) -> Self {
+        self.security().min_tls_version = Some(version.into());
+        self
+    }
+
+    pub fn insecure_forms_warnings(mut self, enabled: bool) -> Self {
+        self.security().insecure_forms_warnings = Some(enabled);
+        self
+    }
+
+    /// `[security] ssl_error_override`; `false` blocks clicking through
+    /// certificate errors.
+    pub fn ssl_error_override(mut self, allowed: bool) -> Self {
+        self.security().ssl_error_override = Some(allowed);
+        self
+    }
+
+    /// `[privacy] dns_over_https`: `"off"`, `"automatic"` or `"secure"`.
+    pub fn dns_over_https(mut self, mode: impl Into<String>) -> Self {
+        self.privacy().dns_over_https = Some(mode.into());
+        self
+    }
+
+    pub fn dns_over_https_templates<I, S>(mut self, templates: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.privacy().dns_over_https_templates =
+            Some(templates.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// `[privacy] clear_on_exit`: `["all"]`, or `["cache"]` for just the
+    /// cache.
+    pub fn clear_on_exit<I, S>(mut self, kinds: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.privacy().clear_on_exit = Some(kinds.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn password_monitor(mut self, allowed: bool) -> Self {
+        self.privacy().password_monitor = Some(allowed);
+        self
+    }
+
+    /// `[privacy] password_protection_warning_trigger`: `"off"` or
+    /// `"password_reuse"`.
+    pub fn password_protection_warning_trigger(mut self, trigger: impl Into<String>) -> Self {
+        self.privacy().password_protection_warning_trigger = Some(trigger.into());
+        self
+    }
+
+    pub fn password_generation(mut self, enabled: bool) -> Self {
+        self.privacy().password_generation = Some(enabled);
+        self
+    }
+
+    /// `[privacy] webrtc_ip_handling`, e.g. `"disable_non_proxied_udp"`.
+    pub fn webrtc_ip_handling(mut self, handling: impl Into<String>) -> Self {
+        self.privacy().webrtc_ip_handling = Some(handling.into());
+        self
+    }
+
+    /// `[content] cookies`, e.g. `"block"`; likewise for the other
+    /// content settings below.
+    pub fn cookies_setting(mut self, choice: impl Into<String>) -> Self {
+        self.content().cookies = Some(choice.into());
+        self
+    }
+
+    pub fn javascript_setting(mut self, choice: impl Into<String>) -> Self {
+        self.content().javascript = Some(choice.into());
+        self
+    }
+
+    pub fn popups_setting(mut self, choice: impl Into<String>) -> Self {
+        self.content().popups = Some(choice.into());
+        self
+    }
+
+    pub fn notifications_setting(mut self, choice: impl Into<String>) -> Self {
+        self.content().notifications = Some(choice.into());
+        self
+    }
+
+    pub fn geolocation_setting(mut self, choice: impl Into<String>) -> Self {
+        self.content().geolocation = Some(choice.into());
+        self
+    }
+
+    pub fn images_setting(mut self, choice: impl Into<String>) -> Self {
+        self.content().images = Some(choice.into());
+        self
+    }
+
+    /// `[content.allow]`: sites exempt from a setting's default, e.g.
+    /// `allow_sites("cookies", ["[*.]example.com"])`. A `setting` that isn't
+    /// one of the `[content]` settings is an error from
+    /// [`PolicySet::check`].
+    pub fn allow_sites<I, S>(mut self, setting: &str, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if !is_site_setting(setting) {
+            self.unknown_keys.push(format!("content.allow.{setting}"));
+            return self;
+        }
+        let sites = self.content().allow.get_or_insert_with(SiteLists::default);
+        set_sites(sites, setting, urls);
+        self
+    }
+
+    /// `[content.block]`, like [`PolicySet::allow_sites`].
+    pub fn block_sites<I, S>(mut self, setting: &str, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if !is_site_setting(setting) {
+            self.unknown_keys.push(format!("content.block.{setting}"));
+            return self;
+        }
+        let sites = self.content().block.get_or_insert_with(SiteLists::default);
+        set_sites(sites, setting, urls);
+        self
+    }
+
+    /// `[content] cookies_session_only`: sites whose cookies last until
+    /// Edge closes.
+    pub fn session_only_cookies<I, S>(mut self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.content().cookies_session_only = Some(urls.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// `[urls] block`: URL patterns Edge refuses to load.
+    pub fn block_urls<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.urls().block = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// `[urls] allow`: exceptions to [`PolicySet::block_urls`].
+    pub fn allow_urls<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.urls().allow = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// `[account] sync_disabled`.
+    pub fn sync_disabled(mut self, disabled: bool) -> Self {
+        self.account().sync_disabled = Some(disabled);
+        self
+    }
+
+    /// `[account] browser_signin`: `"disabled"`, `"enabled"` or `"forced"`.
+    pub fn browser_signin(mut self, mode: impl Into<String>) -> Self {
+        self.account().browser_signin = Some(mode.into());
+        self
+    }
+
+    pub fn non_removable_profile(mut self, enabled: bool) -> Self {
+        self.account().non_removable_profile_enabled = Some(enabled);
+        self
+    }
+
+    pub fn implicit_signin(mut self, enabled: bool) -> Self {
+        self.account().implicit_signin_enabled = Some(enabled);
+        self
+    }
+
+    pub fn sleeping_tabs(mut self, enabled: bool) -> Self {
+        self.performance().sleeping_tabs = Some(enabled);
+        self
+    }
+
+    /// `[performance] sleeping_tabs_timeout`, e.g. `"15m"`.
+    pub fn sleeping_tabs_timeout(mut self, timeout: impl Into<String>) -> Self {
+        self.performance().sleeping_tabs_timeout = Some(timeout.into());
+        self
+    }
+
+    pub fn startup_boost(mut self, enabled: bool) -> Self {
+        self.performance().startup_boost = Some(enabled);
+        self
+    }
+
+    pub fn background_mode(mut self, enabled: bool) -> Self {
+        self.performance().background_mode = Some(enabled);
+        self
+    }
+
+    /// `[performance] efficiency_mode`, e.g. `"active_when_unplugged"`.
+    pub fn efficiency_mode(mut self, mode: impl Into<String>) -> Self {
+        self.performance().efficiency_mode = Some(mode.into());
+        self
+    }
+
+    pub fn vertical_tabs(mut self, allowed: bool) -> Self {
+        self.appearance().vertical_tabs = Some(allowed);
+        self
+    }
+
+    pub fn sidebar(mut self, enabled: bool) -> Self {
+        self.appearance().sidebar = Some(enabled);
+        self
+    }
+
+    pub fn new_tab_content(mut self, enabled: bool) -> Self {
+        self.appearance().new_tab_content = Some(enabled);
+        self
+    }
+
+    pub fn new_tab_quick_links(mut self, enabled: bool) -> Self {
+        self.appearance().new_tab_quick_links = Some(enabled);
+        self
+    }
+
+    /// `[appearance] new_tab_background`, e.g. `"disable_all"`.
+    pub fn new_tab_background(mut self, types: impl Into<String>) -> Self {
+        self.appearance().new_tab_background = Some(types.into());
+        self
+    }
+
+    /// A `[features]` toggle by key, e.g. `feature("shopping_assistant", false)`.
+    /// A `key` that isn't one of the `[features]` keys is an error from
+    /// [`PolicySet::check`].
+    pub fn feature(mut self, key: &str, enabled: bool) -> Self {
+        let full = format!("features.{key}");
+        let Some(policy) = config_key_policy(&full) else {
+            self.unknown_keys.push(full);
+            return self;
+        };
+        let features = self
+            .config
+            .features
+            .get_or_insert_with(FeaturesConfig::default);
+        match features.toggle_mut(policy) {
+            Some(toggle) => *toggle = Some(enabled),
+            None => self.unknown_keys.push(full),
+        }
+        self
+    }
+
+    /// `[telemetry] diagnostic_data`: `"off"`, `"required"` or `"optional"`.
+    pub fn diagnostic_data(mut self, level: impl Into<String>) -> Self {
+        self.telemetry().diagnostic_data = Some(level.into());
+        self
+    }
+
+    pub fn personalization_reporting(mut self, enabled: bool) -> Self {
+        self.telemetry().personalization_reporting = Some(enabled);
+        self
+    }
+
+    pub fn user_feedback_allowed(mut self, allowed: bool) -> Self {
+        self.telemetry().user_feedback_allowed = Some(allowed);
+        self
+    }
+
+    /// `[telemetry] experimentation_and_configuration_service`, e.g.
+    /// `"restricted"`.
+    pub fn experimentation_and_configuration_service(mut self, level: impl Into<String>) -> Self {
+        self.telemetry().experimentation_and_configuration_service = Some(level.into());
+        self
+    }
+
+    pub fn translate(mut self, enabled: bool) -> Self {
+        self.language().translate = Some(enabled);
+        self
+    }
+
+    pub fn spellcheck(mut self, enabled: bool) -> Self {
+        self.language().spellcheck = Some(enabled);
+        self
+    }
+
+    /// `[language] spellcheck_languages`, e.g. `["en-US", "de"]`.
+    pub fn spellcheck_languages<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.language().spellcheck_languages =
+            Some(languages.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn spellcheck_language_blocklist<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.language().spellcheck_language_blocklist =
+            Some(languages.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// `[advanced] developer_tools`, e.g. `"disallowed"`.
+    pub fn developer_tools(mut self, availability: impl Into<String>) -> Self {
+        self.advanced().developer_tools = Some(availability.into());
+        self
+    }
+
+    pub fn add_profile(mut self, enabled: bool) -> Self {
+        self.advanced().add_profile = Some(enabled);
+        self
+    }
+
+    pub fn guest_mode(mut self, enabled: bool) -> Self {
+        self.advanced().guest_mode = Some(enabled);
+        self
+    }
+
+    pub fn command_line_flag_warnings(mut self, enabled: bool) -> Self {
+        self.advanced().command_line_flag_warnings = Some(enabled);
+        self
+    }
+
+    /// `[ie_mode] integration_level`, e.g. `"ie_mode"`.
+    pub fn ie_integration_level(mut self, level: impl Into<String>) -> Self {
+        self.ie_mode().integration_level = Some(level.into());
+        self
+    }
+
+    /// `[ie_mode] site_list`, the Enterprise Mode Site List's location.
+    pub fn ie_site_list(mut self, location: impl Into<String>) -> Self {
+        self.ie_mode().site_list = Some(location.into());
+        self
+    }
+
+    /// An `[import]` toggle by key, e.g. `import("passwords", false)`. A
+    /// `key` that isn't one of the `[import]` keys is an error from
+    /// [`PolicySet::check`].
+    pub fn import(mut self, key: &str, allowed: bool) -> Self {
+        let full = format!("import.{key}");
+        let Some(policy) = config_key_policy(&full) else {
+            self.unknown_keys.push(full);
+            return self;
+        };
+        let import = self.config.import.get_or_insert_with(ImportConfig::default);
+        match import.toggle_mut(policy) {
+            Some(toggle) => *toggle = Some(allowed),
+            None => self.unknown_keys.push(full),
+        }
+        self
+    }
+
+    /// `[favorites] toplevel_name`: the folder managed favorites appear in.
+    pub fn favorites_folder(mut self, name: impl Into<String>) -> Self {
+        self.favorites().toplevel_name = Some(name.into());
+        self
+    }
+
+    /// Add to `[favorites] items`, e.g.
+    /// `favorite(Favorite::link("Docs", "https://docs.example"))`.
+    pub fn favorite(mut self, favorite: Favorite) -> Self {
+        self.favorites().items.push(favorite);
+        self
+    }
+
+    /// `[raw]`: any policy by name. Booleans and integers become DWORDs,
+    /// strings REG_SZ, and string arrays numbered list subkeys.
+    pub fn raw(mut self, name: impl Into<String>, value: impl Into<toml::Value>) -> Self {
+        self.config
+            .raw
+            .get_or_insert_with(Default::default)
+            .insert(name.into(), value.into());
+        self
+    }
+
+    /// The registry entries these settings produce.
+    pub fn entries(&self) -> Vec<PolicyEntry> {
+        policy::build_entries(&self.config)
+    }
+
+    /// Catalog and scope checks, as `edge-profile validate` runs them.
+    pub fn check(&self, scope: Scope) -> Vec<Issue> {
+        let entries = self.entries();
+        let mut issues: Vec<Issue> = self
+            .unknown_keys
+            .iter()
+            .map(|key| Issue {
+                severity: validate::Severity::Error,
+                message: format!("{key} is not a config key, so it was ignored"),
+            })
+            .collect();
+        issues.extend(validate::check(&self.config, &entries));
+        issues.extend(validate::check_scope(&entries, scope));
+        issues
+    }
+
+    /// The equivalent config file.
+    pub fn to_toml(&self) -> Result<String> {
+        config::to_toml(&self.config)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn into_config(self) -> Config {
+        self.config
+    }
+
+    fn browser(&mut self) -> &mut BrowserConfig {
+        self.config
+            .browser
+            .get_or_insert_with(BrowserConfig::default)
+    }
+
+    fn privacy(&mut self) -> &mut PrivacyConfig {
+        self.config
+            .privacy
+            .get_or_insert_with(PrivacyConfig::default)
+    }
+
+    fn downloads(&mut self) -> &mut DownloadsConfig {
+        self.config
+            .downloads
+            .get_or_insert_with(DownloadsConfig::default)
+    }
+
+    fn security(&mut self) -> &mut SecurityConfig {
+        self.config
+            .security
+            .get_or_insert_with(SecurityConfig::default)
+    }
+
+    fn content(&mut self) -> &mut ContentConfig {
+        self.config
+            .content
+            .get_or_insert_with(ContentConfig::default)
+    }
+
+    fn account(&mut self) -> &mut AccountConfig {
+        self.config
+            .account
+            .get_or_insert_with(AccountConfig::default)
+    }
+
+    fn performance(&mut self) -> &mut PerformanceConfig {
+        self.config
+            .performance
+            .get_or_insert_with(PerformanceConfig::default)
+    }
+
+    fn appearance(&mut self) -> &mut AppearanceConfig {
+        self.config
+            .appearance
+            .get_or_insert_with(AppearanceConfig::default)
+    }
+
+    fn telemetry(&mut self) -> &mut TelemetryConfig {
+        self.config
+            .telemetry
+            .get_or_insert_with(TelemetryConfig::default)
+    }
+
+    fn language(&mut self) -> &mut LanguageConfig {
+        self.config
+            .language
+            .get_or_insert_with(LanguageConfig::default)
+    }
+
+    fn advanced(&mut self) -> &mut AdvancedConfig {
+        self.config
+            .advanced
+            .get_or_insert_with(AdvancedConfig::default)
+    }
+
+    fn ie_mode(&mut self) -> &mut IeModeConfig {
+        self.config
+            .ie_mode
+            .get_or_insert_with(IeModeConfig::default)
+    }
+
+    fn favorites(&mut self) -> &mut FavoritesConfig {
+        self.config
+            .favorites
+            .get_or_insert_with(FavoritesConfig::default)
+    }
+
+    fn urls(&mut self) -> &mut UrlsConfig {
+        self.config.urls.get_or_insert_with(UrlsConfig::default)
+    }
+}
+
+/// The policy a config key writes.
+fn config_key_policy(key: &str) -> Option<&'static str> {
+    policy::CONFIG_KEYS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, policy)| *policy)
+}
+
+fn is_site_setting(setting: &str) -> bool {
+    SiteLists::default().list_mut(setting).is_some()
+}
+
+fn set_sites<I, S>(sites: &mut SiteLists, setting: &str, urls: I)
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    if let Some(list) = sites.list_mut(setting) {
+        *list = Some(urls.into_iter().map(Into::into).collect());
+    }
+}
+
+impl From<Config> for PolicySet {
+    fn from(config: Config) -> Self {
+        Self {
+            config,
+            unknown_keys: Vec::new(),
+        }
+    }
+}
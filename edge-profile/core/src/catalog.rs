@@ -0,0 +1,196 @@
+//! Embedded catalog of Edge policies, generated from `catalog/policies.toml`
+//! and, when the build finds them, Microsoft's administrative templates.
+
+/// Edge policy data types, as listed in Microsoft's policy reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyKind {
+    /// REG_DWORD 0 or 1.
+    Boolean,
+    /// REG_DWORD, optionally restricted to an enumerated set or range.
+    Integer,
+    /// REG_SZ.
+    String,
+    /// Subkey of numbered REG_SZ values.
+    List,
+    /// REG_SZ holding a JSON object.
+    Dictionary,
+}
+
+impl PolicyKind {
+    pub fn registry_type(self) -> &'static str {
+        match self {
+            PolicyKind::Boolean | PolicyKind::Integer => "REG_DWORD",
+            PolicyKind::String | PolicyKind::Dictionary => "REG_SZ",
+            PolicyKind::List => "list of REG_SZ",
+        }
+    }
+}
+
+/// Syntax of string values, for policies where Edge expects more than free text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueFormat {
+    /// Absolute http(s) URL.
+    Url,
+    /// URL filter pattern such as `[*.]example.com` or `https://host/*`.
+    UrlPattern,
+    /// `extension_id;update_url` forcelist entry.
+    Forcelist,
+}
+
+#[derive(Debug)]
+pub struct PolicyDef {
+    pub name: &'static str,
+    pub kind: PolicyKind,
+    /// One-line description from Microsoft's policy reference.
+    pub summary: &'static str,
+    /// Functional group, e.g. `privacy`; the catalog file is ordered by it.
+    pub category: &'static str,
+    /// First Edge major version that honors the policy.
+    pub since: u32,
+    /// First Edge major version that no longer honors it.
+    pub removed: Option<u32>,
+    /// Syntax of REG_SZ values (string and list policies).
+    pub format: Option<ValueFormat>,
+    /// Policy that supersedes this one, if it is deprecated.
+    pub replaced_by: Option<&'static str>,
+    /// Edge ignores the policy when it is set under HKCU.
+    pub machine_only: bool,
+    /// Permitted DWORDs and their meaning (integer policies).
+    pub values: &'static [(u32, &'static str)],
+    /// Permitted DWORD range when there is no fixed set (integer policies).
+    pub range: Option<(u32, u32)>,
+    /// Permitted strings (string policies); empty means free-form.
+    pub allowed: &'static [&'static str],
+}
+
+impl PolicyDef {
+    /// Inclusive DWORD bounds for policies without an enumerated value set.
+    pub fn dword_bounds(&self) -> Option<(u32, u32)> {
+        match self.kind {
+            PolicyKind::Boolean => Some((0, 1)),
+            PolicyKind::Integer => self.range,
+            _ => None,
+        }
+    }
+
+    /// DWORD for an enumerated value name, e.g. `"strict"` -> 3.
+    pub fn value_of(&self, name: &str) -> Option<u32> {
+        self.values
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(v, _)| *v)
+    }
+
+    /// Meaning of an enumerated DWORD.
+    pub fn value_name(&self, value: u32) -> Option<&'static str> {
+        self.values
+            .iter()
+            .find(|(v, _)| *v == value)
+            .map(|(_, n)| *n)
+    }
+
+    /// `1 (previous_session), 4 (urls), ...` for error messages.
+    pub fn describe_values(&self) -> String {
+        self.values
+            .iter()
+            .map(|(v, n)| format!("{v} ({n})"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Anchor into Microsoft's Edge policy reference.
+    pub fn docs_url(&self) -> String {
+        format!(
+            "https://learn.microsoft.com/en-us/deployedge/microsoft-edge-policies#{}",
+            self.name.to_ascii_lowercase()
+        )
+    }
+
+    /// The replacement for a deprecated policy, if it can be used as a drop-in
+    /// rename (same data type, so the existing value carries over).
+    pub fn drop_in_replacement(&self) -> Option<&'static PolicyDef> {
+        let new = lookup(self.replaced_by?)?;
+        (new.kind == self.kind).then_some(new)
+    }
+}
+
+// `FROM_TEMPLATES`, whether the build found Microsoft's templates and so
+// covers every policy, and `POLICIES`.
+include!(concat!(env!("OUT_DIR"), "/catalog.rs"));
+
+/// Find a policy by name. Registry value names are case-insensitive, so
+/// lookups are too.
+pub fn lookup(name: &str) -> Option<&'static PolicyDef> {
+    POLICIES.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// The closest catalog names to an unknown one, best first, for "did you
+/// mean" hints. Empty if nothing is close enough to be a likely typo.
+pub fn suggest(name: &str) -> Vec<&'static str> {
+    let name = name.to_ascii_lowercase();
+    let max = (name.len() / 4).max(2);
+    let mut close: Vec<(usize, &str)> = POLICIES
+        .iter()
+        .filter_map(|p| {
+            let d = edit_distance(&name, &p.name.to_ascii_lowercase());
+            (d <= max).then_some((d, p.name))
+        })
+        .collect();
+    close.sort();
+    close.into_iter().take(3).map(|(_, n)| n).collect()
+}
+
+/// Levenshtein distance over bytes; policy names are ASCII.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// ` (did you mean X?)` for an unknown name, or an empty string.
+pub fn did_you_mean(name: &str) -> String {
+    match suggest(name).as_slice() {
+        [] => String::new(),
+        [one] => format!(" (did you mean {one}?)"),
+        [rest @ .., last] => format!(" (did you mean {} or {last}?)", rest.join(", ")),
+    }
+}
+
+/// Categories in catalog order.
+pub fn categories() -> Vec<&'static str> {
+    let mut categories: Vec<&str> = Vec::new();
+    for p in POLICIES {
+        if !categories.contains(&p.category) {
+            categories.push(p.category);
+        }
+    }
+    categories
+}
+
+/// Heading for a category, e.g. `ie_mode` -> `IE mode`.
+pub fn category_title(category: &str) -> String {
+    match category {
+        "ie_mode" => "IE mode".to_owned(),
+        "urls" => "URLs".to_owned(),
+        _ => {
+            let mut title = category.replace('_', " ");
+            if let Some(first) = title.get_mut(..1) {
+                first.make_ascii_uppercase();
+            }
+            title
+        }
+    }
+}
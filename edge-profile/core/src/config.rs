@@ -0,0 +1,583 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    pub search: Option<SearchConfig>,
+    /// Extensions to force-install, by any name, plus the reserved
+    /// `blocklist` and `allowlist` arrays of IDs (or `"*"`) and the
+    /// `sources` array of URL patterns.
+    pub extensions: Option<toml::map::Map<String, toml::Value>>,
+    /// `ExtensionSettings` per extension spec, or `"*"` for the default.
+    pub extension_settings: Option<BTreeMap<String, ExtensionSetting>>,
+    pub browser: Option<BrowserConfig>,
+    pub privacy: Option<PrivacyConfig>,
+    pub telemetry: Option<TelemetryConfig>,
+    pub downloads: Option<DownloadsConfig>,
+    pub security: Option<SecurityConfig>,
+    pub content: Option<ContentConfig>,
+    pub urls: Option<UrlsConfig>,
+    pub favorites: Option<FavoritesConfig>,
+    pub account: Option<AccountConfig>,
+    pub performance: Option<PerformanceConfig>,
+    pub appearance: Option<AppearanceConfig>,
+    pub features: Option<FeaturesConfig>,
+    pub language: Option<LanguageConfig>,
+    pub advanced: Option<AdvancedConfig>,
+    pub import: Option<ImportConfig>,
+    pub ie_mode: Option<IeModeConfig>,
+    /// Policies without a first-class config key, written as-is.
+    pub raw: Option<toml::map::Map<String, toml::Value>>,
+    pub hooks: Option<HooksConfig>,
+    pub report: Option<ReportConfig>,
+}
+
+/// A config table that writes policies, for applying part of a config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Section {
+    Search,
+    Extensions,
+    Browser,
+    Privacy,
+    Telemetry,
+    Downloads,
+    Security,
+    Content,
+    Urls,
+    Favorites,
+    Account,
+    Performance,
+    Appearance,
+    Features,
+    Language,
+    Advanced,
+    Import,
+    IeMode,
+    Raw,
+}
+
+impl Section {
+    /// The table that writes a policy: the one holding its config key, or
+    /// `[raw]` for everything else.
+    pub fn of(policy: &str) -> Section {
+        let key = crate::policy::config_key(policy).unwrap_or("raw");
+        match key.split('.').next() {
+            Some("search") => Section::Search,
+            Some("extensions" | "extension_settings") => Section::Extensions,
+            Some("browser") => Section::Browser,
+            Some("privacy") => Section::Privacy,
+            Some("telemetry") => Section::Telemetry,
+            Some("downloads") => Section::Downloads,
+            Some("security") => Section::Security,
+            Some("content") => Section::Content,
+            Some("urls") => Section::Urls,
+            Some("favorites") => Section::Favorites,
+            Some("account") => Section::Account,
+            Some("performance") => Section::Performance,
+            Some("appearance") => Section::Appearance,
+            Some("features") => Section::Features,
+            Some("language") => Section::Language,
+            Some("advanced") => Section::Advanced,
+            Some("import") => Section::Import,
+            Some("ie_mode") => Section::IeMode,
+            _ => Section::Raw,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SearchConfig {
+    pub provider: Option<String>,
+    pub search_url: Option<String>,
+    pub suggest_url: Option<String>,
+    /// Suggestions as you type in the address bar.
+    pub suggestions: Option<bool>,
+    /// Microsoft Search in Bing results among the address bar suggestions.
+    pub bing_suggestions: Option<bool>,
+    /// `"enabled"`, `"disabled"` or `"disabled_for_kids_mode"`.
+    pub sidebar_search: Option<String>,
+}
+
+impl SearchConfig {
+    /// Whether the table configures a default provider, rather than only
+    /// the suggestion settings.
+    pub fn sets_provider(&self) -> bool {
+        self.provider.is_some() || self.search_url.is_some() || self.suggest_url.is_some()
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct BrowserConfig {
+    pub restore_on_startup: Option<String>,
+    /// Sites opened at startup when `restore_on_startup` is `"urls"` or
+    /// `"previous_session_and_urls"`.
+    pub startup_urls: Option<Vec<String>>,
+    pub show_home_button: Option<bool>,
+    /// Where the home button and Alt+Home go.
+    pub homepage: Option<String>,
+    /// Make the new tab page the home page; overrides `homepage`.
+    pub homepage_is_new_tab: Option<bool>,
+    /// Page shown in new tabs instead of Edge's own.
+    pub new_tab_page: Option<String>,
+    pub favorites_bar: Option<bool>,
+    pub hide_first_run: Option<bool>,
+    /// `"enabled"`, `"disabled"` or `"forced"` (every window is InPrivate).
+    pub inprivate: Option<String>,
+    /// `false` stops Edge asking to be the default browser. `true` only
+    /// allows the prompts; it doesn't make Edge register itself.
+    pub default_browser_prompt: Option<bool>,
+    /// Hand PDFs to the system's default PDF app instead of opening them in
+    /// Edge.
+    pub always_open_pdf_externally: Option<bool>,
+    /// Reopen a PDF at the page and zoom it was left at.
+    pub restore_pdf_view: Option<bool>,
+    /// After an update, `"recommended"` nags the user to restart Edge and
+    /// `"required"` restarts it at the end of the notification period.
+    pub relaunch_notification: Option<String>,
+    /// Length of that period in hours, from 1 to 1193.
+    pub relaunch_notification_period: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DownloadsConfig {
+    /// Edge expands its own `${user_home}` style variables; `%USERPROFILE%`
+    /// and the other `%VAR%`s it has an equivalent for are rewritten to them.
+    pub default_directory: Option<String>,
+    pub prompt_for_location: Option<bool>,
+    /// A `DownloadRestrictions` value name, e.g. `"block_dangerous"`.
+    pub restrictions: Option<String>,
+}
+
+/// Microsoft Defender SmartScreen.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SecurityConfig {
+    pub smartscreen: Option<bool>,
+    /// Also block potentially unwanted apps.
+    pub smartscreen_pua: Option<bool>,
+    /// Take away the option to continue past a SmartScreen warning.
+    pub prevent_smartscreen_override: Option<bool>,
+    /// Give every site its own process, not just the ones Edge picks.
+    pub site_per_process: Option<bool>,
+    /// Oldest TLS version Edge will connect with: `"tls1"`, `"tls1.1"` or
+    /// `"tls1.2"`.
+    pub min_tls_version: Option<String>,
+    /// Warn before submitting a form from a secure page over http.
+    pub insecure_forms_warnings: Option<bool>,
+    /// `false` takes away the option to continue past a certificate error.
+    pub ssl_error_override: Option<bool>,
+}
+
+/// What sites may do unless a per-site policy says otherwise. Each takes
+/// the catalog's value names: `"allow"`, `"block"`, and where the policy
+/// has them `"ask"` or (for cookies) `"session_only"`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ContentConfig {
+    pub cookies: Option<String>,
+    pub javascript: Option<String>,
+    pub popups: Option<String>,
+    pub notifications: Option<String>,
+    pub geolocation: Option<String>,
+    pub images: Option<String>,
+    /// Sites exempt from a setting's default, as `[content.allow]` and
+    /// `[content.block]`.
+    pub allow: Option<SiteLists>,
+    pub block: Option<SiteLists>,
+    /// Sites whose cookies are cleared when Edge closes.
+    pub cookies_session_only: Option<Vec<String>>,
+}
+
+/// URL patterns such as `"[*.]example.com"` for each content setting,
+/// written to `<Setting>AllowedForUrls` or `<Setting>BlockedForUrls`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SiteLists {
+    pub cookies: Option<Vec<String>>,
+    pub javascript: Option<Vec<String>>,
+    pub popups: Option<Vec<String>>,
+    pub notifications: Option<Vec<String>>,
+    pub geolocation: Option<Vec<String>>,
+    pub images: Option<Vec<String>>,
+}
+
+impl SiteLists {
+    /// Each list with the setting's name as it appears in policy names.
+    pub fn lists(&self) -> [(&'static str, &Option<Vec<String>>); 6] {
+        [
+            ("Cookies", &self.cookies),
+            ("JavaScript", &self.javascript),
+            ("Popups", &self.popups),
+            ("Notifications", &self.notifications),
+            ("Geolocation", &self.geolocation),
+            ("Images", &self.images),
+        ]
+    }
+
+    /// The list under a config key, e.g. `"cookies"`.
+    pub fn list_mut(&mut self, key: &str) -> Option<&mut Option<Vec<String>>> {
+        match key {
+            "cookies" => Some(&mut self.cookies),
+            "javascript" => Some(&mut self.javascript),
+            "popups" => Some(&mut self.popups),
+            "notifications" => Some(&mut self.notifications),
+            "geolocation" => Some(&mut self.geolocation),
+            "images" => Some(&mut self.images),
+            _ => None,
+        }
+    }
+}
+
+/// Which URLs Edge loads at all, as URL patterns. `allow` carves
+/// exceptions out of `block`, e.g. `block = ["*"]` with a short `allow`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UrlsConfig {
+    pub block: Option<Vec<String>>,
+    pub allow: Option<Vec<String>>,
+}
+
+/// Bookmarks pushed into Edge's favorites bar as `ManagedFavorites`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct FavoritesConfig {
+    /// Name of the folder holding them; Edge picks one if unset.
+    pub toplevel_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<Favorite>,
+}
+
+/// A bookmark, or with `children` a folder of them.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Favorite {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<Favorite>>,
+}
+
+impl Favorite {
+    pub fn link(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: Some(url.into()),
+            children: None,
+        }
+    }
+
+    pub fn folder(name: impl Into<String>, children: Vec<Favorite>) -> Self {
+        Self {
+            name: name.into(),
+            url: None,
+            children: Some(children),
+        }
+    }
+}
+
+/// Signing in to Edge and syncing with a Microsoft account.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AccountConfig {
+    pub sync_disabled: Option<bool>,
+    /// `"disabled"`, `"enabled"` or `"forced"`.
+    pub browser_signin: Option<String>,
+    /// Keep a profile signed in with the work or school account Windows
+    /// uses.
+    pub non_removable_profile_enabled: Option<bool>,
+    /// Sign in with the Windows account without asking.
+    pub implicit_signin_enabled: Option<bool>,
+}
+
+/// Sleeping tabs, efficiency mode, and whether Edge stays resident after
+/// its last window closes.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PerformanceConfig {
+    pub sleeping_tabs: Option<bool>,
+    /// How long a background tab idles before sleeping: `"30s"`, `"5m"`,
+    /// `"15m"`, `"30m"`, `"1h"`, `"2h"`, `"3h"`, `"6h"` or `"12h"`.
+    pub sleeping_tabs_timeout: Option<String>,
+    pub startup_boost: Option<bool>,
+    pub background_mode: Option<bool>,
+    /// An `EfficiencyMode` value name, e.g. `"active_when_unplugged"`.
+    pub efficiency_mode: Option<String>,
+}
+
+/// Browser chrome and new tab page layout. The favorites bar is under
+/// `[browser]`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AppearanceConfig {
+    pub vertical_tabs: Option<bool>,
+    pub sidebar: Option<bool>,
+    /// Microsoft News on the new tab page.
+    pub new_tab_content: Option<bool>,
+    pub new_tab_quick_links: Option<bool>,
+    /// Backgrounds to turn off: `"disable_daily_image"`,
+    /// `"disable_custom_image"` or `"disable_all"`.
+    pub new_tab_background: Option<String>,
+}
+
+/// Edge's bundled extras, each switched on or off.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct FeaturesConfig {
+    pub shopping_assistant: Option<bool>,
+    pub collections: Option<bool>,
+    /// Drop, for sending files and notes between devices.
+    pub drop: Option<bool>,
+    pub follow: Option<bool>,
+    pub wallet_etree: Option<bool>,
+    pub rewards: Option<bool>,
+    pub recommendations: Option<bool>,
+    pub spotlight: Option<bool>,
+    pub insider_promotion: Option<bool>,
+    /// The menu shown over selected text.
+    pub mini_menu: Option<bool>,
+    /// The Edge bar, a floating search and news widget.
+    pub edge_bar: Option<bool>,
+    pub edge_bar_on_startup: Option<bool>,
+}
+
+impl FeaturesConfig {
+    /// Each toggle with the policy it writes.
+    pub fn toggles(&self) -> [(&'static str, Option<bool>); 12] {
+        [
+            ("EdgeShoppingAssistantEnabled", self.shopping_assistant),
+            ("EdgeCollectionsEnabled", self.collections),
+            ("EdgeEDropEnabled", self.drop),
+            ("EdgeFollowEnabled", self.follow),
+            ("EdgeWalletEtreeEnabled", self.wallet_etree),
+            ("ShowMicrosoftRewards", self.rewards),
+            ("ShowRecommendationsEnabled", self.recommendations),
+            ("SpotlightExperiencesAndRecommendationsEnabled", self.spotlight),
+            ("MicrosoftEdgeInsiderPromotionEnabled", self.insider_promotion),
+            ("QuickSearchShowMiniMenu", self.mini_menu),
+            ("WebWidgetAllowed", self.edge_bar),
+            ("WebWidgetIsEnabledOnStartup", self.edge_bar_on_startup),
+        ]
+    }
+
+    /// The toggle that writes `policy`.
+    pub fn toggle_mut(&mut self, policy: &str) -> Option<&mut Option<bool>> {
+        match policy {
+            "EdgeShoppingAssistantEnabled" => Some(&mut self.shopping_assistant),
+            "EdgeCollectionsEnabled" => Some(&mut self.collections),
+            "EdgeEDropEnabled" => Some(&mut self.drop),
+            "EdgeFollowEnabled" => Some(&mut self.follow),
+            "EdgeWalletEtreeEnabled" => Some(&mut self.wallet_etree),
+            "ShowMicrosoftRewards" => Some(&mut self.rewards),
+            "ShowRecommendationsEnabled" => Some(&mut self.recommendations),
+            "SpotlightExperiencesAndRecommendationsEnabled" => Some(&mut self.spotlight),
+            "MicrosoftEdgeInsiderPromotionEnabled" => Some(&mut self.insider_promotion),
+            "QuickSearchShowMiniMenu" => Some(&mut self.mini_menu),
+            "WebWidgetAllowed" => Some(&mut self.edge_bar),
+            "WebWidgetIsEnabledOnStartup" => Some(&mut self.edge_bar_on_startup),
+            _ => None,
+        }
+    }
+}
+
+/// What Edge reports back to Microsoft.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TelemetryConfig {
+    /// `"off"`, `"required"` or `"optional"`.
+    pub diagnostic_data: Option<String>,
+    pub personalization_reporting: Option<bool>,
+    pub user_feedback_allowed: Option<bool>,
+    /// `"restricted"`, `"configurations_only"` or `"full"`.
+    pub experimentation_and_configuration_service: Option<String>,
+}
+
+/// Translation and spellcheck. Languages are tags such as `"en-US"`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct LanguageConfig {
+    pub translate: Option<bool>,
+    pub spellcheck: Option<bool>,
+    /// Languages spellchecked whatever the user picks.
+    pub spellcheck_languages: Option<Vec<String>>,
+    /// Languages never spellchecked.
+    pub spellcheck_language_blocklist: Option<Vec<String>>,
+}
+
+/// Developer tools and who can add profiles, for shared machines.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AdvancedConfig {
+    /// `"allowed"`, `"disallowed"` or `"allowed_except_policy_extensions"`.
+    pub developer_tools: Option<String>,
+    pub add_profile: Option<bool>,
+    pub guest_mode: Option<bool>,
+    /// Warn when Edge is started with unsupported command-line flags.
+    pub command_line_flag_warnings: Option<bool>,
+}
+
+/// Internet Explorer mode, for sites that still need Trident.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct IeModeConfig {
+    /// `"none"`, `"ie_mode"` (sites open in an Edge tab) or `"needie"` (in
+    /// Internet Explorer 11).
+    pub integration_level: Option<String>,
+    /// Where the Enterprise Mode Site List XML lives: an http(s) or
+    /// `file://` URL, or a UNC path.
+    pub site_list: Option<String>,
+}
+
+/// How Edge treats one extension, or all of them under `"*"`.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ExtensionSetting {
+    /// `"allowed"`, `"blocked"`, `"force_installed"`, `"normal_installed"`
+    /// or `"removed"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installation_mode: Option<String>,
+    /// `"force_shown"` pins the extension to the toolbar; `"default_shown"`
+    /// pins it until the user unpins it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toolbar_state: Option<String>,
+    /// Defaults to the store named by an `edge:` or `chrome:` spec.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_url: Option<String>,
+}
+
+/// What Edge may import from other browsers.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ImportConfig {
+    pub favorites: Option<bool>,
+    pub history: Option<bool>,
+    pub search_engine: Option<bool>,
+    pub passwords: Option<bool>,
+    /// Offer to import from other browsers every time Edge starts, not
+    /// just the first time.
+    pub on_each_launch: Option<bool>,
+}
+
+impl ImportConfig {
+    /// Each toggle with the policy it writes.
+    pub fn toggles(&self) -> [(&'static str, Option<bool>); 5] {
+        [
+            ("ImportFavorites", self.favorites),
+            ("ImportHistory", self.history),
+            ("ImportSearchEngine", self.search_engine),
+            ("ImportSavedPasswords", self.passwords),
+            ("ImportOnEachLaunch", self.on_each_launch),
+        ]
+    }
+
+    /// The toggle that writes `policy`.
+    pub fn toggle_mut(&mut self, policy: &str) -> Option<&mut Option<bool>> {
+        match policy {
+            "ImportFavorites" => Some(&mut self.favorites),
+            "ImportHistory" => Some(&mut self.history),
+            "ImportSearchEngine" => Some(&mut self.search_engine),
+            "ImportSavedPasswords" => Some(&mut self.passwords),
+            "ImportOnEachLaunch" => Some(&mut self.on_each_launch),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PrivacyConfig {
+    pub tracking_prevention: Option<String>,
+    pub password_manager: Option<bool>,
+    /// Alerts when a saved password turns up in a leak.
+    pub password_monitor: Option<bool>,
+    /// `"off"`, or `"password_reuse"` to warn when a protected password is
+    /// typed into another site.
+    pub password_protection_warning_trigger: Option<String>,
+    /// Strong password suggestions when signing up.
+    pub password_generation: Option<bool>,
+    pub autofill_credit_card: Option<bool>,
+    pub autofill_address: Option<bool>,
+    /// `"off"`, `"automatic"` or `"secure"`.
+    pub dns_over_https: Option<String>,
+    /// Resolver URI templates, e.g. `"https://dns.example/dns-query{?dns}"`.
+    pub dns_over_https_templates: Option<Vec<String>>,
+    /// What to clear when Edge closes: `"all"` browsing data, or just the
+    /// `"cache"`.
+    pub clear_on_exit: Option<Vec<String>>,
+    /// Which local IP addresses WebRTC may expose, e.g.
+    /// `"disable_non_proxied_udp"` to keep it behind a VPN or proxy.
+    pub webrtc_ip_handling: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct HooksConfig {
+    /// Commands run before writing; a non-zero exit aborts the apply.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_apply: Vec<String>,
+    /// Commands run after a successful apply.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_apply: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReportConfig {
+    /// URL that receives a JSON summary after each apply and verify.
+    pub webhook: Option<String>,
+}
+
+pub fn load(path: Option<&Path>) -> Result<Config> {
+    let path = resolve_path(path)?;
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(source) => return Err(Error::ConfigRead { path, source }),
+    };
+
+    toml::from_str(&text).map_err(|e| Error::ConfigParse {
+        path: Some(path),
+        source: Box::new(e),
+    })
+}
+
+pub fn parse(text: &str) -> Result<Config> {
+    toml::from_str(text).map_err(|e| Error::ConfigParse {
+        path: None,
+        source: Box::new(e),
+    })
+}
+
+/// Canonical TOML for a config: sections in declaration order, unset keys
+/// omitted. Comments and formatting from the original file are not kept.
+pub fn to_toml(config: &Config) -> Result<String> {
+    Ok(toml::to_string_pretty(config)?)
+}
+
+/// The given config path, or the default one.
+pub fn resolve_path(path: Option<&Path>) -> Result<std::path::PathBuf> {
+    match path {
+        Some(p) => Ok(p.to_owned()),
+        None => default_config_path(),
+    }
+}
+
+/// The profile chosen with `profile use`, if any, otherwise `config.toml`.
+fn default_config_path() -> Result<std::path::PathBuf> {
+    match active_profile() {
+        Some(name) => profile_path(&name),
+        None => Ok(data_dir()?.join("config.toml")),
+    }
+}
+
+/// `~/.edge-profile/profiles`, one config file per named profile.
+pub fn profiles_dir() -> Result<std::path::PathBuf> {
+    Ok(data_dir()?.join("profiles"))
+}
+
+pub fn profile_path(name: &str) -> Result<std::path::PathBuf> {
+    Ok(profiles_dir()?.join(format!("{name}.toml")))
+}
+
+/// The profile last switched to, named in `~/.edge-profile/profile`.
+pub fn active_profile() -> Option<String> {
+    let text = std::fs::read_to_string(active_profile_path().ok()?).ok()?;
+    let name = text.trim();
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+pub fn active_profile_path() -> Result<std::path::PathBuf> {
+    Ok(data_dir()?.join("profile"))
+}
+
+/// `~/.edge-profile`, home to the default config and the tool's own files.
+pub fn data_dir() -> Result<std::path::PathBuf> {
+    let home = dirs::home_dir().ok_or(Error::NoHomeDir)?;
+    Ok(home.join(".edge-profile"))
+}
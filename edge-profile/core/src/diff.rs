@@ -0,0 +1,126 @@
+use std::fmt;
+
+use crate::policy::PolicyEntry;
+
+/// One difference between the desired entries and the registry.
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// In the config, missing from the registry.
+    Added(PolicyEntry),
+    /// In both, with a different value.
+    Changed { old: PolicyEntry, new: PolicyEntry },
+    /// A stale numbered item in a list subkey that apply recreates.
+    Removed(PolicyEntry),
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added(e) => write!(f, "+ {e}"),
+            Change::Changed { old, new } => {
+                write!(f, "~ {}: {} -> {}", new.path(), old.value, new.value)
+            }
+            Change::Removed(e) => write!(f, "- {e}"),
+        }
+    }
+}
+
+impl Change {
+    /// The entry the change is about: the new one for additions and changes,
+    /// the stale one for removals.
+    pub fn entry(&self) -> &PolicyEntry {
+        match self {
+            Change::Added(e) | Change::Removed(e) => e,
+            Change::Changed { new, .. } => new,
+        }
+    }
+}
+
+/// Compare the entries built from the config against what the registry holds.
+///
+/// Top-level values that aren't in the config are left alone, as apply does;
+/// list subkeys the config sets are rewritten wholesale, so extra items in
+/// them count as removals.
+pub fn compare(desired: &[PolicyEntry], current: &[PolicyEntry]) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    for new in desired {
+        match current
+            .iter()
+            .find(|c| c.subkey == new.subkey && c.name == new.name)
+        {
+            None => changes.push(Change::Added(new.clone())),
+            Some(old) if old.value != new.value => changes.push(Change::Changed {
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    let rewritten = |subkey: &str| desired.iter().any(|d| d.subkey == subkey);
+    for old in current {
+        if old.subkey.is_empty() || !rewritten(&old.subkey) {
+            continue;
+        }
+        if !desired
+            .iter()
+            .any(|d| d.subkey == old.subkey && d.name == old.name)
+        {
+            changes.push(Change::Removed(old.clone()));
+        }
+    }
+
+    changes
+}
+
+/// Counts for the line that ends an apply.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub added: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    pub conflicts: usize,
+    /// Changes left unwritten by `apply --interactive`.
+    pub skipped: usize,
+}
+
+impl Summary {
+    pub fn new(desired: &[PolicyEntry], changes: &[Change], conflicts: usize) -> Self {
+        let mut summary = Summary {
+            conflicts,
+            ..Summary::default()
+        };
+        for change in changes {
+            match change {
+                Change::Added(_) => summary.added += 1,
+                Change::Changed { .. } => summary.changed += 1,
+                Change::Removed(_) => summary.removed += 1,
+            }
+        }
+        summary.unchanged = desired.len() - summary.added - summary.changed;
+        summary
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} changed, {} unchanged",
+            self.added, self.changed, self.unchanged
+        )?;
+        if self.removed > 0 {
+            write!(f, ", {} removed (pruned)", self.removed)?;
+        }
+        if self.skipped > 0 {
+            write!(f, ", {} skipped", self.skipped)?;
+        }
+        match self.conflicts {
+            0 => Ok(()),
+            1 => write!(f, ", 1 HKLM conflict"),
+            n => write!(f, ", {n} HKLM conflicts"),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+#[cfg(windows)]
+use std::path::Path;
+
+#[cfg(windows)]
+use winreg::enums::*;
+#[cfg(windows)]
+use winreg::RegKey;
+
+#[cfg(windows)]
+const BLBEACON_PATH: &str = r"SOFTWARE\Microsoft\Edge\BLBeacon";
+#[cfg(windows)]
+const APP_PATH: &str = r"SOFTWARE\Microsoft\Windows\CurrentVersion\App Paths\msedge.exe";
+
+/// An installed Edge version, e.g. `120.0.2210.91`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EdgeVersion(pub [u32; 4]);
+
+impl EdgeVersion {
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = [0; 4];
+        let mut count = 0;
+        for (slot, part) in parts.iter_mut().zip(s.trim().split('.')) {
+            *slot = part.parse().ok()?;
+            count += 1;
+        }
+        (count == 4).then_some(Self(parts))
+    }
+
+    pub fn major(&self) -> u32 {
+        self.0[0]
+    }
+}
+
+impl fmt::Display for EdgeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.0;
+        write!(f, "{a}.{b}.{c}.{d}")
+    }
+}
+
+/// Detect the installed Edge version.
+///
+/// Edge records its version in the `BLBeacon` key on every launch; if it has
+/// never run for this user, fall back to the versioned directory that sits
+/// next to `msedge.exe`.
+#[cfg(windows)]
+pub fn installed_version() -> Option<EdgeVersion> {
+    blbeacon_version().or_else(app_path_version)
+}
+
+#[cfg(windows)]
+fn blbeacon_version() -> Option<EdgeVersion> {
+    [HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE]
+        .into_iter()
+        .find_map(|hive| {
+            let key = RegKey::predef(hive).open_subkey(BLBEACON_PATH).ok()?;
+            let version: String = key.get_value("version").ok()?;
+            EdgeVersion::parse(&version)
+        })
+}
+
+#[cfg(windows)]
+fn app_path_version() -> Option<EdgeVersion> {
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(APP_PATH)
+        .ok()?;
+    let exe: String = key.get_value("").ok()?;
+    let dir = Path::new(exe.trim_matches('"')).parent()?;
+
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| EdgeVersion::parse(&e.file_name().to_string_lossy()))
+        .max()
+}
@@ -0,0 +1,54 @@
+use std::io;
+use std::path::PathBuf;
+
+/// Failure kinds of the library API.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to read config: {}", path.display())]
+    ConfigRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// `path` is unset when parsing text that didn't come from a file.
+    #[error("Failed to parse config{}", in_file(path))]
+    ConfigParse {
+        path: Option<PathBuf>,
+        #[source]
+        source: Box<toml::de::Error>,
+    },
+    #[error("Failed to serialize config")]
+    ConfigSerialize(#[from] toml::ser::Error),
+    #[error("Cannot determine home directory")]
+    NoHomeDir,
+    /// The config parsed but has validation errors; see `validate::check`.
+    #[error("Config has {errors} validation error(s)")]
+    Validation { errors: usize },
+    #[error("{context}")]
+    RegistryAccess {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+    /// Higher-precedence (HKLM) policies override these config values.
+    #[error("HKLM policies override the config: {}", names.join(", "))]
+    Conflict { names: Vec<String> },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(windows)]
+impl Error {
+    pub(crate) fn registry(context: impl Into<String>, source: io::Error) -> Self {
+        Error::RegistryAccess {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+fn in_file(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|p| format!(": {}", p.display()))
+        .unwrap_or_default()
+}
@@ -0,0 +1,30 @@
+//! The config model, policy catalog and registry backend behind
+//! `edge-profile`, for tools that want to build or apply Edge policies
+//! without going through the CLI.
+//!
+//! ```no_run
+//! use edge_profile_core::{build_entries, config, Backend, Scope};
+//!
+//! fn provision(backend: &impl Backend) -> edge_profile_core::Result<()> {
+//!     let config = config::parse("[privacy]\ntracking_prevention = \"strict\"\n")?;
+//!     backend.apply(&build_entries(&config), Scope::User)
+//! }
+//! ```
+
+mod builder;
+pub mod catalog;
+pub mod config;
+pub mod diff;
+pub mod edge;
+mod error;
+pub mod policy;
+pub mod registry;
+pub mod validate;
+
+pub use builder::PolicySet;
+pub use config::Config;
+pub use error::{Error, Result};
+pub use policy::{build_entries, PolicyEntry, RegValue};
+#[cfg(windows)]
+pub use registry::Registry;
+pub use registry::{Backend, Scope};
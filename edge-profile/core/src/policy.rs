@@ -0,0 +1,884 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::catalog::{self, PolicyKind};
+use crate::config::{Config, ExtensionSetting, FavoritesConfig, SiteLists};
+
+/// Registry value types we write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegValue {
+    Dword(u32),
+    Sz(String),
+}
+
+impl RegValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RegValue::Dword(_) => "REG_DWORD",
+            RegValue::Sz(_) => "REG_SZ",
+        }
+    }
+}
+
+/// A single registry entry to write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyEntry {
+    /// Subkey path relative to `HKCU\SOFTWARE\Policies\Microsoft\Edge`.
+    /// Empty string means the Edge key itself.
+    pub subkey: String,
+    pub name: String,
+    pub value: RegValue,
+}
+
+impl fmt::Display for RegValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegValue::Dword(v) => write!(f, "DWORD({v})"),
+            RegValue::Sz(v) => write!(f, "\"{v}\""),
+        }
+    }
+}
+
+impl PolicyEntry {
+    /// Path relative to the Edge policy key, e.g. `ExtensionInstallForcelist\1`.
+    pub fn path(&self) -> String {
+        if self.subkey.is_empty() {
+            self.name.clone()
+        } else {
+            format!(r"{}\{}", self.subkey, self.name)
+        }
+    }
+
+    /// The Edge policy this entry belongs to: the value name for top-level
+    /// entries, the subkey name for numbered list items.
+    pub fn policy_name(&self) -> &str {
+        if self.subkey.is_empty() {
+            &self.name
+        } else {
+            &self.subkey
+        }
+    }
+}
+
+impl fmt::Display for PolicyEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.path(), self.value)
+    }
+}
+
+/// All top-level value names this tool may write (used by `clean`).
+pub const MANAGED_VALUES: &[&str] = &[
+    "DefaultSearchProviderEnabled",
+    "DefaultSearchProviderName",
+    "DefaultSearchProviderSearchURL",
+    "DefaultSearchProviderSuggestURL",
+    "SearchSuggestEnabled",
+    "AddressBarMicrosoftSearchInBingProviderEnabled",
+    "SearchInSidebarEnabled",
+    "ExtensionSettings",
+    "RestoreOnStartup",
+    "ShowHomeButton",
+    "HomepageLocation",
+    "HomepageIsNewTabPage",
+    "NewTabPageLocation",
+    "FavoritesBarEnabled",
+    "HideFirstRunExperience",
+    "InPrivateModeAvailability",
+    "DefaultBrowserSettingEnabled",
+    "DefaultBrowserSettingsCampaignEnabled",
+    "AlwaysOpenPdfExternally",
+    "RestorePdfView",
+    "RelaunchNotification",
+    "RelaunchNotificationPeriod",
+    "TrackingPrevention",
+    "PasswordManagerEnabled",
+    "PasswordMonitorAllowed",
+    "PasswordProtectionWarningTrigger",
+    "PasswordGeneratorEnabled",
+    "AutofillCreditCardEnabled",
+    "AutofillAddressEnabled",
+    "DnsOverHttpsMode",
+    "DnsOverHttpsTemplates",
+    "ClearBrowsingDataOnExit",
+    "ClearCachedImagesAndFilesOnExit",
+    "WebRtcLocalhostIpHandling",
+    "DownloadDirectory",
+    "PromptForDownloadLocation",
+    "DownloadRestrictions",
+    "SmartScreenEnabled",
+    "SmartScreenPuaEnabled",
+    "PreventSmartScreenPromptOverride",
+    "SitePerProcess",
+    "SSLVersionMin",
+    "InsecureFormsWarningsEnabled",
+    "SSLErrorOverrideAllowed",
+    "DefaultCookiesSetting",
+    "DefaultJavaScriptSetting",
+    "DefaultPopupsSetting",
+    "DefaultNotificationsSetting",
+    "DefaultGeolocationSetting",
+    "DefaultImagesSetting",
+    "ManagedFavorites",
+    "SyncDisabled",
+    "BrowserSignin",
+    "NonRemovableProfileEnabled",
+    "ImplicitSignInEnabled",
+    "SleepingTabsEnabled",
+    "SleepingTabsTimeout",
+    "StartupBoostEnabled",
+    "BackgroundModeEnabled",
+    "EfficiencyMode",
+    "VerticalTabsAllowed",
+    "HubsSidebarEnabled",
+    "NewTabPageContentEnabled",
+    "NewTabPageQuickLinksEnabled",
+    "NewTabPageAllowedBackgroundTypes",
+    "EdgeShoppingAssistantEnabled",
+    "EdgeCollectionsEnabled",
+    "EdgeEDropEnabled",
+    "EdgeFollowEnabled",
+    "EdgeWalletEtreeEnabled",
+    "ShowMicrosoftRewards",
+    "ShowRecommendationsEnabled",
+    "SpotlightExperiencesAndRecommendationsEnabled",
+    "MicrosoftEdgeInsiderPromotionEnabled",
+    "QuickSearchShowMiniMenu",
+    "WebWidgetAllowed",
+    "WebWidgetIsEnabledOnStartup",
+    "DiagnosticData",
+    "PersonalizationReportingEnabled",
+    "UserFeedbackAllowed",
+    "ExperimentationAndConfigurationServiceControl",
+    "TranslateEnabled",
+    "SpellcheckEnabled",
+    "DeveloperToolsAvailability",
+    "BrowserAddProfileEnabled",
+    "BrowserGuestModeEnabled",
+    "CommandLineFlagSecurityWarningsEnabled",
+    "ImportFavorites",
+    "ImportHistory",
+    "ImportSearchEngine",
+    "ImportSavedPasswords",
+    "ImportOnEachLaunch",
+    "InternetExplorerIntegrationLevel",
+    "InternetExplorerIntegrationSiteList",
+];
+
+/// RelaunchNotificationPeriod is in milliseconds; the config key is in hours.
+pub const HOUR_MS: u32 = 3_600_000;
+
+/// First-class config keys and the policy each one writes.
+pub const CONFIG_KEYS: &[(&str, &str)] = &[
+    ("search", "DefaultSearchProviderEnabled"),
+    ("search.provider", "DefaultSearchProviderName"),
+    ("search.search_url", "DefaultSearchProviderSearchURL"),
+    ("search.suggest_url", "DefaultSearchProviderSuggestURL"),
+    ("search.suggestions", "SearchSuggestEnabled"),
+    (
+        "search.bing_suggestions",
+        "AddressBarMicrosoftSearchInBingProviderEnabled",
+    ),
+    ("search.sidebar_search", "SearchInSidebarEnabled"),
+    ("extensions", "ExtensionInstallForcelist"),
+    ("extensions.blocklist", "ExtensionInstallBlocklist"),
+    ("extensions.allowlist", "ExtensionInstallAllowlist"),
+    ("extensions.sources", "ExtensionInstallSources"),
+    ("extension_settings", "ExtensionSettings"),
+    ("browser.restore_on_startup", "RestoreOnStartup"),
+    ("browser.startup_urls", "RestoreOnStartupURLs"),
+    ("browser.show_home_button", "ShowHomeButton"),
+    ("browser.homepage", "HomepageLocation"),
+    ("browser.homepage_is_new_tab", "HomepageIsNewTabPage"),
+    ("browser.new_tab_page", "NewTabPageLocation"),
+    ("browser.favorites_bar", "FavoritesBarEnabled"),
+    ("browser.hide_first_run", "HideFirstRunExperience"),
+    ("browser.inprivate", "InPrivateModeAvailability"),
+    ("browser.default_browser_prompt", "DefaultBrowserSettingEnabled"),
+    ("browser.default_browser_prompt", "DefaultBrowserSettingsCampaignEnabled"),
+    ("browser.always_open_pdf_externally", "AlwaysOpenPdfExternally"),
+    ("browser.restore_pdf_view", "RestorePdfView"),
+    ("browser.relaunch_notification", "RelaunchNotification"),
+    ("browser.relaunch_notification_period", "RelaunchNotificationPeriod"),
+    ("privacy.tracking_prevention", "TrackingPrevention"),
+    ("privacy.password_manager", "PasswordManagerEnabled"),
+    ("privacy.password_monitor", "PasswordMonitorAllowed"),
+    (
+        "privacy.password_protection_warning_trigger",
+        "PasswordProtectionWarningTrigger",
+    ),
+    ("privacy.password_generation", "PasswordGeneratorEnabled"),
+    ("privacy.autofill_credit_card", "AutofillCreditCardEnabled"),
+    ("privacy.autofill_address", "AutofillAddressEnabled"),
+    ("privacy.dns_over_https", "DnsOverHttpsMode"),
+    ("privacy.dns_over_https_templates", "DnsOverHttpsTemplates"),
+    ("privacy.clear_on_exit", "ClearBrowsingDataOnExit"),
+    ("privacy.clear_on_exit", "ClearCachedImagesAndFilesOnExit"),
+    ("privacy.webrtc_ip_handling", "WebRtcLocalhostIpHandling"),
+    ("downloads.default_directory", "DownloadDirectory"),
+    ("downloads.prompt_for_location", "PromptForDownloadLocation"),
+    ("downloads.restrictions", "DownloadRestrictions"),
+    ("security.smartscreen", "SmartScreenEnabled"),
+    ("security.smartscreen_pua", "SmartScreenPuaEnabled"),
+    ("security.prevent_smartscreen_override", "PreventSmartScreenPromptOverride"),
+    ("security.site_per_process", "SitePerProcess"),
+    ("security.min_tls_version", "SSLVersionMin"),
+    ("security.insecure_forms_warnings", "InsecureFormsWarningsEnabled"),
+    ("security.ssl_error_override", "SSLErrorOverrideAllowed"),
+    ("content.cookies", "DefaultCookiesSetting"),
+    ("content.javascript", "DefaultJavaScriptSetting"),
+    ("content.popups", "DefaultPopupsSetting"),
+    ("content.notifications", "DefaultNotificationsSetting"),
+    ("content.geolocation", "DefaultGeolocationSetting"),
+    ("content.images", "DefaultImagesSetting"),
+    ("content.allow.cookies", "CookiesAllowedForUrls"),
+    ("content.allow.javascript", "JavaScriptAllowedForUrls"),
+    ("content.allow.popups", "PopupsAllowedForUrls"),
+    ("content.allow.notifications", "NotificationsAllowedForUrls"),
+    ("content.allow.geolocation", "GeolocationAllowedForUrls"),
+    ("content.allow.images", "ImagesAllowedForUrls"),
+    ("content.block.cookies", "CookiesBlockedForUrls"),
+    ("content.block.javascript", "JavaScriptBlockedForUrls"),
+    ("content.block.popups", "PopupsBlockedForUrls"),
+    ("content.block.notifications", "NotificationsBlockedForUrls"),
+    ("content.block.geolocation", "GeolocationBlockedForUrls"),
+    ("content.block.images", "ImagesBlockedForUrls"),
+    ("content.cookies_session_only", "CookiesSessionOnlyForUrls"),
+    ("urls.block", "URLBlocklist"),
+    ("urls.allow", "URLAllowlist"),
+    ("favorites", "ManagedFavorites"),
+    ("account.sync_disabled", "SyncDisabled"),
+    ("account.browser_signin", "BrowserSignin"),
+    ("account.non_removable_profile_enabled", "NonRemovableProfileEnabled"),
+    ("account.implicit_signin_enabled", "ImplicitSignInEnabled"),
+    ("performance.sleeping_tabs", "SleepingTabsEnabled"),
+    ("performance.sleeping_tabs_timeout", "SleepingTabsTimeout"),
+    ("performance.startup_boost", "StartupBoostEnabled"),
+    ("performance.background_mode", "BackgroundModeEnabled"),
+    ("performance.efficiency_mode", "EfficiencyMode"),
+    ("appearance.vertical_tabs", "VerticalTabsAllowed"),
+    ("appearance.sidebar", "HubsSidebarEnabled"),
+    ("appearance.new_tab_content", "NewTabPageContentEnabled"),
+    ("appearance.new_tab_quick_links", "NewTabPageQuickLinksEnabled"),
+    ("appearance.new_tab_background", "NewTabPageAllowedBackgroundTypes"),
+    ("features.shopping_assistant", "EdgeShoppingAssistantEnabled"),
+    ("features.collections", "EdgeCollectionsEnabled"),
+    ("features.drop", "EdgeEDropEnabled"),
+    ("features.follow", "EdgeFollowEnabled"),
+    ("features.wallet_etree", "EdgeWalletEtreeEnabled"),
+    ("features.rewards", "ShowMicrosoftRewards"),
+    ("features.recommendations", "ShowRecommendationsEnabled"),
+    ("features.spotlight", "SpotlightExperiencesAndRecommendationsEnabled"),
+    ("features.insider_promotion", "MicrosoftEdgeInsiderPromotionEnabled"),
+    ("features.mini_menu", "QuickSearchShowMiniMenu"),
+    ("features.edge_bar", "WebWidgetAllowed"),
+    ("features.edge_bar_on_startup", "WebWidgetIsEnabledOnStartup"),
+    ("telemetry.diagnostic_data", "DiagnosticData"),
+    ("telemetry.personalization_reporting", "PersonalizationReportingEnabled"),
+    ("telemetry.user_feedback_allowed", "UserFeedbackAllowed"),
+    (
+        "telemetry.experimentation_and_configuration_service",
+        "ExperimentationAndConfigurationServiceControl",
+    ),
+    ("language.translate", "TranslateEnabled"),
+    ("language.spellcheck", "SpellcheckEnabled"),
+    ("language.spellcheck_languages", "SpellcheckLanguage"),
+    ("language.spellcheck_language_blocklist", "SpellcheckLanguageBlocklist"),
+    ("advanced.developer_tools", "DeveloperToolsAvailability"),
+    ("advanced.add_profile", "BrowserAddProfileEnabled"),
+    ("advanced.guest_mode", "BrowserGuestModeEnabled"),
+    ("advanced.command_line_flag_warnings", "CommandLineFlagSecurityWarningsEnabled"),
+    ("import.favorites", "ImportFavorites"),
+    ("import.history", "ImportHistory"),
+    ("import.search_engine", "ImportSearchEngine"),
+    ("import.passwords", "ImportSavedPasswords"),
+    ("import.on_each_launch", "ImportOnEachLaunch"),
+    ("ie_mode.integration_level", "InternetExplorerIntegrationLevel"),
+    ("ie_mode.site_list", "InternetExplorerIntegrationSiteList"),
+];
+
+/// The config key that drives a policy, if it has one.
+pub fn config_key(policy: &str) -> Option<&'static str> {
+    CONFIG_KEYS
+        .iter()
+        .find(|(_, p)| p.eq_ignore_ascii_case(policy))
+        .map(|(k, _)| *k)
+}
+
+/// Reserved `[extensions]` keys holding lists rather than an extension to
+/// force-install, and the policy each writes.
+pub const EXTENSION_LISTS: &[(&str, &str)] = &[
+    ("blocklist", "ExtensionInstallBlocklist"),
+    ("allowlist", "ExtensionInstallAllowlist"),
+    ("sources", "ExtensionInstallSources"),
+];
+
+/// The names `privacy.clear_on_exit` takes and the policy each one turns
+/// on. Edge can't clear other kinds of data on their own.
+pub const CLEAR_ON_EXIT: &[(&str, &str)] = &[
+    ("all", "ClearBrowsingDataOnExit"),
+    ("cache", "ClearCachedImagesAndFilesOnExit"),
+];
+
+/// Subkeys this tool may create (used by `clean`): the config keys whose
+/// policy the catalog lists as a list.
+pub fn managed_subkeys() -> impl Iterator<Item = &'static str> {
+    CONFIG_KEYS
+        .iter()
+        .map(|(_, policy)| *policy)
+        .filter(|policy| catalog::lookup(policy).is_some_and(|d| d.kind == PolicyKind::List))
+}
+
+pub fn is_managed_subkey(name: &str) -> bool {
+    managed_subkeys().any(|subkey| subkey == name)
+}
+
+const EDGE_UPDATE_URL: &str =
+    "https://edge.microsoft.com/extensionwebstorebase/v1/crx";
+const CHROME_UPDATE_URL: &str =
+    "https://clients2.google.com/service/update2/crx";
+
+pub fn build_entries(cfg: &Config) -> Vec<PolicyEntry> {
+    let mut entries = Vec::new();
+
+    if let Some(ref search) = cfg.search {
+        if search.sets_provider() {
+            entries.push(dword("", "DefaultSearchProviderEnabled", 1));
+        }
+
+        if let Some(ref name) = search.provider {
+            entries.push(sz("", "DefaultSearchProviderName", name));
+        }
+        if let Some(ref url) = search.search_url {
+            entries.push(sz("", "DefaultSearchProviderSearchURL", url));
+        }
+        if let Some(ref url) = search.suggest_url {
+            entries.push(sz("", "DefaultSearchProviderSuggestURL", url));
+        }
+        if let Some(v) = search.suggestions {
+            entries.push(dword("", "SearchSuggestEnabled", v as u32));
+        }
+        if let Some(v) = search.bing_suggestions {
+            entries.push(dword("", "AddressBarMicrosoftSearchInBingProviderEnabled", v as u32));
+        }
+        if let Some(ref mode) = search.sidebar_search {
+            entries.extend(enum_dword("SearchInSidebarEnabled", mode));
+        }
+    }
+
+    if let Some(ref exts) = cfg.extensions {
+        let forced = exts.iter().filter(|(key, _)| !EXTENSION_LISTS.iter().any(|(k, _)| k == key));
+        for (i, s) in forced.filter_map(|(_, val)| val.as_str()).enumerate() {
+            let entry_value = resolve_extension(s);
+            entries.push(sz(
+                "ExtensionInstallForcelist",
+                &(i + 1).to_string(),
+                &entry_value,
+            ));
+        }
+        for (key, policy) in EXTENSION_LISTS {
+            let Some(items) = exts.get(*key).and_then(|v| v.as_array()) else {
+                continue;
+            };
+            // Specs become bare IDs; source URL patterns pass through.
+            let items: Vec<String> = items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| extension_id(s).to_owned())
+                .collect();
+            entries.extend(list(policy, &items));
+        }
+    }
+
+    if let Some(ref settings) = cfg.extension_settings {
+        entries.push(sz("", "ExtensionSettings", &extension_settings_json(settings)));
+    }
+
+    if let Some(ref browser) = cfg.browser {
+        if let Some(ref mode) = browser.restore_on_startup {
+            entries.extend(enum_dword("RestoreOnStartup", mode));
+        }
+        if let Some(ref urls) = browser.startup_urls {
+            entries.extend(list("RestoreOnStartupURLs", urls));
+        }
+        if let Some(v) = browser.show_home_button {
+            entries.push(dword("", "ShowHomeButton", v as u32));
+        }
+        if let Some(ref url) = browser.homepage {
+            entries.push(sz("", "HomepageLocation", url));
+        }
+        if let Some(v) = browser.homepage_is_new_tab {
+            entries.push(dword("", "HomepageIsNewTabPage", v as u32));
+        }
+        if let Some(ref url) = browser.new_tab_page {
+            entries.push(sz("", "NewTabPageLocation", url));
+        }
+        if let Some(v) = browser.favorites_bar {
+            entries.push(dword("", "FavoritesBarEnabled", v as u32));
+        }
+        if let Some(v) = browser.hide_first_run {
+            entries.push(dword("", "HideFirstRunExperience", v as u32));
+        }
+        if let Some(ref availability) = browser.inprivate {
+            entries.extend(enum_dword("InPrivateModeAvailability", availability));
+        }
+        // DefaultBrowserSettingEnabled = 1 would have Edge make itself the
+        // default, so allowing the prompt leaves it unset.
+        match browser.default_browser_prompt {
+            Some(false) => {
+                entries.push(dword("", "DefaultBrowserSettingEnabled", 0));
+                entries.push(dword("", "DefaultBrowserSettingsCampaignEnabled", 0));
+            }
+            Some(true) => entries.push(dword("", "DefaultBrowserSettingsCampaignEnabled", 1)),
+            None => {}
+        }
+        if let Some(v) = browser.always_open_pdf_externally {
+            entries.push(dword("", "AlwaysOpenPdfExternally", v as u32));
+        }
+        if let Some(v) = browser.restore_pdf_view {
+            entries.push(dword("", "RestorePdfView", v as u32));
+        }
+        if let Some(ref level) = browser.relaunch_notification {
+            entries.extend(enum_dword("RelaunchNotification", level));
+        }
+        if let Some(hours) = browser.relaunch_notification_period {
+            let millis = hours.saturating_mul(HOUR_MS);
+            entries.push(dword("", "RelaunchNotificationPeriod", millis));
+        }
+    }
+
+    if let Some(ref privacy) = cfg.privacy {
+        if let Some(ref level) = privacy.tracking_prevention {
+            entries.extend(enum_dword("TrackingPrevention", level));
+        }
+        if let Some(v) = privacy.password_manager {
+            entries.push(dword("", "PasswordManagerEnabled", v as u32));
+        }
+        if let Some(v) = privacy.password_monitor {
+            entries.push(dword("", "PasswordMonitorAllowed", v as u32));
+        }
+        if let Some(ref trigger) = privacy.password_protection_warning_trigger {
+            entries.extend(enum_dword("PasswordProtectionWarningTrigger", trigger));
+        }
+        if let Some(v) = privacy.password_generation {
+            entries.push(dword("", "PasswordGeneratorEnabled", v as u32));
+        }
+        if let Some(v) = privacy.autofill_credit_card {
+            entries.push(dword("", "AutofillCreditCardEnabled", v as u32));
+        }
+        if let Some(v) = privacy.autofill_address {
+            entries.push(dword("", "AutofillAddressEnabled", v as u32));
+        }
+        if let Some(ref mode) = privacy.dns_over_https {
+            entries.push(sz("", "DnsOverHttpsMode", mode));
+        }
+        // Edge takes several templates as one space-separated string.
+        if let Some(ref templates) = privacy.dns_over_https_templates {
+            entries.push(sz("", "DnsOverHttpsTemplates", &templates.join(" ")));
+        }
+        // Listing any kind pins the others off, so a shorter list clears less.
+        if let Some(ref kinds) = privacy.clear_on_exit {
+            for (kind, policy) in CLEAR_ON_EXIT {
+                let on = kinds.iter().any(|k| k == kind);
+                entries.push(dword("", policy, on as u32));
+            }
+        }
+        if let Some(ref handling) = privacy.webrtc_ip_handling {
+            entries.push(sz("", "WebRtcLocalhostIpHandling", handling));
+        }
+    }
+
+    if let Some(ref downloads) = cfg.downloads {
+        if let Some(ref dir) = downloads.default_directory {
+            entries.push(sz("", "DownloadDirectory", &edge_path_variables(dir).0));
+        }
+        if let Some(v) = downloads.prompt_for_location {
+            entries.push(dword("", "PromptForDownloadLocation", v as u32));
+        }
+        if let Some(ref level) = downloads.restrictions {
+            entries.extend(enum_dword("DownloadRestrictions", level));
+        }
+    }
+
+    if let Some(ref security) = cfg.security {
+        if let Some(v) = security.smartscreen {
+            entries.push(dword("", "SmartScreenEnabled", v as u32));
+        }
+        if let Some(v) = security.smartscreen_pua {
+            entries.push(dword("", "SmartScreenPuaEnabled", v as u32));
+        }
+        if let Some(v) = security.prevent_smartscreen_override {
+            entries.push(dword("", "PreventSmartScreenPromptOverride", v as u32));
+        }
+        if let Some(v) = security.site_per_process {
+            entries.push(dword("", "SitePerProcess", v as u32));
+        }
+        if let Some(ref version) = security.min_tls_version {
+            entries.push(sz("", "SSLVersionMin", version));
+        }
+        if let Some(v) = security.insecure_forms_warnings {
+            entries.push(dword("", "InsecureFormsWarningsEnabled", v as u32));
+        }
+        if let Some(v) = security.ssl_error_override {
+            entries.push(dword("", "SSLErrorOverrideAllowed", v as u32));
+        }
+    }
+
+    if let Some(ref content) = cfg.content {
+        if let Some(ref choice) = content.cookies {
+            entries.extend(enum_dword("DefaultCookiesSetting", choice));
+        }
+        if let Some(ref choice) = content.javascript {
+            entries.extend(enum_dword("DefaultJavaScriptSetting", choice));
+        }
+        if let Some(ref choice) = content.popups {
+            entries.extend(enum_dword("DefaultPopupsSetting", choice));
+        }
+        if let Some(ref choice) = content.notifications {
+            entries.extend(enum_dword("DefaultNotificationsSetting", choice));
+        }
+        if let Some(ref choice) = content.geolocation {
+            entries.extend(enum_dword("DefaultGeolocationSetting", choice));
+        }
+        if let Some(ref choice) = content.images {
+            entries.extend(enum_dword("DefaultImagesSetting", choice));
+        }
+        for (sites, verb) in [(&content.allow, "Allowed"), (&content.block, "Blocked")] {
+            for (setting, urls) in sites.iter().flat_map(SiteLists::lists) {
+                if let Some(urls) = urls {
+                    entries.extend(list(&format!("{setting}{verb}ForUrls"), urls));
+                }
+            }
+        }
+        if let Some(ref urls) = content.cookies_session_only {
+            entries.extend(list("CookiesSessionOnlyForUrls", urls));
+        }
+    }
+
+    if let Some(ref urls) = cfg.urls {
+        if let Some(ref patterns) = urls.block {
+            entries.extend(list("URLBlocklist", patterns));
+        }
+        if let Some(ref patterns) = urls.allow {
+            entries.extend(list("URLAllowlist", patterns));
+        }
+    }
+
+    if let Some(ref favorites) = cfg.favorites {
+        entries.push(sz("", "ManagedFavorites", &favorites_json(favorites)));
+    }
+
+    if let Some(ref account) = cfg.account {
+        if let Some(v) = account.sync_disabled {
+            entries.push(dword("", "SyncDisabled", v as u32));
+        }
+        if let Some(ref mode) = account.browser_signin {
+            entries.extend(enum_dword("BrowserSignin", mode));
+        }
+        if let Some(v) = account.non_removable_profile_enabled {
+            entries.push(dword("", "NonRemovableProfileEnabled", v as u32));
+        }
+        if let Some(v) = account.implicit_signin_enabled {
+            entries.push(dword("", "ImplicitSignInEnabled", v as u32));
+        }
+    }
+
+    if let Some(ref performance) = cfg.performance {
+        if let Some(v) = performance.sleeping_tabs {
+            entries.push(dword("", "SleepingTabsEnabled", v as u32));
+        }
+        if let Some(ref timeout) = performance.sleeping_tabs_timeout {
+            entries.extend(enum_dword("SleepingTabsTimeout", timeout));
+        }
+        if let Some(v) = performance.startup_boost {
+            entries.push(dword("", "StartupBoostEnabled", v as u32));
+        }
+        if let Some(v) = performance.background_mode {
+            entries.push(dword("", "BackgroundModeEnabled", v as u32));
+        }
+        if let Some(ref mode) = performance.efficiency_mode {
+            entries.extend(enum_dword("EfficiencyMode", mode));
+        }
+    }
+
+    if let Some(ref appearance) = cfg.appearance {
+        if let Some(v) = appearance.vertical_tabs {
+            entries.push(dword("", "VerticalTabsAllowed", v as u32));
+        }
+        if let Some(v) = appearance.sidebar {
+            entries.push(dword("", "HubsSidebarEnabled", v as u32));
+        }
+        if let Some(v) = appearance.new_tab_content {
+            entries.push(dword("", "NewTabPageContentEnabled", v as u32));
+        }
+        if let Some(v) = appearance.new_tab_quick_links {
+            entries.push(dword("", "NewTabPageQuickLinksEnabled", v as u32));
+        }
+        if let Some(ref types) = appearance.new_tab_background {
+            entries.extend(enum_dword("NewTabPageAllowedBackgroundTypes", types));
+        }
+    }
+
+    if let Some(ref features) = cfg.features {
+        for (policy, enabled) in features.toggles() {
+            if let Some(v) = enabled {
+                entries.push(dword("", policy, v as u32));
+            }
+        }
+    }
+
+    if let Some(ref telemetry) = cfg.telemetry {
+        if let Some(ref level) = telemetry.diagnostic_data {
+            entries.extend(enum_dword("DiagnosticData", level));
+        }
+        if let Some(v) = telemetry.personalization_reporting {
+            entries.push(dword("", "PersonalizationReportingEnabled", v as u32));
+        }
+        if let Some(v) = telemetry.user_feedback_allowed {
+            entries.push(dword("", "UserFeedbackAllowed", v as u32));
+        }
+        if let Some(ref level) = telemetry.experimentation_and_configuration_service {
+            entries.extend(enum_dword("ExperimentationAndConfigurationServiceControl", level));
+        }
+    }
+
+    if let Some(ref language) = cfg.language {
+        if let Some(v) = language.translate {
+            entries.push(dword("", "TranslateEnabled", v as u32));
+        }
+        if let Some(v) = language.spellcheck {
+            entries.push(dword("", "SpellcheckEnabled", v as u32));
+        }
+        if let Some(ref languages) = language.spellcheck_languages {
+            entries.extend(list("SpellcheckLanguage", languages));
+        }
+        if let Some(ref languages) = language.spellcheck_language_blocklist {
+            entries.extend(list("SpellcheckLanguageBlocklist", languages));
+        }
+    }
+
+    if let Some(ref advanced) = cfg.advanced {
+        if let Some(ref availability) = advanced.developer_tools {
+            entries.extend(enum_dword("DeveloperToolsAvailability", availability));
+        }
+        if let Some(v) = advanced.add_profile {
+            entries.push(dword("", "BrowserAddProfileEnabled", v as u32));
+        }
+        if let Some(v) = advanced.guest_mode {
+            entries.push(dword("", "BrowserGuestModeEnabled", v as u32));
+        }
+        if let Some(v) = advanced.command_line_flag_warnings {
+            entries.push(dword("", "CommandLineFlagSecurityWarningsEnabled", v as u32));
+        }
+    }
+
+    if let Some(ref import) = cfg.import {
+        for (policy, allowed) in import.toggles() {
+            if let Some(v) = allowed {
+                entries.push(dword("", policy, v as u32));
+            }
+        }
+    }
+
+    if let Some(ref ie_mode) = cfg.ie_mode {
+        if let Some(ref level) = ie_mode.integration_level {
+            entries.extend(enum_dword("InternetExplorerIntegrationLevel", level));
+        }
+        if let Some(ref location) = ie_mode.site_list {
+            entries.push(sz("", "InternetExplorerIntegrationSiteList", location));
+        }
+    }
+
+    if let Some(ref raw) = cfg.raw {
+        for (name, val) in raw {
+            push_raw(&mut entries, name, val);
+        }
+    }
+
+    entries
+}
+
+/// Point entries for deprecated policies at their drop-in replacement.
+/// Returns each `(old, new)` rename that was made.
+pub fn replace_deprecated(entries: &mut [PolicyEntry]) -> Vec<(String, &'static str)> {
+    let mut renamed: Vec<(String, &'static str)> = Vec::new();
+
+    for entry in entries.iter_mut() {
+        let name = if entry.subkey.is_empty() {
+            &mut entry.name
+        } else {
+            &mut entry.subkey
+        };
+        let Some(new) = catalog::lookup(name).and_then(|d| d.drop_in_replacement()) else {
+            continue;
+        };
+        if !renamed.iter().any(|(old, _)| old == name) {
+            renamed.push((name.clone(), new.name));
+        }
+        *name = new.name.to_owned();
+    }
+
+    renamed
+}
+
+/// Map a `[raw]` value onto registry entries by its TOML type. Values that
+/// can't be represented are skipped here and reported by `validate`.
+fn push_raw(entries: &mut Vec<PolicyEntry>, name: &str, val: &toml::Value) {
+    match val {
+        toml::Value::Boolean(b) => entries.push(dword("", name, *b as u32)),
+        toml::Value::Integer(i) => {
+            if let Ok(v) = u32::try_from(*i) {
+                entries.push(dword("", name, v));
+            }
+        }
+        toml::Value::String(s) => entries.push(sz("", name, s)),
+        toml::Value::Array(items) => {
+            for (i, item) in items.iter().filter_map(|v| v.as_str()).enumerate() {
+                entries.push(sz(name, &(i + 1).to_string(), item));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parse `edge:ID` or `chrome:ID` into `ID;update_url`.
+fn resolve_extension(spec: &str) -> String {
+    format!("{};{}", extension_id(spec), store_url(spec))
+}
+
+/// The update URL of the store a spec names. A bare ID is from Edge's.
+fn store_url(spec: &str) -> &'static str {
+    if spec.starts_with("chrome:") {
+        CHROME_UPDATE_URL
+    } else {
+        EDGE_UPDATE_URL
+    }
+}
+
+/// The `ExtensionSettings` JSON object, keyed by bare ID. Extensions that
+/// Edge installs get their store's update URL unless one is given.
+fn extension_settings_json(settings: &BTreeMap<String, ExtensionSetting>) -> String {
+    let mut resolved = BTreeMap::new();
+    for (spec, setting) in settings {
+        let mut setting = setting.clone();
+        let installs = matches!(
+            setting.installation_mode.as_deref(),
+            Some("force_installed" | "normal_installed")
+        );
+        if installs && spec != "*" && setting.update_url.is_none() {
+            setting.update_url = Some(store_url(spec).to_owned());
+        }
+        resolved.insert(extension_id(spec), setting);
+    }
+    serde_json::to_string(&resolved).expect("extension settings serialize to JSON")
+}
+
+/// The ID in an `edge:ID` or `chrome:ID` spec; a bare ID or `*` as is.
+pub fn extension_id(spec: &str) -> &str {
+    spec.strip_prefix("edge:")
+        .or_else(|| spec.strip_prefix("chrome:"))
+        .unwrap_or(spec)
+}
+
+/// The config form of a forcelist value, `edge:ID` or `chrome:ID`; `None`
+/// for update URLs other than the two stores.
+pub fn extension_spec(value: &str) -> Option<String> {
+    match value.split_once(';')? {
+        (id, EDGE_UPDATE_URL) => Some(format!("edge:{id}")),
+        (id, CHROME_UPDATE_URL) => Some(format!("chrome:{id}")),
+        _ => None,
+    }
+}
+
+/// Windows environment variables and the Edge policy variable with the
+/// same meaning. Edge expands its own variables when it reads the policy,
+/// for the user running it.
+const PATH_VARIABLES: &[(&str, &str)] = &[
+    ("USERPROFILE", "${user_home}"),
+    ("USERNAME", "${user_name}"),
+    ("COMPUTERNAME", "${machine_name}"),
+    ("LOCALAPPDATA", "${local_app_data}"),
+    ("APPDATA", "${roaming_app_data}"),
+    ("PROGRAMDATA", "${global_app_data}"),
+    ("PROGRAMFILES", "${program_files}"),
+    ("WINDIR", "${windows}"),
+    ("SYSTEMROOT", "${windows}"),
+];
+
+/// `value` with each `%NAME%` Edge has its own variable for rewritten to
+/// that variable, and the names it has none for, which are left as written.
+/// Nothing is read from this process's environment, which may belong to
+/// another account or machine than the one Edge runs on.
+pub fn edge_path_variables(value: &str) -> (String, Vec<&str>) {
+    let mut out = String::new();
+    let mut unknown = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+        let Some(len) = after.find('%') else {
+            break;
+        };
+        let name = &after[..len];
+        // A stray percent sign, as in "50% off", isn't a reference.
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            out.push_str(&rest[..=start]);
+            rest = after;
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        match PATH_VARIABLES.iter().find(|(var, _)| var.eq_ignore_ascii_case(name)) {
+            Some((_, edge)) => out.push_str(edge),
+            None => {
+                unknown.push(name);
+                out.push_str(&rest[start..start + len + 2]);
+            }
+        }
+        rest = &after[len + 1..];
+    }
+    out.push_str(rest);
+    (out, unknown)
+}
+
+/// The `ManagedFavorites` JSON: an array whose first element may name the
+/// folder, followed by the favorites themselves.
+fn favorites_json(favorites: &FavoritesConfig) -> String {
+    let mut items = Vec::new();
+    if let Some(ref name) = favorites.toplevel_name {
+        items.push(serde_json::json!({ "toplevel_name": name }).to_string());
+    }
+    for favorite in &favorites.items {
+        // Straight to a string, keeping fields in declaration order.
+        items.push(serde_json::to_string(favorite).expect("favorites serialize to JSON"));
+    }
+    format!("[{}]", items.join(","))
+}
+
+/// DWORD entry for a config enum string, using the catalog's value names.
+/// Unknown names produce no entry; `validate` reports them.
+fn enum_dword(policy: &str, choice: &str) -> Option<PolicyEntry> {
+    let value = catalog::lookup(policy)?.value_of(choice)?;
+    Some(dword("", policy, value))
+}
+
+/// Numbered items of a list policy, from `1`.
+fn list<'a>(policy: &'a str, items: &'a [String]) -> impl Iterator<Item = PolicyEntry> + 'a {
+    items
+        .iter()
+        .enumerate()
+        .map(move |(i, item)| sz(policy, &(i + 1).to_string(), item))
+}
+
+fn dword(subkey: &str, name: &str, value: u32) -> PolicyEntry {
+    PolicyEntry {
+        subkey: subkey.to_owned(),
+        name: name.to_owned(),
+        value: RegValue::Dword(value),
+    }
+}
+
+fn sz(subkey: &str, name: &str, value: &str) -> PolicyEntry {
+    PolicyEntry {
+        subkey: subkey.to_owned(),
+        name: name.to_owned(),
+        value: RegValue::Sz(value.to_owned()),
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::policy::PolicyEntry;
+
+/// Edge's policy key, relative to the hive root.
+pub const EDGE_POLICY_PATH: &str = r"SOFTWARE\Policies\Microsoft\Edge";
+
+/// Chrome's policy key, which `import --from chrome` reads.
+pub const CHROME_POLICY_PATH: &str = r"SOFTWARE\Policies\Google\Chrome";
+
+/// Which hive policies are written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// HKCU, applies to the current user
+    #[default]
+    User,
+    /// HKLM, applies to every user and requires elevation
+    Machine,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scope::User => "HKCU",
+            Scope::Machine => "HKLM",
+        })
+    }
+}
+
+/// Storage for policy values under the Edge policy key. [`Registry`] is the
+/// real thing; tests and provisioning tools can supply their own.
+pub trait Backend {
+    /// The DWORD and string values currently under the key, including
+    /// numbered items in its subkeys.
+    fn read_current(&self, scope: Scope) -> Result<Vec<PolicyEntry>>;
+
+    /// Write a full policy set. List subkeys in `entries` are rewritten
+    /// wholesale so no stale numbered entries survive; other subkeys are
+    /// left alone.
+    fn apply(&self, entries: &[PolicyEntry], scope: Scope) -> Result<()>;
+
+    /// Write individual values without touching anything else under the key.
+    fn set_values(&self, entries: &[PolicyEntry], scope: Scope) -> Result<()>;
+
+    /// Delete values by path relative to the key, either a top-level name or
+    /// `Subkey\Name` for a list item; ones that don't exist are skipped.
+    fn remove_values(&self, paths: &[&str], scope: Scope) -> Result<()>;
+}
+
+/// The Windows registry.
+#[cfg(windows)]
+pub struct Registry;
+
+#[cfg(windows)]
+mod windows {
+    use tracing::debug;
+    use winreg::enums::*;
+    use winreg::RegKey;
+
+    use super::{Backend, Registry, Scope, EDGE_POLICY_PATH};
+    use crate::error::{Error, Result};
+    use crate::policy::{PolicyEntry, RegValue};
+
+    impl Scope {
+        pub fn hive(self) -> RegKey {
+            RegKey::predef(match self {
+                Scope::User => HKEY_CURRENT_USER,
+                Scope::Machine => HKEY_LOCAL_MACHINE,
+            })
+        }
+    }
+
+    impl Registry {
+        /// Read a policy key laid out like Edge's, such as Chrome's.
+        pub fn read_key(&self, scope: Scope, path: &str) -> Result<Vec<PolicyEntry>> {
+            let base = match scope.hive().open_subkey(path) {
+                Ok(k) => k,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    debug!(r"{scope}\{path} does not exist");
+                    return Ok(Vec::new());
+                }
+                Err(e) => {
+                    return Err(Error::registry(
+                        format!(r"Failed to open {scope}\{path}"),
+                        e,
+                    ))
+                }
+            };
+
+            let mut entries = read_values(&base, "");
+            for name in base.enum_keys().filter_map(|r| r.ok()) {
+                if let Ok(sub) = base.open_subkey(&name) {
+                    entries.extend(read_values(&sub, &name));
+                }
+            }
+            debug!(r"read {} values from {scope}\{path}", entries.len());
+            Ok(entries)
+        }
+    }
+
+    impl Backend for Registry {
+        fn read_current(&self, scope: Scope) -> Result<Vec<PolicyEntry>> {
+            self.read_key(scope, EDGE_POLICY_PATH)
+        }
+
+        fn apply(&self, entries: &[PolicyEntry], scope: Scope) -> Result<()> {
+            let hive = scope.hive();
+            let (base, _) = hive
+                .create_subkey(EDGE_POLICY_PATH)
+                .map_err(|e| Error::registry("Failed to open/create Edge policy key", e))?;
+
+            // Delete-then-recreate the lists being written to avoid stale
+            // numbered entries. Lists the config doesn't set are left alone.
+            for entry in entries.iter().filter(|e| !e.subkey.is_empty()) {
+                if base.delete_subkey_all(&entry.subkey).is_ok() {
+                    debug!(r"deleted {scope}\{EDGE_POLICY_PATH}\{}", entry.subkey);
+                }
+            }
+
+            for entry in entries {
+                write_entry(&hive, &base, entry, scope)?;
+            }
+
+            Ok(())
+        }
+
+        fn set_values(&self, entries: &[PolicyEntry], scope: Scope) -> Result<()> {
+            let hive = scope.hive();
+            let (base, _) = hive
+                .create_subkey(EDGE_POLICY_PATH)
+                .map_err(|e| Error::registry("Failed to open/create Edge policy key", e))?;
+            for entry in entries {
+                write_entry(&hive, &base, entry, scope)?;
+            }
+            Ok(())
+        }
+
+        fn remove_values(&self, paths: &[&str], scope: Scope) -> Result<()> {
+            let hive = scope.hive();
+            for path in paths {
+                let (subkey, name) = match path.rsplit_once('\\') {
+                    Some((subkey, name)) => (format!(r"{EDGE_POLICY_PATH}\{subkey}"), name),
+                    None => (EDGE_POLICY_PATH.to_owned(), *path),
+                };
+                let key = match hive.open_subkey_with_flags(&subkey, KEY_SET_VALUE) {
+                    Ok(k) => k,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(Error::registry(format!("Failed to open {subkey}"), e)),
+                };
+                match key.delete_value(name) {
+                    Ok(()) => debug!(r"deleted {scope}\{EDGE_POLICY_PATH}\{path}"),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(Error::registry(format!("Failed to delete {path}"), e)),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn write_entry(hive: &RegKey, base: &RegKey, entry: &PolicyEntry, scope: Scope) -> Result<()> {
+        let key = if entry.subkey.is_empty() {
+            base
+        } else {
+            &hive
+                .create_subkey(format!(r"{}\{}", EDGE_POLICY_PATH, entry.subkey))
+                .map_err(|e| {
+                    Error::registry(format!("Failed to create subkey: {}", entry.subkey), e)
+                })?
+                .0
+        };
+
+        match &entry.value {
+            RegValue::Dword(v) => key
+                .set_value(&entry.name, v)
+                .map_err(|e| Error::registry(format!("Failed to set DWORD: {}", entry.name), e))?,
+            RegValue::Sz(v) => key
+                .set_value(&entry.name, v)
+                .map_err(|e| Error::registry(format!("Failed to set SZ: {}", entry.name), e))?,
+        }
+        debug!(r"set {scope}\{EDGE_POLICY_PATH}\{entry}");
+        Ok(())
+    }
+
+    fn read_values(key: &RegKey, subkey: &str) -> Vec<PolicyEntry> {
+        key.enum_values()
+            .filter_map(|r| r.ok())
+            .filter_map(|(name, value)| {
+                let value = match value.vtype {
+                    REG_DWORD => RegValue::Dword(key.get_value(&name).ok()?),
+                    REG_SZ | REG_EXPAND_SZ => RegValue::Sz(key.get_value(&name).ok()?),
+                    _ => return None,
+                };
+                Some(PolicyEntry {
+                    subkey: subkey.to_owned(),
+                    name,
+                    value,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,879 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use url::Url;
+
+use crate::catalog::{self, PolicyKind, ValueFormat};
+use crate::config::{Config, ExtensionSetting, Favorite, SearchConfig};
+use crate::edge::EdgeVersion;
+use crate::error::{Error, Result};
+use crate::policy::{self, PolicyEntry, RegValue, CLEAR_ON_EXIT, EXTENSION_LISTS};
+use crate::registry::Scope;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found in the config or the entries built from it.
+#[derive(Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{label}: {}", self.message)
+    }
+}
+
+/// Check the config and its built entries against the policy catalog.
+pub fn check(cfg: &Config, entries: &[PolicyEntry]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    if let Some(ref search) = cfg.search {
+        check_search(search, &mut issues);
+    }
+    if let Some(ref exts) = cfg.extensions {
+        check_extension_lists(exts, &mut issues);
+    }
+    if let Some(ref settings) = cfg.extension_settings {
+        check_extension_settings(settings, &mut issues);
+    }
+    if let Some(ref browser) = cfg.browser {
+        if let Some(ref mode) = browser.restore_on_startup {
+            check_enum(
+                "browser.restore_on_startup",
+                "RestoreOnStartup",
+                mode,
+                &mut issues,
+            );
+        }
+        if let Some(ref level) = browser.relaunch_notification {
+            check_enum(
+                "browser.relaunch_notification",
+                "RelaunchNotification",
+                level,
+                &mut issues,
+            );
+        }
+        if let Some(hours) = browser.relaunch_notification_period {
+            let max = u32::MAX / policy::HOUR_MS;
+            if !(1..=max).contains(&hours) {
+                issues.push(error(format!(
+                    "browser.relaunch_notification_period = {hours} is out of range; \
+                     expected 1 to {max} hours"
+                )));
+            }
+        }
+        if let Some(ref availability) = browser.inprivate {
+            check_enum(
+                "browser.inprivate",
+                "InPrivateModeAvailability",
+                availability,
+                &mut issues,
+            );
+        }
+    }
+    if let Some(url) = cfg.report.as_ref().and_then(|r| r.webhook.as_deref()) {
+        if let Some(problem) = web_url_problem(url) {
+            issues.push(error(format!("report.webhook: {problem}")));
+        }
+    }
+    if let Some(ref privacy) = cfg.privacy {
+        if let Some(ref level) = privacy.tracking_prevention {
+            check_enum(
+                "privacy.tracking_prevention",
+                "TrackingPrevention",
+                level,
+                &mut issues,
+            );
+        }
+        if let Some(ref trigger) = privacy.password_protection_warning_trigger {
+            check_enum(
+                "privacy.password_protection_warning_trigger",
+                "PasswordProtectionWarningTrigger",
+                trigger,
+                &mut issues,
+            );
+        }
+        for kind in privacy.clear_on_exit.iter().flatten() {
+            if !CLEAR_ON_EXIT.iter().any(|(k, _)| k == kind) {
+                let hint = match kind.as_str() {
+                    "cookies" => "; content.cookies = \"session_only\" clears cookies on exit",
+                    _ => "; use \"all\" to clear everything",
+                };
+                issues.push(error(format!(
+                    "privacy.clear_on_exit: Edge can't clear \"{kind}\" on its own{hint}"
+                )));
+            }
+        }
+        for template in privacy.dns_over_https_templates.iter().flatten() {
+            if let Some(problem) = doh_template_problem(template) {
+                issues.push(error(format!(
+                    "privacy.dns_over_https_templates: {problem}"
+                )));
+            }
+        }
+    }
+
+    if let Some(dir) = cfg
+        .downloads
+        .as_ref()
+        .and_then(|d| d.default_directory.as_ref())
+    {
+        for name in policy::edge_path_variables(dir).1 {
+            issues.push(warning(format!(
+                "downloads.default_directory: Edge doesn't expand %{name}%; \
+                 use one of its own variables such as ${{user_home}}"
+            )));
+        }
+    }
+    if let Some(level) = cfg.downloads.as_ref().and_then(|d| d.restrictions.as_ref()) {
+        check_enum(
+            "downloads.restrictions",
+            "DownloadRestrictions",
+            level,
+            &mut issues,
+        );
+    }
+
+    if let Some(ref content) = cfg.content {
+        let settings = [
+            ("content.cookies", "DefaultCookiesSetting", &content.cookies),
+            (
+                "content.javascript",
+                "DefaultJavaScriptSetting",
+                &content.javascript,
+            ),
+            ("content.popups", "DefaultPopupsSetting", &content.popups),
+            (
+                "content.notifications",
+                "DefaultNotificationsSetting",
+                &content.notifications,
+            ),
+            (
+                "content.geolocation",
+                "DefaultGeolocationSetting",
+                &content.geolocation,
+            ),
+            ("content.images", "DefaultImagesSetting", &content.images),
+        ];
+        for (field, policy, choice) in settings {
+            if let Some(choice) = choice {
+                check_enum(field, policy, choice, &mut issues);
+            }
+        }
+    }
+
+    if let Some(mode) = cfg.account.as_ref().and_then(|a| a.browser_signin.as_ref()) {
+        check_enum("account.browser_signin", "BrowserSignin", mode, &mut issues);
+    }
+
+    if let Some(ref performance) = cfg.performance {
+        let settings = [
+            (
+                "performance.sleeping_tabs_timeout",
+                "SleepingTabsTimeout",
+                &performance.sleeping_tabs_timeout,
+            ),
+            (
+                "performance.efficiency_mode",
+                "EfficiencyMode",
+                &performance.efficiency_mode,
+            ),
+        ];
+        for (field, policy, choice) in settings {
+            if let Some(choice) = choice {
+                check_enum(field, policy, choice, &mut issues);
+            }
+        }
+    }
+
+    if let Some(types) = cfg
+        .appearance
+        .as_ref()
+        .and_then(|a| a.new_tab_background.as_ref())
+    {
+        check_enum(
+            "appearance.new_tab_background",
+            "NewTabPageAllowedBackgroundTypes",
+            types,
+            &mut issues,
+        );
+    }
+
+    if let Some(ref telemetry) = cfg.telemetry {
+        let settings = [
+            (
+                "telemetry.diagnostic_data",
+                "DiagnosticData",
+                &telemetry.diagnostic_data,
+            ),
+            (
+                "telemetry.experimentation_and_configuration_service",
+                "ExperimentationAndConfigurationServiceControl",
+                &telemetry.experimentation_and_configuration_service,
+            ),
+        ];
+        for (field, policy, choice) in settings {
+            if let Some(choice) = choice {
+                check_enum(field, policy, choice, &mut issues);
+            }
+        }
+    }
+
+    if let Some(availability) = cfg
+        .advanced
+        .as_ref()
+        .and_then(|a| a.developer_tools.as_ref())
+    {
+        check_enum(
+            "advanced.developer_tools",
+            "DeveloperToolsAvailability",
+            availability,
+            &mut issues,
+        );
+    }
+
+    if let Some(ref ie_mode) = cfg.ie_mode {
+        if let Some(ref level) = ie_mode.integration_level {
+            check_enum(
+                "ie_mode.integration_level",
+                "InternetExplorerIntegrationLevel",
+                level,
+                &mut issues,
+            );
+        }
+        if let Some(problem) = ie_mode.site_list.as_deref().and_then(site_list_problem) {
+            issues.push(error(format!("ie_mode.site_list: {problem}")));
+        }
+    }
+
+    if let Some(ref favorites) = cfg.favorites {
+        check_favorites("/", &favorites.items, &mut issues);
+    }
+
+    if let Some(ref raw) = cfg.raw {
+        for (name, val) in raw {
+            check_raw_value(name, val, &mut issues);
+        }
+    }
+
+    let mut seen_lists = Vec::new();
+    for entry in entries {
+        let key = config_label(cfg, entry.policy_name());
+        // Each list policy only needs its type checked once; formats are
+        // checked per item.
+        if !entry.subkey.is_empty() {
+            if seen_lists.contains(&entry.subkey.as_str()) {
+                check_format(entry, key, &mut issues);
+                continue;
+            }
+            seen_lists.push(&entry.subkey);
+        }
+        check_entry(entry, key, &mut issues);
+    }
+
+    check_conflicts(entries, &mut issues);
+
+    issues
+}
+
+/// Combinations of settings that contradict each other or make one of them
+/// dead weight.
+fn check_conflicts(entries: &[PolicyEntry], issues: &mut Vec<Issue>) {
+    // Typed keys and [raw] can target the same policy; for lists the numbered
+    // items then collide.
+    let mut written: Vec<(&str, &str)> = Vec::new();
+    let mut reported: Vec<&str> = Vec::new();
+    for entry in entries {
+        let id = (entry.subkey.as_str(), entry.name.as_str());
+        let name = entry.policy_name();
+        if !written.contains(&id) {
+            written.push(id);
+        } else if !reported.contains(&name) {
+            reported.push(name);
+            issues.push(error(format!(
+                "{name} is set more than once (typed key and [raw]?)"
+            )));
+        }
+    }
+
+    let dword = |name: &str| {
+        entries.iter().find_map(|e| match e.value {
+            RegValue::Dword(v) if e.subkey.is_empty() && e.name == name => Some(v),
+            _ => None,
+        })
+    };
+    let has = |name: &str| entries.iter().any(|e| e.policy_name() == name);
+
+    if dword("HomepageIsNewTabPage") == Some(1) && has("HomepageLocation") {
+        issues.push(warning(
+            "HomepageLocation is ignored because HomepageIsNewTabPage is enabled".to_owned(),
+        ));
+    }
+
+    match dword("RestoreOnStartup") {
+        Some(4 | 6) if !has("RestoreOnStartupURLs") => issues.push(error(
+            "RestoreOnStartup opens a list of URLs, but no RestoreOnStartupURLs are set".to_owned(),
+        )),
+        Some(1 | 5) if has("RestoreOnStartupURLs") => issues.push(warning(
+            "RestoreOnStartupURLs is ignored unless RestoreOnStartup is \"urls\" or \
+             \"previous_session_and_urls\""
+                .to_owned(),
+        )),
+        _ => {}
+    }
+
+    if dword("DefaultSearchProviderEnabled") == Some(0) {
+        for entry in entries {
+            let name = entry.policy_name();
+            if name.starts_with("DefaultSearchProvider") && name != "DefaultSearchProviderEnabled" {
+                issues.push(warning(format!(
+                    "{name} is ignored because DefaultSearchProviderEnabled is off"
+                )));
+            }
+        }
+    }
+
+    if dword("PasswordManagerEnabled") == Some(0) {
+        for name in ["PasswordMonitorAllowed", "PasswordGeneratorEnabled"] {
+            if dword(name) == Some(1) {
+                issues.push(warning(format!(
+                    "{name} has no effect while PasswordManagerEnabled is off"
+                )));
+            }
+        }
+    }
+
+    if dword("SleepingTabsEnabled") == Some(0) && has("SleepingTabsTimeout") {
+        issues.push(warning(
+            "SleepingTabsTimeout is ignored because SleepingTabsEnabled is off".to_owned(),
+        ));
+    }
+
+    if dword("AlwaysOpenPdfExternally") == Some(1) && has("RestorePdfView") {
+        issues.push(warning(
+            "RestorePdfView is ignored because AlwaysOpenPdfExternally is on".to_owned(),
+        ));
+    }
+
+    if has("InternetExplorerIntegrationSiteList")
+        && !matches!(dword("InternetExplorerIntegrationLevel"), Some(1 | 2))
+    {
+        issues.push(warning(
+            "InternetExplorerIntegrationSiteList is ignored unless \
+             InternetExplorerIntegrationLevel is \"ie_mode\" or \"needie\""
+                .to_owned(),
+        ));
+    }
+
+    if dword("SpellcheckEnabled") == Some(0) && has("SpellcheckLanguage") {
+        issues.push(warning(
+            "SpellcheckLanguage is ignored because SpellcheckEnabled is off".to_owned(),
+        ));
+    }
+    for entry in entries.iter().filter(|e| e.subkey == "SpellcheckLanguage") {
+        if entries
+            .iter()
+            .any(|e| e.subkey == "SpellcheckLanguageBlocklist" && e.value == entry.value)
+        {
+            issues.push(warning(format!(
+                "{} is in both SpellcheckLanguage and SpellcheckLanguageBlocklist; \
+                 drop it from one",
+                entry.value
+            )));
+        }
+    }
+
+    let blocks_all = entries
+        .iter()
+        .any(|e| e.subkey == "ExtensionInstallBlocklist" && e.value == RegValue::Sz("*".into()));
+    if has("ExtensionInstallAllowlist") && !blocks_all {
+        issues.push(warning(
+            "ExtensionInstallAllowlist only matters with ExtensionInstallBlocklist = [\"*\"]"
+                .to_owned(),
+        ));
+    }
+
+    let doh_mode = entries.iter().find_map(|e| match e.value {
+        RegValue::Sz(ref v) if e.name == "DnsOverHttpsMode" => Some(v.as_str()),
+        _ => None,
+    });
+    match doh_mode {
+        Some("secure") if !has("DnsOverHttpsTemplates") => issues.push(error(
+            "DnsOverHttpsMode is \"secure\", but no DnsOverHttpsTemplates are set".to_owned(),
+        )),
+        Some("off") if has("DnsOverHttpsTemplates") => issues.push(warning(
+            "DnsOverHttpsTemplates is ignored because DnsOverHttpsMode is \"off\"".to_owned(),
+        )),
+        _ => {}
+    }
+
+    for entry in entries {
+        let Some(setting) = entry.subkey.strip_suffix("AllowedForUrls") else {
+            continue;
+        };
+        let blocked = format!("{setting}BlockedForUrls");
+        if entries
+            .iter()
+            .any(|e| e.subkey == blocked && e.value == entry.value)
+        {
+            issues.push(warning(format!(
+                "{} is in both {} and {blocked}; drop it from one",
+                entry.value, entry.subkey
+            )));
+        }
+    }
+
+    let proxy_mode = entries.iter().find_map(|e| match e.value {
+        RegValue::Sz(ref v) if e.name == "ProxyMode" => Some(v.as_str()),
+        _ => None,
+    });
+    if has("ProxyPacUrl") && proxy_mode.is_some_and(|m| m != "pac_script") {
+        issues.push(warning(
+            "ProxyPacUrl is ignored unless ProxyMode is \"pac_script\"".to_owned(),
+        ));
+    }
+}
+
+/// Warn about policies the installed Edge version doesn't honor.
+pub fn check_support(entries: &[PolicyEntry], version: EdgeVersion) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut seen = Vec::new();
+
+    for entry in entries {
+        let name = entry.policy_name();
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name);
+
+        let Some(def) = catalog::lookup(name) else {
+            continue;
+        };
+        if version.major() < def.since {
+            issues.push(warning(format!(
+                "{name} requires Edge {} or later (installed: {version})",
+                def.since
+            )));
+        } else if let Some(removed) = def.removed.filter(|&r| version.major() >= r) {
+            issues.push(warning(format!(
+                "{name} was removed in Edge {removed} and is ignored (installed: {version})"
+            )));
+        }
+    }
+
+    issues
+}
+
+/// Warn about machine-only policies when writing at user scope.
+pub fn check_scope(entries: &[PolicyEntry], scope: Scope) -> Vec<Issue> {
+    if scope == Scope::Machine {
+        return Vec::new();
+    }
+
+    let mut issues = Vec::new();
+    let mut seen = Vec::new();
+    for entry in entries {
+        let name = entry.policy_name();
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name);
+
+        if catalog::lookup(name).is_some_and(|d| d.machine_only) {
+            issues.push(warning(format!(
+                "{name} is only honored at machine scope and will be ignored in HKCU; \
+                 apply with --scope machine"
+            )));
+        }
+    }
+    issues
+}
+
+/// Fail with [`Error::Validation`] if any of the issues is an error.
+pub fn ensure_valid(issues: &[Issue]) -> Result<()> {
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(Error::Validation { errors });
+    }
+    Ok(())
+}
+
+/// A DefaultSearchProvider block with a broken URL is ignored by Edge as a
+/// whole, so template problems are errors rather than warnings.
+fn check_search(search: &SearchConfig, issues: &mut Vec<Issue>) {
+    if let Some(ref mode) = search.sidebar_search {
+        check_enum(
+            "search.sidebar_search",
+            "SearchInSidebarEnabled",
+            mode,
+            issues,
+        );
+    }
+    if !search.sets_provider() {
+        return;
+    }
+    match search.search_url {
+        Some(ref url) => check_search_template("search.search_url", url, issues),
+        None => issues.push(error(
+            "search.search_url is required; Edge ignores the search provider without it".to_owned(),
+        )),
+    }
+    if let Some(ref url) = search.suggest_url {
+        check_search_template("search.suggest_url", url, issues);
+    }
+}
+
+fn check_search_template(field: &str, template: &str, issues: &mut Vec<Issue>) {
+    if !template.contains("{searchTerms}") {
+        issues.push(error(format!(
+            "{field} must contain the {{searchTerms}} placeholder, e.g. \
+             \"https://duckduckgo.com/?q={{searchTerms}}\""
+        )));
+    }
+    if let Some(problem) = web_url_problem(template) {
+        issues.push(error(format!("{field}: {problem}")));
+    }
+}
+
+/// Why `value` isn't a usable http(s) URL, if it isn't.
+fn web_url_problem(value: &str) -> Option<String> {
+    match Url::parse(value) {
+        Err(e) => Some(format!("\"{value}\" is not a valid URL ({e})")),
+        Ok(url) if !matches!(url.scheme(), "http" | "https") => Some(format!(
+            "\"{value}\" must use http or https, not {}",
+            url.scheme()
+        )),
+        Ok(url) if url.host_str().is_none_or(str::is_empty) => {
+            Some(format!("\"{value}\" has no host"))
+        }
+        Ok(_) => None,
+    }
+}
+
+/// Edge fetches the site list over http(s), or reads it from a `file://` URL
+/// or UNC path.
+fn site_list_problem(value: &str) -> Option<String> {
+    if value.starts_with(r"\\") {
+        return None;
+    }
+    match Url::parse(value) {
+        Ok(url) if url.scheme() == "file" => None,
+        _ => web_url_problem(value).map(|p| format!("{p}; a file:// URL or UNC path also works")),
+    }
+}
+
+/// DoH resolvers must be reached over https; the template may carry a
+/// `{?dns}` variable, which parses as part of the path.
+fn doh_template_problem(value: &str) -> Option<String> {
+    match Url::parse(value) {
+        Err(e) => Some(format!("\"{value}\" is not a valid URL ({e})")),
+        Ok(url) if url.scheme() != "https" => Some(format!("\"{value}\" must use https")),
+        Ok(url) if url.host_str().is_none_or(str::is_empty) => {
+            Some(format!("\"{value}\" has no host"))
+        }
+        Ok(_) => None,
+    }
+}
+
+/// Each favorite is either a link or a folder. `folder` is the path of the
+/// enclosing folders, for messages.
+fn check_favorites(folder: &str, items: &[Favorite], issues: &mut Vec<Issue>) {
+    for item in items {
+        let path = format!("{folder}{}", item.name);
+        if item.name.trim().is_empty() {
+            issues.push(error(format!(
+                "favorites: an item in \"{folder}\" has no name"
+            )));
+        }
+        match (&item.url, &item.children) {
+            (Some(_), None) => {}
+            (None, Some(children)) => check_favorites(&format!("{path}/"), children, issues),
+            (Some(_), Some(_)) => issues.push(error(format!(
+                "favorites: \"{path}\" has both a url and children; use one"
+            ))),
+            (None, None) => issues.push(error(format!(
+                "favorites: \"{path}\" needs a url, or children to be a folder"
+            ))),
+        }
+    }
+}
+
+/// Typed config enums use the catalog's value names as their spelling.
+fn check_enum(field: &str, policy: &str, choice: &str, issues: &mut Vec<Issue>) {
+    let Some(def) = catalog::lookup(policy) else {
+        return;
+    };
+    if def.value_of(choice).is_none() {
+        let names: Vec<_> = def.values.iter().map(|(_, n)| format!("\"{n}\"")).collect();
+        issues.push(error(format!(
+            "{field} = \"{choice}\" is not valid; expected one of {}",
+            names.join(", ")
+        )));
+    }
+}
+
+fn check_raw_value(name: &str, val: &toml::Value, issues: &mut Vec<Issue>) {
+    let problem = match val {
+        toml::Value::Boolean(_) | toml::Value::String(_) => None,
+        toml::Value::Integer(i) if u32::try_from(*i).is_err() => {
+            Some(format!("{i} does not fit in a DWORD"))
+        }
+        toml::Value::Integer(_) => None,
+        toml::Value::Array(items) if items.iter().any(|v| !v.is_str()) => {
+            Some("list items must be strings".to_owned())
+        }
+        toml::Value::Array(_) => None,
+        other => Some(format!("unsupported value type {}", other.type_str())),
+    };
+
+    if let Some(problem) = problem {
+        issues.push(error(format!("raw.{name}: {problem}")));
+    }
+}
+
+/// What the user wrote to set `policy`: its config key, or the policy name
+/// for `[raw]` entries and policies no config key writes.
+fn config_label<'a>(cfg: &Config, policy: &'a str) -> &'a str {
+    let raw = cfg
+        .raw
+        .as_ref()
+        .is_some_and(|raw| raw.keys().any(|k| k.eq_ignore_ascii_case(policy)));
+    match policy::config_key(policy) {
+        Some(key) if !raw => key,
+        _ => policy,
+    }
+}
+
+/// `key` is the entry's [`config_label`].
+fn check_entry(entry: &PolicyEntry, key: &str, issues: &mut Vec<Issue>) {
+    let is_list = !entry.subkey.is_empty();
+    let name = entry.policy_name();
+
+    let Some(def) = catalog::lookup(name) else {
+        issues.push(warning(format!(
+            "{name} is not in edge-profile's policy catalog, so its value isn't checked{}",
+            catalog::did_you_mean(name)
+        )));
+        return;
+    };
+
+    if let Some(new) = def.replaced_by {
+        let hint = if def.drop_in_replacement().is_some() {
+            "run `edge-profile migrate-config` or apply with --replace-deprecated"
+        } else {
+            "it takes a different value type, so update the config by hand"
+        };
+        issues.push(warning(format!(
+            "{name} is deprecated in favor of {new}; {hint}"
+        )));
+    }
+
+    let type_ok = match (&entry.value, is_list) {
+        (_, true) => def.kind == PolicyKind::List,
+        (RegValue::Dword(_), false) => {
+            matches!(def.kind, PolicyKind::Boolean | PolicyKind::Integer)
+        }
+        (RegValue::Sz(_), false) => {
+            matches!(def.kind, PolicyKind::String | PolicyKind::Dictionary)
+        }
+    };
+    if !type_ok {
+        issues.push(error(format!(
+            "{name} expects {}, got {}",
+            def.kind.registry_type(),
+            if is_list {
+                "a list"
+            } else {
+                entry.value.type_name()
+            }
+        )));
+        return;
+    }
+
+    match &entry.value {
+        RegValue::Dword(v)
+            if !is_list && !def.values.is_empty() && def.value_name(*v).is_none() =>
+        {
+            issues.push(error(format!(
+                "{name} = {v} is not a permitted value; valid options: {}",
+                def.describe_values()
+            )));
+        }
+        RegValue::Dword(v) if !is_list && def.values.is_empty() => {
+            if let Some((lo, hi)) = def.dword_bounds() {
+                if *v < lo || *v > hi {
+                    issues.push(error(format!("{name} = {v} is out of range ({lo}..={hi})")));
+                }
+            }
+        }
+        RegValue::Sz(v)
+            if !is_list && !def.allowed.is_empty() && !def.allowed.contains(&v.as_str()) =>
+        {
+            issues.push(error(format!(
+                "{name} = \"{v}\" is not one of: {}",
+                def.allowed.join(", ")
+            )));
+        }
+        _ => {}
+    }
+
+    check_format(entry, key, issues);
+}
+
+/// Check REG_SZ values against the syntax their policy expects, reporting
+/// problems against `key`, the entry's [`config_label`].
+fn check_format(entry: &PolicyEntry, key: &str, issues: &mut Vec<Issue>) {
+    let RegValue::Sz(ref value) = entry.value else {
+        return;
+    };
+    let name = entry.policy_name();
+    let Some(format) = catalog::lookup(name).and_then(|def| def.format) else {
+        return;
+    };
+
+    let problem = match format {
+        ValueFormat::Url => web_url_problem(value),
+        ValueFormat::UrlPattern => url_pattern_problem(value),
+        ValueFormat::Forcelist => forcelist_problem(value),
+    };
+    if let Some(problem) = problem {
+        issues.push(error(format!("{key}: {problem}")));
+    }
+}
+
+fn url_pattern_problem(value: &str) -> Option<String> {
+    if value.trim().is_empty() {
+        return Some("empty URL pattern".to_owned());
+    }
+    if value.chars().any(char::is_whitespace) {
+        return Some(format!("\"{value}\" contains whitespace"));
+    }
+    let scheme = value.split_once("://")?.0;
+    let valid = scheme == "*"
+        || scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    (!valid).then(|| format!("\"{value}\" has an invalid scheme \"{scheme}\""))
+}
+
+/// `[extensions] blocklist` and `allowlist` take arrays of IDs or `"*"`,
+/// `sources` an array of URL patterns (checked with the other formats).
+fn check_extension_lists(exts: &toml::map::Map<String, toml::Value>, issues: &mut Vec<Issue>) {
+    for (key, policy) in EXTENSION_LISTS {
+        let Some(value) = exts.get(*key) else {
+            continue;
+        };
+        let Some(items) = value.as_array() else {
+            issues.push(error(format!("extensions.{key} must be an array")));
+            continue;
+        };
+        let patterns = catalog::lookup(policy).is_some_and(|d| d.format.is_some());
+        for item in items {
+            let Some(spec) = item.as_str() else {
+                issues.push(error(format!("extensions.{key}: {item} is not a string")));
+                continue;
+            };
+            if patterns {
+                continue;
+            }
+            let id = policy::extension_id(spec);
+            if id != "*" {
+                if let Some(problem) = forcelist_problem(id) {
+                    issues.push(error(format!("extensions.{key}: {problem}")));
+                }
+            }
+        }
+    }
+}
+
+fn check_extension_settings(
+    settings: &BTreeMap<String, ExtensionSetting>,
+    issues: &mut Vec<Issue>,
+) {
+    const MODES: &[&str] = &[
+        "allowed",
+        "blocked",
+        "force_installed",
+        "normal_installed",
+        "removed",
+    ];
+    const TOOLBAR_STATES: &[&str] = &["force_shown", "default_shown"];
+
+    for (spec, setting) in settings {
+        let field = format!("extension_settings.\"{spec}\"");
+        if spec != "*" {
+            if let Some(problem) = forcelist_problem(policy::extension_id(spec)) {
+                issues.push(error(format!("{field}: {problem}")));
+            }
+        }
+        if let Some(ref mode) = setting.installation_mode {
+            if !MODES.contains(&mode.as_str()) {
+                issues.push(error(format!(
+                    "{field}.installation_mode = \"{mode}\" is not valid; expected one of {}",
+                    quoted(MODES)
+                )));
+            } else if spec == "*" && mode.ends_with("_installed") {
+                issues.push(error(format!(
+                    "{field}.installation_mode can't be \"{mode}\"; name the extensions to install"
+                )));
+            }
+        }
+        if let Some(ref state) = setting.toolbar_state {
+            if !TOOLBAR_STATES.contains(&state.as_str()) {
+                issues.push(error(format!(
+                    "{field}.toolbar_state = \"{state}\" is not valid; expected one of {}",
+                    quoted(TOOLBAR_STATES)
+                )));
+            }
+        }
+        if let Some(problem) = setting.update_url.as_deref().and_then(web_url_problem) {
+            issues.push(error(format!("{field}.update_url: {problem}")));
+        }
+    }
+}
+
+fn quoted(names: &[&str]) -> String {
+    let names: Vec<String> = names.iter().map(|n| format!("\"{n}\"")).collect();
+    names.join(", ")
+}
+
+/// Forcelist entries are `id;update_url`, as produced by `resolve_extension`.
+fn forcelist_problem(value: &str) -> Option<String> {
+    let (id, url) = value.split_once(';').unwrap_or((value, ""));
+    if id.len() != 32 || !id.chars().all(|c| ('a'..='p').contains(&c)) {
+        return Some(format!(
+            "\"{id}\" is not an extension ID (32 characters a-p)"
+        ));
+    }
+    if url.is_empty() {
+        return None;
+    }
+    web_url_problem(url).map(|p| format!("update URL {p}"))
+}
+
+fn warning(message: String) -> Issue {
+    Issue {
+        severity: Severity::Warning,
+        message,
+    }
+}
+
+fn error(message: String) -> Issue {
+    Issue {
+        severity: Severity::Error,
+        message,
+    }
+}
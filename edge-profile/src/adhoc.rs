@@ -0,0 +1,167 @@
+//! `set`, `get` and `remove`: single policies, outside the config.
+
+use anyhow::{bail, Result};
+use edge_profile_core::PolicySet;
+
+use crate::catalog::{self, PolicyKind};
+use crate::diff::{self, Change};
+use crate::registry::{self, Backend, Registry, Scope};
+use crate::{config, history, journal, output, policy};
+
+/// Write one policy. The catalog decides the registry type: booleans take
+/// `1`/`0`/`true`/`false`, enumerated integers a number or a value name,
+/// lists one argument per item. Validated like the same setting under
+/// `[raw]` would be.
+pub fn set(name: &str, values: &[String], scope: Scope) -> Result<()> {
+    let Some(def) = catalog::lookup(name) else {
+        bail!("Unknown policy {name}{}", catalog::did_you_mean(name));
+    };
+    if def.kind != PolicyKind::List && values.len() != 1 {
+        bail!("{} takes a single value", def.name);
+    }
+    let value = match def.kind {
+        PolicyKind::Boolean => match values[0].to_ascii_lowercase().as_str() {
+            "1" | "true" | "on" => toml::Value::Boolean(true),
+            "0" | "false" | "off" => toml::Value::Boolean(false),
+            other => bail!("{} is a boolean; expected 1 or 0, got {other}", def.name),
+        },
+        PolicyKind::Integer => {
+            let v = &values[0];
+            match v.parse::<u32>().ok().or_else(|| def.value_of(v)) {
+                Some(n) => toml::Value::Integer(n.into()),
+                None if def.values.is_empty() => bail!("{} takes an integer, got {v}", def.name),
+                None => bail!(
+                    "{} takes one of {}, got {v}",
+                    def.name,
+                    def.describe_values()
+                ),
+            }
+        }
+        PolicyKind::String | PolicyKind::Dictionary => toml::Value::String(values[0].clone()),
+        PolicyKind::List => toml::Value::Array(
+            values
+                .iter()
+                .map(|v| toml::Value::String(v.clone()))
+                .collect(),
+        ),
+    };
+
+    let set = PolicySet::new().raw(def.name, value);
+    crate::report_issues(&set.check(scope))?;
+    let entries = set.entries();
+    let current = Registry.read_current(scope)?;
+    let changes = diff::compare(&entries, &current);
+    if changes.is_empty() {
+        println!("{} is already set to that in {scope}.", def.name);
+        return Ok(());
+    }
+
+    let stale: Vec<String> = changes
+        .iter()
+        .filter_map(|c| match c {
+            Change::Removed(old) => Some(old.path()),
+            _ => None,
+        })
+        .collect();
+    let stale: Vec<&str> = stale.iter().map(String::as_str).collect();
+    Registry.remove_values(&stale, scope)?;
+    Registry.set_values(&entries, scope)?;
+    journal::record(scope, &changes);
+    history::record("set", scope, None, &changes);
+    for change in &changes {
+        anstream::println!("  {}", output::change(change));
+    }
+    if scope == Scope::User && !registry::check_hklm_conflicts(&entries).is_empty() {
+        let line = format!("HKLM also sets {}, and takes precedence.", def.name);
+        anstream::println!("{}", output::paint(output::WARNING, line));
+    }
+    Ok(())
+}
+
+/// Print one policy's value in `scope`, and the HKLM value that overrides
+/// it, if any.
+pub fn get(name: &str, scope: Scope) -> Result<()> {
+    let name = catalog::lookup(name).map_or(name, |def| def.name);
+    let show = |scope: Scope| -> Result<bool> {
+        let entries: Vec<_> = Registry
+            .read_current(scope)?
+            .into_iter()
+            .filter(|e| e.policy_name().eq_ignore_ascii_case(name))
+            .collect();
+        match entries.as_slice() {
+            [] => return Ok(false),
+            [entry] if entry.subkey.is_empty() => {
+                println!("{scope}  {name} = {}", registry::describe(entry));
+            }
+            items => {
+                println!("{scope}  {name}:");
+                for item in items {
+                    println!("        {} = {}", item.name, registry::describe(item));
+                }
+            }
+        }
+        Ok(true)
+    };
+
+    let found = show(scope)?;
+    if !found {
+        println!("{name} is not set in {scope}.");
+    }
+    if scope == Scope::User && show(Scope::Machine)? {
+        let line = "Edge uses the HKLM value.";
+        anstream::println!("{}", output::paint(output::WARNING, line));
+    }
+    if catalog::lookup(name).is_none() {
+        let hint = format!(
+            "{name} is not in the policy catalog{}",
+            catalog::did_you_mean(name)
+        );
+        anstream::println!("{}", output::paint(output::NOTE, hint));
+    }
+    Ok(())
+}
+
+/// Delete one policy: a top-level value, a whole list subkey, or a single
+/// `Subkey\N` item in one.
+pub fn remove(name: &str, scope: Scope) -> Result<()> {
+    let removed: Vec<_> = Registry
+        .read_current(scope)?
+        .into_iter()
+        .filter(|e| {
+            if name.contains('\\') {
+                e.path().eq_ignore_ascii_case(name)
+            } else {
+                e.policy_name().eq_ignore_ascii_case(name)
+            }
+        })
+        .map(Change::Removed)
+        .collect();
+    if removed.is_empty() {
+        println!("{name} is not set in {scope}.");
+        return Ok(());
+    }
+
+    let paths: Vec<String> = removed.iter().map(|c| c.entry().path()).collect();
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+    Registry.remove_values(&paths, scope)?;
+    journal::record(scope, &removed);
+    history::record("remove", scope, None, &removed);
+    for change in &removed {
+        anstream::println!("  {}", output::change(change));
+    }
+
+    // Best-effort: a missing or broken config just skips the reminder.
+    let policy = removed[0].entry().policy_name();
+    let configured = config::load(None)
+        .map(|cfg| {
+            policy::build_entries(&cfg)
+                .iter()
+                .any(|e| e.policy_name().eq_ignore_ascii_case(policy))
+        })
+        .unwrap_or(false);
+    if configured {
+        let line = format!("The config still sets {policy}; the next apply writes it back.");
+        anstream::println!("{}", output::paint(output::WARNING, line));
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! Writing a config's policies, shared by every command that applies one:
+//! `apply`, `apply --watch`, `bootstrap`, the DSC resource and packed
+//! executables. Each run lands in the journal `undo` reads, the history
+//! `rollback` reads and the state `apply --if-changed` reads, with the
+//! config's hooks and webhook around the write.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::diff::{self, Change};
+use crate::policy::PolicyEntry;
+use crate::registry::{self, Backend, Registry, Scope};
+use crate::{history, hooks, interactive, journal, output, state};
+
+/// What [`Apply::run`] prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    /// Each change, then the summary line.
+    Changes,
+    /// Only the summary line, for callers that already showed the changes.
+    Summary,
+    /// `apply --porcelain` lines.
+    Porcelain,
+    /// Nothing, for callers whose stdout another program reads.
+    Quiet,
+}
+
+pub struct Apply<'a> {
+    /// Logged as the run's command in the history and the webhook.
+    pub command: &'static str,
+    pub cfg: &'a Config,
+    pub entries: &'a [PolicyEntry],
+    pub scope: Scope,
+    /// The file the config came from, if any.
+    pub config: Option<&'a Path>,
+    /// Recorded as the applied state, unless HKLM overrides part of the
+    /// config, so the next `apply --if-changed` can skip it.
+    pub config_key: Option<String>,
+    /// Write only the changes passed to `run`, as `apply --interactive`
+    /// picked them, rather than the whole config.
+    pub interactive: bool,
+    pub output: Output,
+}
+
+impl<'a> Apply<'a> {
+    /// Write the config, given `changes` as [`diff::compare`] found them
+    /// against the registry and the ones `apply --interactive` left out.
+    /// Returns the policies HKLM overrides.
+    pub fn run(self, changes: &[Change], skipped: &[Change]) -> Result<Vec<&'a str>> {
+        let scope = self.scope;
+        let overridden = match scope {
+            Scope::User => registry::check_hklm_conflicts(self.entries),
+            Scope::Machine => Vec::new(),
+        };
+        if let Some(ref hooks) = self.cfg.hooks {
+            hooks::pre_apply(&hooks.pre_apply, scope, changes)?;
+        }
+        if self.interactive {
+            interactive::write(changes, scope)?;
+        } else {
+            Registry.apply(self.entries, scope)?;
+            if let Some(key) = self.config_key.filter(|_| overridden.is_empty()) {
+                state::record(scope, key);
+            }
+        }
+        journal::record(scope, changes);
+        history::record(self.command, scope, self.config, changes);
+
+        match self.output {
+            Output::Porcelain => crate::print_porcelain(changes, &overridden),
+            Output::Quiet => {}
+            Output::Changes | Output::Summary => {
+                if self.output == Output::Changes {
+                    for change in changes {
+                        anstream::println!("  {}", output::change(change));
+                    }
+                }
+                let mut summary = diff::Summary::new(self.entries, changes, overridden.len());
+                summary.skipped = skipped.len();
+                summary.unchanged -= skipped
+                    .iter()
+                    .filter(|c| !matches!(c, Change::Removed(_)))
+                    .count();
+                println!("Applied to {scope}: {summary}.");
+            }
+        }
+
+        if let Some(ref hooks) = self.cfg.hooks {
+            hooks::post_apply(&hooks.post_apply, scope, changes);
+        }
+        // The webhook identifies the config by its file's hash, so a packed
+        // config, which has no file, isn't reported.
+        if let Some(path) = self.config {
+            crate::report(
+                self.cfg,
+                self.command,
+                scope,
+                Some(path),
+                changes,
+                &overridden,
+            )?;
+        }
+        Ok(overridden)
+    }
+}
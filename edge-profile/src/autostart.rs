@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use winreg::enums::*;
+use winreg::RegKey;
+
+const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+const VALUE_NAME: &str = "edge-profile";
+
+/// Register `apply --if-changed --notify` to run at every sign-in.
+pub fn enable(config: &Path) -> Result<()> {
+    let config = std::path::absolute(config)
+        .with_context(|| format!("Failed to resolve config path: {}", config.display()))?;
+    let exe = std::env::current_exe().context("Cannot determine executable path")?;
+    let command = format!(
+        "\"{}\" apply --if-changed --notify --config \"{}\"",
+        exe.display(),
+        config.display()
+    );
+
+    let (run, _) = RegKey::predef(HKEY_CURRENT_USER)
+        .create_subkey(RUN_KEY)
+        .context("Failed to open Run key")?;
+    run.set_value(VALUE_NAME, &command)
+        .context("Failed to write Run entry")?;
+
+    println!("Enabled autostart: {command}");
+    Ok(())
+}
+
+pub fn disable() -> Result<()> {
+    let run = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey_with_flags(RUN_KEY, KEY_SET_VALUE)
+        .context("Failed to open Run key")?;
+    match run.delete_value(VALUE_NAME) {
+        Ok(()) => println!("Disabled autostart."),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("Autostart was not enabled.")
+        }
+        Err(e) => return Err(e).context("Failed to remove Run entry"),
+    }
+    Ok(())
+}
+
+/// The config the sign-in entry applies, or `None` if autostart is off.
+pub fn registered_config() -> Option<PathBuf> {
+    let command: String = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(RUN_KEY)
+        .ok()?
+        .get_value(VALUE_NAME)
+        .ok()?;
+    let (_, config) = command.split_once("--config \"")?;
+    Some(PathBuf::from(config.trim_end_matches('"')))
+}
@@ -0,0 +1,297 @@
+//! JSON snapshots of the Edge policy key: `backup`, `restore`, and the copy
+//! taken before destructive changes.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::policy::{PolicyEntry, RegValue};
+use crate::registry::{self, KeyTree, RawValue, Scope};
+use crate::{config, history, output};
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    scope: Scope,
+    /// Seconds since the Unix epoch.
+    created: u64,
+    /// Every subkey, relative to the Edge policy key, so empty ones come
+    /// back too. Missing from snapshots that only kept policy values.
+    #[serde(default)]
+    keys: Vec<String>,
+    values: Vec<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Value {
+    /// A value exactly as the registry held it.
+    Raw {
+        /// Relative to the Edge policy key; empty for the key itself.
+        key: String,
+        name: String,
+        /// e.g. `REG_MULTI_SZ`.
+        #[serde(rename = "type")]
+        kind: String,
+        /// The value's bytes, in hex.
+        data: String,
+    },
+    /// A policy value, as snapshots stored them before they kept raw types.
+    Policy {
+        /// Relative to the Edge policy key, e.g. `ExtensionInstallForcelist\1`.
+        path: String,
+        #[serde(flatten)]
+        data: Data,
+    },
+}
+
+impl From<&RawValue> for Value {
+    fn from(value: &RawValue) -> Self {
+        Value::Raw {
+            key: value.key.clone(),
+            name: value.name.clone(),
+            kind: value.type_name(),
+            data: value.bytes.iter().map(|b| format!("{b:02x}")).collect(),
+        }
+    }
+}
+
+impl Value {
+    fn into_raw(self) -> Result<RawValue> {
+        match self {
+            Value::Raw {
+                key,
+                name,
+                kind,
+                data,
+            } => {
+                let path = if key.is_empty() {
+                    name.clone()
+                } else {
+                    format!(r"{key}\{name}")
+                };
+                let kind = RawValue::type_from_name(&kind)
+                    .with_context(|| format!("{path} has an unknown registry type {kind}"))?;
+                let bytes = (0..data.len())
+                    .step_by(2)
+                    .map(|i| {
+                        data.get(i..i + 2)
+                            .and_then(|b| u8::from_str_radix(b, 16).ok())
+                    })
+                    .collect::<Option<_>>()
+                    .with_context(|| format!("{path} has malformed data {data:?}"))?;
+                Ok(RawValue {
+                    key,
+                    name,
+                    kind,
+                    bytes,
+                })
+            }
+            Value::Policy { path, data } => Ok(RawValue::from_entry(&data.into_entry(&path))),
+        }
+    }
+}
+
+/// A value's type and data, as stored in the apply journal.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Data {
+    Dword(u32),
+    Sz(String),
+}
+
+impl From<&RegValue> for Data {
+    fn from(value: &RegValue) -> Self {
+        match value {
+            RegValue::Dword(v) => Data::Dword(*v),
+            RegValue::Sz(s) => Data::Sz(s.clone()),
+        }
+    }
+}
+
+impl Data {
+    /// The entry at `path` (relative to the Edge policy key) holding this
+    /// value.
+    pub fn into_entry(self, path: &str) -> PolicyEntry {
+        let (subkey, name) = path.rsplit_once('\\').unwrap_or(("", path));
+        PolicyEntry {
+            subkey: subkey.to_owned(),
+            name: name.to_owned(),
+            value: match self {
+                Data::Dword(n) => RegValue::Dword(n),
+                Data::Sz(s) => RegValue::Sz(s),
+            },
+        }
+    }
+}
+
+/// Snapshot everything under the `scope` Edge policy key, including values
+/// edge-profile doesn't manage. Returns the file's path and how many values
+/// it holds.
+pub fn snapshot(scope: Scope) -> Result<(PathBuf, usize)> {
+    let tree = registry::read_tree(scope)?;
+    Ok((write(scope, &tree)?, tree.values.len()))
+}
+
+/// Write `tree` to `~/.edge-profile/backups/<scope>-<UTC time>.json` and
+/// return the file's path.
+fn write(scope: Scope, tree: &KeyTree) -> Result<PathBuf> {
+    let created = now();
+    let snapshot = Snapshot {
+        scope,
+        created,
+        keys: tree.keys.clone(),
+        values: tree.values.iter().map(Value::from).collect(),
+    };
+
+    let dir = dir()?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let stem = format!(
+        "{}-{}",
+        scope.to_string().to_lowercase(),
+        timestamp(created)
+    );
+    // Snapshots taken within the same second get a counter.
+    let mut path = dir.join(format!("{stem}.json"));
+    for n in 2.. {
+        if !path.exists() {
+            break;
+        }
+        path = dir.join(format!("{stem}-{n}.json"));
+    }
+    let json = serde_json::to_string_pretty(&snapshot)?;
+    std::fs::write(&path, json)
+        .with_context(|| format!("Failed to write backup: {}", path.display()))?;
+    Ok(path)
+}
+
+/// `~/.edge-profile/backups`.
+pub fn dir() -> Result<PathBuf> {
+    Ok(config::data_dir()?.join("backups"))
+}
+
+/// Read a snapshot: the scope it was taken from and the key's contents.
+pub fn load(path: &Path) -> Result<(Scope, KeyTree)> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read backup: {}", path.display()))?;
+    let snapshot: Snapshot = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse backup: {}", path.display()))?;
+    let values = snapshot
+        .values
+        .into_iter()
+        .map(Value::into_raw)
+        .collect::<Result<_>>()
+        .with_context(|| format!("Failed to parse backup: {}", path.display()))?;
+    let tree = KeyTree {
+        keys: snapshot.keys,
+        values,
+    };
+    Ok((snapshot.scope, tree))
+}
+
+/// Replace the Edge policy key with the contents of a snapshot, after
+/// showing what would change and backing up what's there now. `scope`
+/// defaults to the hive the snapshot was taken from. Returns false if the
+/// user declined.
+pub fn restore(path: &Path, scope: Option<Scope>, yes: bool) -> Result<bool> {
+    let (saved_scope, tree) = load(path)?;
+    let scope = scope.unwrap_or(saved_scope);
+    let current = registry::read_tree(scope)?;
+
+    // Unlike apply, restore replaces the whole key, so anything not in the
+    // snapshot goes.
+    let mut lines = Vec::new();
+    let (mut added, mut changed, mut removed) = (0, 0, 0);
+    for value in &tree.values {
+        match current.values.iter().find(|c| c.same_place(value)) {
+            None => {
+                added += 1;
+                lines.push(output::paint(
+                    output::ADDED,
+                    format!("+ {} = {value}", value.path()),
+                ));
+            }
+            Some(old) if old.kind != value.kind || old.bytes != value.bytes => {
+                changed += 1;
+                let line = format!("~ {}: {old} -> {value}", value.path());
+                lines.push(output::paint(output::CHANGED, line));
+            }
+            Some(_) => {}
+        }
+    }
+    for value in &current.values {
+        if !tree.values.iter().any(|v| v.same_place(value)) {
+            removed += 1;
+            lines.push(output::paint(
+                output::REMOVED,
+                format!("- {} = {value}", value.path()),
+            ));
+        }
+    }
+    let has = |keys: &[String], key: &String| keys.iter().any(|k| k.eq_ignore_ascii_case(key));
+    for key in tree.keys.iter().filter(|k| !has(&current.keys, k)) {
+        lines.push(output::paint(output::ADDED, format!(r"+ {key}\")));
+    }
+    for key in current.keys.iter().filter(|k| !has(&tree.keys, k)) {
+        lines.push(output::paint(output::REMOVED, format!(r"- {key}\")));
+    }
+    if lines.is_empty() {
+        println!("{scope} policies already match the backup.");
+        return Ok(true);
+    }
+    for line in &lines {
+        anstream::println!("  {line}");
+    }
+    let prompt = format!("Restore {} value(s) to {scope}?", tree.values.len());
+    if !crate::confirm(&prompt, yes)? {
+        return Ok(false);
+    }
+
+    let (previous, count) = snapshot(scope)?;
+    println!(
+        "Backed up the current {count} value(s) to {}",
+        previous.display()
+    );
+    registry::clean_all(scope)?;
+    registry::write_tree(scope, &tree)?;
+    history::record_restore(scope, added, changed, removed);
+    println!("Restored {} value(s) to {scope}.", tree.values.len());
+    Ok(true)
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC.
+fn timestamp(secs: u64) -> String {
+    let (year, month, day, h, m, s) = civil(secs);
+    format!("{year:04}{month:02}{day:02}-{h:02}{m:02}{s:02}")
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in UTC, for display.
+pub fn format_time(secs: u64) -> String {
+    let (year, month, day, h, m, s) = civil(secs);
+    format!("{year:04}-{month:02}-{day:02} {h:02}:{m:02}:{s:02}")
+}
+
+/// Year, month, day, hour, minute and second in UTC.
+fn civil(secs: u64) -> (i64, i64, i64, u64, u64, u64) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days, after Howard Hinnant's date algorithms.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
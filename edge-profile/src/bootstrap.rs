@@ -0,0 +1,71 @@
+//! `bootstrap`: set up a fresh machine from a config hosted somewhere.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::registry::{Backend, Registry, Scope};
+use crate::{apply, autostart, config, diff, http, policy, state};
+
+/// Download, validate and preview the config, then on confirmation save it,
+/// apply it and enable autostart. Returns false if the user declined.
+pub fn run(url: &str, path: &Path, scope: Scope, yes: bool) -> Result<bool> {
+    let text = http::agent()?
+        .get(url)
+        .call()
+        .with_context(|| format!("Failed to download {url}"))?
+        .into_string()
+        .context("Failed to read response body")?;
+
+    let cfg = config::parse(&text).with_context(|| format!("Failed to parse config from {url}"))?;
+    let entries = policy::build_entries(&cfg);
+    crate::report_issues(&crate::check_config(&cfg, &entries, scope))?;
+
+    let current = Registry.read_current(scope)?;
+    crate::print_dry_run(&entries, &current, false);
+    let changes = diff::compare(&entries, &current);
+    if !crate::confirm(
+        &format!("Save to {} and apply to {scope}?", path.display()),
+        yes,
+    )? {
+        return Ok(false);
+    }
+
+    save(path, &text)?;
+    // Record the state too, so the logon task's `apply --if-changed` finds
+    // this config already applied.
+    apply::Apply {
+        command: "bootstrap",
+        cfg: &cfg,
+        entries: &entries,
+        scope,
+        config: Some(path),
+        config_key: state::config_key(path, false).ok(),
+        interactive: false,
+        output: apply::Output::Summary,
+    }
+    .run(&changes, &[])?;
+    autostart::enable(path)?;
+    Ok(true)
+}
+
+/// Write the config, keeping any different existing one as `.bak`.
+fn save(path: &Path, text: &str) -> Result<()> {
+    match std::fs::read_to_string(path) {
+        Ok(old) if old == text => return Ok(()),
+        Ok(_) => {
+            let backup = path.with_extension("toml.bak");
+            std::fs::rename(path, &backup)
+                .with_context(|| format!("Failed to back up {}", path.display()))?;
+            println!("Existing config moved to {}", backup.display());
+        }
+        Err(_) => {}
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Saved config to {}", path.display());
+    Ok(())
+}
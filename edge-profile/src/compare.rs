@@ -0,0 +1,46 @@
+//! `compare`: what switching from one config to another would change.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::{config, diff, output, policy};
+
+pub fn run(from: &Path, to: &Path) -> Result<()> {
+    let old = policy::build_entries(&config::load(Some(from))?);
+    let new = policy::build_entries(&config::load(Some(to))?);
+
+    // Treat `from` as what the registry holds after applying it.
+    let changes = diff::compare(&new, &old);
+    // Apply never deletes top-level values, so these stay behind.
+    let left: Vec<_> = old
+        .iter()
+        .filter(|o| o.subkey.is_empty())
+        .filter(|o| !new.iter().any(|n| n.subkey.is_empty() && n.name == o.name))
+        .collect();
+
+    if changes.is_empty() && left.is_empty() {
+        println!(
+            "{} and {} produce the same policies.",
+            from.display(),
+            to.display()
+        );
+        return Ok(());
+    }
+
+    println!("{} -> {}:", from.display(), to.display());
+    for change in &changes {
+        anstream::println!("  {}", output::change(change));
+    }
+    if !left.is_empty() {
+        println!(
+            "\nOnly in {} (apply leaves these in the registry):",
+            from.display()
+        );
+        for entry in &left {
+            anstream::println!("  {}", output::paint(output::NOTE, entry));
+        }
+    }
+    println!("\n{}", diff::Summary::new(&new, &changes, 0));
+    Ok(())
+}
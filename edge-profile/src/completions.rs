@@ -0,0 +1,71 @@
+//! `completions`: shell completion scripts generated from the CLI definition.
+//!
+//! Arguments that name a policy list the catalog as their possible values,
+//! which is compiled in, so the scripts stay current with the binary.
+
+use std::ffi::OsStr;
+
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+use clap::ValueEnum;
+
+use crate::{catalog, policy};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Powershell,
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl From<Shell> for clap_complete::Shell {
+    fn from(shell: Shell) -> Self {
+        match shell {
+            Shell::Powershell => clap_complete::Shell::PowerShell,
+            Shell::Bash => clap_complete::Shell::Bash,
+            Shell::Zsh => clap_complete::Shell::Zsh,
+            Shell::Fish => clap_complete::Shell::Fish,
+        }
+    }
+}
+
+pub fn print(shell: Shell, mut command: clap::Command) {
+    let name = command.get_name().to_owned();
+    clap_complete::generate(
+        clap_complete::Shell::from(shell),
+        &mut command,
+        name,
+        &mut std::io::stdout(),
+    );
+}
+
+/// Parser for an argument that takes a policy name. It accepts any string,
+/// since `[raw]` policies, list items and config keys aren't all in the
+/// catalog, but offers the catalog's names to completion scripts.
+#[derive(Clone)]
+pub struct PolicyName {
+    /// Also offer config keys, for `explain`.
+    pub config_keys: bool,
+}
+
+impl TypedValueParser for PolicyName {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<String, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let keys = policy::CONFIG_KEYS
+            .iter()
+            .filter(|_| self.config_keys)
+            .map(|(key, _)| PossibleValue::new(*key));
+        let names = catalog::POLICIES.iter().map(|p| PossibleValue::new(p.name));
+        Some(Box::new(keys.chain(names)))
+    }
+}
@@ -0,0 +1,221 @@
+//! `doctor`: find out why a policy isn't taking effect.
+
+use std::path::Path;
+
+use anyhow::Result;
+use windows_service::service::ServiceState;
+
+use crate::exit::Status;
+use crate::registry::{self, Backend, Registry, Scope, EDGE_POLICY_PATH};
+use crate::{autostart, config, edge, journal, output, policy, schedule, service, state, validate};
+
+/// Run every check, printing each result with a fix for anything wrong.
+/// The status is that of the most serious problem found.
+pub fn run(config: Option<&Path>, scope: Scope) -> Result<Status> {
+    let mut report = Report::default();
+    let path = config::resolve_path(config)?;
+
+    let entries = match config::load(Some(&path)) {
+        Ok(cfg) => {
+            let entries = policy::build_entries(&cfg);
+            let issues = crate::check_config(&cfg, &entries, scope);
+            let errors = issues
+                .iter()
+                .filter(|i| i.severity == validate::Severity::Error)
+                .count();
+            let warnings = issues.len() - errors;
+            let summary = format!("{} parses, {} value(s)", path.display(), entries.len());
+            if errors > 0 {
+                report.fail(
+                    Status::Invalid,
+                    "Config",
+                    format!("{summary}, {errors} error(s)"),
+                    "run `edge-profile validate` for details",
+                );
+            } else if warnings > 0 {
+                report.warn(
+                    "Config",
+                    format!("{summary}, {warnings} warning(s)"),
+                    "run `edge-profile validate` for details",
+                );
+            } else {
+                report.ok("Config", summary);
+            }
+            Some(entries)
+        }
+        Err(_) if !path.exists() => {
+            report.fail(
+                Status::Invalid,
+                "Config",
+                format!("{} does not exist", path.display()),
+                "run `edge-profile init` or `edge-profile import` to create one",
+            );
+            None
+        }
+        Err(e) => {
+            report.fail(
+                Status::Invalid,
+                "Config",
+                format!("{e:#}"),
+                "fix the file, then run `edge-profile validate`",
+            );
+            None
+        }
+    };
+
+    match edge::installed_version() {
+        Some(version) => report.ok("Edge", format!("version {version}")),
+        None => report.warn(
+            "Edge",
+            "not found; version-specific checks were skipped",
+            "install Edge, or launch it once so it records its version",
+        ),
+    }
+
+    let key = format!(r"{scope}\{EDGE_POLICY_PATH}");
+    if registry::can_write(scope) {
+        report.ok("Registry", format!("{key} is writable"));
+    } else {
+        report.fail(
+            Status::Registry,
+            "Registry",
+            format!("{key} is not writable by this process"),
+            "run from an elevated prompt",
+        );
+    }
+
+    if scope == Scope::User {
+        let overridden = entries
+            .as_deref()
+            .map(registry::check_hklm_conflicts)
+            .unwrap_or_default();
+        let machine = Registry.read_current(Scope::Machine)?.len();
+        if !overridden.is_empty() {
+            report.fail(
+                Status::Conflict,
+                "HKLM",
+                format!("overrides {}", overridden.join(", ")),
+                "remove them from HKLM (or the GPO that sets them), or apply with --scope machine",
+            );
+        } else if machine > 0 {
+            report.ok(
+                "HKLM",
+                format!("{machine} value(s), none overlap the config"),
+            );
+        } else {
+            report.ok("HKLM", "no machine policies");
+        }
+    }
+
+    let mdm = registry::mdm_policies();
+    if mdm.is_empty() {
+        report.ok("MDM", "no Edge policies from device management");
+    } else {
+        report.warn(
+            "MDM",
+            format!("Edge policies delivered by MDM: {}", mdm.join(", ")),
+            "MDM policies win over both hives; change them in the management console",
+        );
+    }
+
+    match autostart::registered_config() {
+        None => report.ok("Autostart", "not enabled"),
+        Some(registered) if !registered.exists() => report.warn(
+            "Autostart",
+            format!("applies {}, which does not exist", registered.display()),
+            "run `edge-profile autostart enable` to point it at the current config",
+        ),
+        Some(registered) if registered != std::path::absolute(&path)? => report.warn(
+            "Autostart",
+            format!("applies {}, not this config", registered.display()),
+            "run `edge-profile autostart enable` if it should apply this one",
+        ),
+        Some(registered) => report.ok("Autostart", format!("applies {}", registered.display())),
+    }
+
+    if schedule::installed() {
+        report.ok(
+            "Schedule",
+            format!("{} task registered", schedule::TASK_NAME),
+        );
+    } else {
+        report.ok("Schedule", "no scheduled task");
+    }
+
+    match service::state() {
+        None => report.ok("Service", "not installed"),
+        Some(ServiceState::Running) => report.ok("Service", "running, enforcing at HKLM"),
+        Some(state) => report.warn(
+            "Service",
+            format!("installed but {state:?}"),
+            format!("start it with `sc start {}`", service::SERVICE_NAME),
+        ),
+    }
+
+    for (label, file) in [("State", state::path()?), ("Journal", journal::path()?)] {
+        match std::fs::read_to_string(&file) {
+            Err(_) => report.ok(label, format!("{} not written yet", file.display())),
+            Ok(text) if serde_json::from_str::<serde_json::Value>(&text).is_err() => report.warn(
+                label,
+                format!("{} is corrupt", file.display()),
+                "delete it; it is rewritten by the next apply",
+            ),
+            Ok(_) => report.ok(label, file.display()),
+        }
+    }
+
+    println!();
+    match report.status {
+        Status::Ok if report.warnings == 0 => println!("No problems found."),
+        Status::Ok => println!("{} warning(s).", report.warnings),
+        status => println!("{status}."),
+    }
+    Ok(report.status)
+}
+
+/// Prints check results as they come in and tracks the worst one.
+struct Report {
+    status: Status,
+    warnings: usize,
+}
+
+impl Default for Report {
+    fn default() -> Self {
+        Self {
+            status: Status::Ok,
+            warnings: 0,
+        }
+    }
+}
+
+impl Report {
+    fn ok(&mut self, label: &str, message: impl std::fmt::Display) {
+        let mark = output::paint(output::ADDED, "ok  ");
+        anstream::println!("  {mark}  {label:<10} {message}");
+    }
+
+    fn warn(&mut self, label: &str, message: impl std::fmt::Display, fix: impl std::fmt::Display) {
+        self.warnings += 1;
+        let mark = output::paint(output::WARNING, "warn");
+        anstream::println!("  {mark}  {label:<10} {message}");
+        Self::fix(fix);
+    }
+
+    fn fail(
+        &mut self,
+        status: Status,
+        label: &str,
+        message: impl std::fmt::Display,
+        fix: impl std::fmt::Display,
+    ) {
+        self.status = self.status.max(status);
+        let mark = output::paint(output::ERROR, "fail");
+        anstream::println!("  {mark}  {label:<10} {message}");
+        Self::fix(fix);
+    }
+
+    fn fix(fix: impl std::fmt::Display) {
+        let line = format!("fix: {fix}");
+        anstream::println!("              {}", output::paint(output::NOTE, line));
+    }
+}
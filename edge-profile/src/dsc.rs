@@ -0,0 +1,141 @@
+//! DSC v3 resource interface, so `winget configure` and `dsc` can drive the
+//! tool. Each operation reads the instance's properties as JSON on stdin and
+//! writes the resulting state as JSON on stdout; the manifest that tells DSC
+//! how to call us is `edge-profile.dsc.resource.json`.
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::exit::Status;
+use crate::registry::{self, Backend, Registry, Scope};
+use crate::{apply, diff};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Input {
+    config_path: PathBuf,
+    #[serde(default)]
+    scope: Scope,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    config_path: PathBuf,
+    scope: Scope,
+    in_sync: bool,
+    changes: Vec<String>,
+    conflicts: Vec<String>,
+    #[serde(rename = "_inDesiredState", skip_serializing_if = "Option::is_none")]
+    in_desired_state: Option<bool>,
+}
+
+pub fn get() -> Result<()> {
+    let input = read_input()?;
+    emit(&state(input)?)
+}
+
+pub fn test() -> Result<()> {
+    let input = read_input()?;
+    let mut state = state(input)?;
+    state.in_desired_state = Some(state.in_sync);
+    emit(&state)
+}
+
+pub fn set() -> Result<()> {
+    let input = read_input()?;
+    let path = &input.config_path;
+    let (cfg, entries) = crate::prepare(Some(path), input.scope, false)?;
+    let current = Registry.read_current(input.scope)?;
+    let changes = diff::compare(&entries, &current);
+    // Stdout carries the resource's JSON, so the run prints nothing.
+    apply::Apply {
+        command: "dsc",
+        cfg: &cfg,
+        entries: &entries,
+        scope: input.scope,
+        config: Some(path),
+        config_key: crate::state::config_key(path, false).ok(),
+        interactive: false,
+        output: apply::Output::Quiet,
+    }
+    .run(&changes, &[])?;
+    emit(&state(input)?)
+}
+
+pub fn schema() -> Result<()> {
+    let schema = serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "EdgeProfile/Policies",
+        "type": "object",
+        "required": ["configPath"],
+        "additionalProperties": false,
+        "properties": {
+            "configPath": {
+                "type": "string",
+                "description": "Path to the edge-profile config.toml to enforce."
+            },
+            "scope": {
+                "type": "string",
+                "enum": ["user", "machine"],
+                "default": "user",
+                "description": "Hive to write policies to."
+            },
+            "inSync": {
+                "type": "boolean",
+                "readOnly": true,
+                "description": "Whether the registry matches the config."
+            },
+            "changes": {
+                "type": "array",
+                "items": { "type": "string" },
+                "readOnly": true,
+                "description": "Values that differ from the config."
+            },
+            "conflicts": {
+                "type": "array",
+                "items": { "type": "string" },
+                "readOnly": true,
+                "description": "Values overridden by HKLM policies."
+            },
+            "_inDesiredState": { "type": ["boolean", "null"], "readOnly": true }
+        }
+    });
+    emit(&schema)
+}
+
+fn state(input: Input) -> Result<State> {
+    let (_, entries) = crate::prepare(Some(&input.config_path), input.scope, false)?;
+    let current = Registry.read_current(input.scope)?;
+    let changes = diff::compare(&entries, &current);
+    let conflicts = match input.scope {
+        Scope::User => registry::check_hklm_conflicts(&entries),
+        Scope::Machine => Vec::new(),
+    };
+    Ok(State {
+        in_sync: changes.is_empty(),
+        changes: changes.iter().map(ToString::to_string).collect(),
+        conflicts: conflicts.into_iter().map(str::to_owned).collect(),
+        config_path: input.config_path,
+        scope: input.scope,
+        in_desired_state: None,
+    })
+}
+
+fn read_input() -> Result<Input> {
+    let mut json = String::new();
+    std::io::stdin()
+        .read_to_string(&mut json)
+        .context("Failed to read resource properties from stdin")?;
+    serde_json::from_str(&json)
+        .context("Invalid resource properties")
+        .context(Status::Invalid)
+}
+
+fn emit(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string(value)?);
+    Ok(())
+}
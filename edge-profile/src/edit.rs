@@ -0,0 +1,92 @@
+//! `edit`: open the config in an editor and check it when the editor exits.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::exit::Status;
+use crate::registry::Scope;
+use crate::{config, output, policy, watch};
+
+/// Edit the config until it validates or the user gives up, in which case
+/// the previous contents are put back. A valid edit can be applied straight
+/// away.
+pub fn run(config: Option<&Path>, scope: Scope, yes: bool) -> Result<Status> {
+    let path = config::resolve_path(config)?;
+    let original = std::fs::read(&path).with_context(|| {
+        format!(
+            "Failed to read {}; run `edge-profile init` to create it",
+            path.display()
+        )
+    })?;
+
+    loop {
+        open(&path)?;
+        let text =
+            std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        if text == original {
+            println!("No changes.");
+            return Ok(Status::Ok);
+        }
+
+        match check(&path, scope) {
+            Ok(values) => {
+                println!("Config OK ({values} values).");
+                break;
+            }
+            Err(e) => {
+                anstream::eprintln!("{}", output::paint(output::ERROR, format!("Error: {e:#}")));
+            }
+        }
+        // Never assume yes here: that would reopen the editor forever.
+        if !crate::confirm("Edit it again? (No puts back the previous version)", false)? {
+            std::fs::write(&path, &original)
+                .with_context(|| format!("Failed to restore {}", path.display()))?;
+            println!("Restored the previous version of {}.", path.display());
+            return Ok(Status::Invalid);
+        }
+    }
+
+    if crate::confirm(&format!("Apply it to {scope} now?"), yes)? {
+        watch::apply(&path, scope, false)?;
+    }
+    Ok(Status::Ok)
+}
+
+/// Parse and validate the file, printing any issues. Returns the number of
+/// registry values it produces.
+fn check(path: &Path, scope: Scope) -> Result<usize> {
+    let cfg = config::load(Some(path))?;
+    let entries = policy::build_entries(&cfg);
+    crate::report_issues(&crate::check_config(&cfg, &entries, scope))?;
+    Ok(entries.len())
+}
+
+/// Run `$VISUAL` or `$EDITOR` on the file and wait for it to exit. Falls back
+/// to Notepad. The variable may hold arguments, e.g. `code --wait`.
+fn open(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "notepad".to_owned());
+
+    // A bare path with spaces, e.g. under Program Files, is one program.
+    let mut command = if Path::new(&editor).is_file() {
+        Command::new(&editor)
+    } else {
+        let mut words = editor.split_whitespace();
+        let mut command = Command::new(words.next().expect("editor is not blank"));
+        command.args(words);
+        command
+    };
+    let status = command
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start {editor}"))?;
+    if !status.success() {
+        bail!("{editor} exited with {status}");
+    }
+    Ok(())
+}
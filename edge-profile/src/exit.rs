@@ -0,0 +1,71 @@
+//! Exit codes, a stable contract for scripts that wrap the tool.
+//!
+//! | code | meaning                              |
+//! |------|--------------------------------------|
+//! | 0    | success, registry in sync            |
+//! | 1    | any other error                      |
+//! | 2    | registry has drifted from the config |
+//! | 3    | HKLM policies override the config    |
+//! | 4    | config failed validation             |
+//! | 5    | registry could not be read/written   |
+
+use std::fmt;
+
+use edge_profile_core::Error;
+
+/// Outcome of a command, ordered by severity. Failure kinds are attached to
+/// errors as context so `main` can recover the code with `downcast_ref`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Ok,
+    Drift,
+    Conflict,
+    Invalid,
+    Registry,
+}
+
+impl Status {
+    pub fn code(self) -> u8 {
+        match self {
+            Status::Ok => 0,
+            Status::Drift => 2,
+            Status::Conflict => 3,
+            Status::Invalid => 4,
+            Status::Registry => 5,
+        }
+    }
+
+    /// The exit status for a library failure, if it has a dedicated code.
+    pub fn of(e: &Error) -> Option<Status> {
+        match e {
+            Error::ConfigParse { .. } | Error::Validation { .. } => Some(Status::Invalid),
+            Error::RegistryAccess { .. } => Some(Status::Registry),
+            Error::Conflict { .. } => Some(Status::Conflict),
+            Error::ConfigRead { .. } | Error::ConfigSerialize(_) | Error::NoHomeDir => None,
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Ok => "Success",
+            Status::Drift => "Registry has drifted from the config",
+            Status::Conflict => "HKLM policies override the config",
+            Status::Invalid => "Invalid config",
+            Status::Registry => "Registry access failed",
+        })
+    }
+}
+
+impl std::error::Error for Status {}
+
+/// `--help` footer.
+pub const HELP: &str = "\
+Exit codes:
+  0  success, registry in sync
+  1  other error
+  2  registry has drifted from the config
+  3  HKLM policies override the config
+  4  config failed validation
+  5  registry could not be read or written";
@@ -0,0 +1,72 @@
+//! `explain`: what a policy does and how to set it.
+
+use anyhow::{bail, Result};
+
+use crate::catalog::{self, PolicyDef, PolicyKind};
+use crate::{output, policy};
+
+/// `name` is a policy name or a config key such as `privacy.tracking_prevention`.
+pub fn explain(name: &str) -> Result<()> {
+    let policy_name = policy::CONFIG_KEYS
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map_or(name, |(_, p)| *p);
+    let Some(def) = catalog::lookup(policy_name) else {
+        bail!(
+            "{name} is neither a policy in the catalog nor a config key{}",
+            catalog::did_you_mean(name)
+        );
+    };
+
+    anstream::println!("{}", output::paint(output::HEADER, def.name));
+    println!("  {}", def.summary);
+    println!();
+    println!("  Category:  {}", catalog::category_title(def.category));
+    println!("  Type:      {}", def.kind.registry_type());
+    match def.removed {
+        Some(removed) => println!("  Edge:      {} to {}", def.since, removed - 1),
+        None => println!("  Edge:      {} and later", def.since),
+    }
+    if def.machine_only {
+        println!("  Scope:     machine only (ignored under HKCU)");
+    }
+    println!("  Config:    {}", config_syntax(def));
+    if let Some(new) = def.replaced_by {
+        anstream::println!(
+            "  {}",
+            output::paint(output::WARNING, format!("Deprecated; use {new} instead"))
+        );
+    }
+
+    if !def.values.is_empty() {
+        println!("\n  Values:");
+        for (value, meaning) in def.values {
+            println!("    {value:<3} {meaning}");
+        }
+    } else if let Some((min, max)) = def.range {
+        println!("\n  Values:    {min} to {max}");
+    } else if !def.allowed.is_empty() {
+        println!("\n  Values:");
+        for value in def.allowed {
+            println!("    {value}");
+        }
+    }
+
+    anstream::println!("\n  {}", output::paint(output::NOTE, def.docs_url()));
+    Ok(())
+}
+
+/// How to set a policy in the config: its config key, or a `[raw]` line
+/// with a placeholder value of the right type.
+pub fn config_syntax(def: &PolicyDef) -> String {
+    if let Some(key) = policy::config_key(def.name) {
+        return key.to_owned();
+    }
+    let example = match def.kind {
+        PolicyKind::Boolean => "true",
+        PolicyKind::Integer => "0",
+        PolicyKind::List => "[\"...\"]",
+        PolicyKind::String | PolicyKind::Dictionary => "\"...\"",
+    };
+    format!("[raw] {} = {example}", def.name)
+}
@@ -0,0 +1,119 @@
+//! `export`: the policies a config produces, in formats other tools read.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::policy::{PolicyEntry, RegValue};
+use crate::registry::{Scope, EDGE_POLICY_PATH};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// Windows Registration Entries, for regedit or `reg import`
+    Reg,
+    /// An array of {subkey, name, type, value} objects
+    Json,
+}
+
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    subkey: &'a str,
+    name: &'a str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    value: serde_json::Value,
+}
+
+/// Write `entries` to `out`, or to stdout.
+pub fn export(
+    entries: &[PolicyEntry],
+    scope: Scope,
+    format: Format,
+    out: Option<&Path>,
+) -> Result<()> {
+    let text = match format {
+        Format::Reg => reg(entries, scope),
+        Format::Json => json(entries)?,
+    };
+    match out {
+        Some(path) => {
+            let bytes: Vec<u8> = match format {
+                // regedit expects UTF-16LE with a BOM for version 5.00 files.
+                Format::Reg => std::iter::once(0xFEFF)
+                    .chain(text.replace('\n', "\r\n").encode_utf16())
+                    .flat_map(u16::to_le_bytes)
+                    .collect(),
+                Format::Json => text.into_bytes(),
+            };
+            std::fs::write(path, bytes)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!(
+                "Wrote {} policy value(s) to {}",
+                entries.len(),
+                path.display()
+            );
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
+
+/// List subkeys are deleted before being written, so importing the file
+/// leaves no stale numbered items, as `apply` does.
+fn reg(entries: &[PolicyEntry], scope: Scope) -> String {
+    let root = match scope {
+        Scope::User => "HKEY_CURRENT_USER",
+        Scope::Machine => "HKEY_LOCAL_MACHINE",
+    };
+    let base = format!(r"{root}\{EDGE_POLICY_PATH}");
+    let mut out = String::from("Windows Registry Editor Version 5.00\n\n");
+
+    out.push_str(&format!("[{base}]\n"));
+    for entry in entries.iter().filter(|e| e.subkey.is_empty()) {
+        out.push_str(&reg_value(entry));
+    }
+
+    let mut subkeys: Vec<&str> = Vec::new();
+    for entry in entries.iter().filter(|e| !e.subkey.is_empty()) {
+        if !subkeys.contains(&entry.subkey.as_str()) {
+            subkeys.push(&entry.subkey);
+        }
+    }
+    for subkey in subkeys {
+        out.push_str(&format!("\n[-{base}\\{subkey}]\n\n[{base}\\{subkey}]\n"));
+        for entry in entries.iter().filter(|e| e.subkey == subkey) {
+            out.push_str(&reg_value(entry));
+        }
+    }
+    out
+}
+
+fn reg_value(entry: &PolicyEntry) -> String {
+    let name = reg_escape(&entry.name);
+    match &entry.value {
+        RegValue::Dword(v) => format!("\"{name}\"=dword:{v:08x}\n"),
+        RegValue::Sz(s) => format!("\"{name}\"=\"{}\"\n", reg_escape(s)),
+    }
+}
+
+fn reg_escape(s: &str) -> String {
+    s.replace('\\', r"\\").replace('"', "\\\"")
+}
+
+fn json(entries: &[PolicyEntry]) -> Result<String> {
+    let entries: Vec<JsonEntry> = entries
+        .iter()
+        .map(|e| JsonEntry {
+            subkey: &e.subkey,
+            name: &e.name,
+            kind: e.value.type_name(),
+            value: match &e.value {
+                RegValue::Dword(v) => (*v).into(),
+                RegValue::Sz(s) => s.as_str().into(),
+            },
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&entries)? + "\n")
+}
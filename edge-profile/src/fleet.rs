@@ -0,0 +1,188 @@
+//! `fleet apply`: push the config to other machines and apply it there.
+//!
+//! Each host needs edge-profile on its PATH. The config travels inside an
+//! encoded PowerShell script that saves it as the host's default config,
+//! applies it, and finishes with `verify --porcelain`, whose exit code and
+//! output come back as the host's result.
+//!
+//! ```toml
+//! [[host]]
+//! address = "laptop.local"
+//! user = "me"
+//!
+//! [[host]]
+//! name = "desktop"
+//! address = "DESKTOP-01"
+//! transport = "winrm"
+//! ```
+
+use std::path::Path;
+use std::process::{Command, Output};
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+
+use crate::exit::Status;
+use crate::powershell;
+
+#[derive(Deserialize)]
+struct HostsFile {
+    host: Vec<Host>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Host {
+    address: String,
+    /// Label for the results; defaults to the address.
+    name: Option<String>,
+    #[serde(default)]
+    transport: Transport,
+    /// SSH user name.
+    user: Option<String>,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Transport {
+    #[default]
+    Ssh,
+    /// PowerShell remoting with the current user's credentials.
+    Winrm,
+}
+
+pub fn apply(hosts: &Path, config: &Path) -> Result<Status> {
+    let text = std::fs::read_to_string(hosts)
+        .with_context(|| format!("Failed to read hosts file: {}", hosts.display()))?;
+    let hosts: HostsFile = toml::from_str(&text)
+        .with_context(|| format!("Failed to parse hosts file: {}", hosts.display()))?;
+    let config = std::fs::read(config)
+        .with_context(|| format!("Failed to read config: {}", config.display()))?;
+    let script = powershell::encode(&remote_script(&config));
+
+    let results: Vec<(String, Result<Output>)> = thread::scope(|s| {
+        let handles: Vec<_> = hosts
+            .host
+            .iter()
+            .map(|host| s.spawn(|| run(host, &script)))
+            .collect();
+        hosts
+            .host
+            .iter()
+            .zip(handles)
+            .map(|(host, handle)| {
+                let name = host.name.clone().unwrap_or_else(|| host.address.clone());
+                let output = handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("worker panicked")));
+                (name, output)
+            })
+            .collect()
+    });
+
+    let width = results.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
+    let mut status = Status::Ok;
+    let mut failed = 0;
+    for (name, result) in &results {
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                println!("{name:width$}  error: {e:#}");
+                failed += 1;
+                continue;
+            }
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match output.status.code() {
+            Some(0) => println!("{name:width$}  in sync"),
+            Some(2) => {
+                println!("{name:width$}  drift");
+                status = status.max(Status::Drift);
+            }
+            Some(3) => {
+                println!("{name:width$}  HKLM conflict");
+                status = status.max(Status::Conflict);
+            }
+            code => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = stderr.lines().rev().find(|l| !l.trim().is_empty());
+                println!(
+                    "{name:width$}  error (exit {}): {}",
+                    code.map_or("?".to_owned(), |c| c.to_string()),
+                    reason.unwrap_or("no output")
+                );
+                failed += 1;
+                continue;
+            }
+        }
+        for line in stdout.lines().filter(|l| !l.is_empty()) {
+            println!("{:width$}    {line}", "");
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} of {} host(s) failed", results.len());
+    }
+    Ok(status)
+}
+
+fn run(host: &Host, script: &str) -> Result<Output> {
+    let mut command = match host.transport {
+        Transport::Ssh => {
+            let target = match host.user {
+                Some(ref user) => format!("{user}@{}", host.address),
+                None => host.address.clone(),
+            };
+            let mut c = Command::new("ssh");
+            c.args(["-o", "BatchMode=yes", &target])
+                .args([
+                    "powershell",
+                    "-NoProfile",
+                    "-NonInteractive",
+                    "-EncodedCommand",
+                ])
+                .arg(script);
+            c
+        }
+        Transport::Winrm => {
+            // Invoke-Command doesn't carry the remote exit code, so the
+            // remote side appends it as the last line of output.
+            let local = format!(
+                "$ErrorActionPreference = 'Stop'\n\
+                 $out = @(Invoke-Command -ComputerName '{}' -ArgumentList '{script}' -ScriptBlock {{\n\
+                     param($cmd)\n\
+                     powershell -NoProfile -NonInteractive -EncodedCommand $cmd\n\
+                     $LASTEXITCODE\n\
+                 }})\n\
+                 $out | Select-Object -SkipLast 1\n\
+                 exit [int]$out[-1]\n",
+                host.address.replace('\'', "''")
+            );
+            let mut c = Command::new("powershell");
+            c.args(["-NoProfile", "-NonInteractive", "-EncodedCommand"])
+                .arg(powershell::encode(&local));
+            c
+        }
+    };
+    command
+        .output()
+        .with_context(|| format!("Failed to connect to {}", host.address))
+}
+
+fn remote_script(config: &[u8]) -> String {
+    format!(
+        "$ErrorActionPreference = 'Stop'\n\
+         $dir = Join-Path $HOME '.edge-profile'\n\
+         New-Item -ItemType Directory -Force $dir | Out-Null\n\
+         $path = Join-Path $dir 'config.toml'\n\
+         [IO.File]::WriteAllBytes($path, [Convert]::FromBase64String('{}'))\n\
+         edge-profile apply --config $path | Out-Null\n\
+         if ($LASTEXITCODE -notin 0, 3) {{ exit $LASTEXITCODE }}\n\
+         edge-profile verify --porcelain --config $path\n\
+         exit $LASTEXITCODE\n",
+        BASE64.encode(config)
+    )
+}
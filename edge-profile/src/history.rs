@@ -0,0 +1,177 @@
+//! A log of every run that wrote policies, for `history`.
+//!
+//! `~/.edge-profile/history.jsonl` holds one JSON object per run: when, the
+//! command, the hive, the config's hash and how many values changed. Runs
+//! that found nothing to change are logged too, so the log shows when the
+//! tool last checked as well as when it last wrote. Runs that did change
+//! something also save a snapshot of the key as they left it, which
+//! `rollback` restores. Only the newest [`MAX_RUNS`] are kept, along with
+//! their snapshots.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::backup;
+use crate::config;
+use crate::diff::Change;
+use crate::registry::Scope;
+use crate::webhook;
+
+const MAX_RUNS: usize = 1000;
+
+#[derive(Serialize, Deserialize)]
+struct Run {
+    /// Counts up from 1; what `rollback --to` takes.
+    id: u64,
+    /// Seconds since the Unix epoch.
+    time: u64,
+    command: String,
+    scope: Scope,
+    /// SHA-256 of the config file, for commands that read one.
+    config_hash: Option<String>,
+    added: usize,
+    changed: usize,
+    removed: usize,
+    /// Backup of the key right after the run, if it changed anything.
+    snapshot: Option<PathBuf>,
+}
+
+/// Log a run that wrote `changes`. Best-effort, like the journal.
+pub fn record(command: &str, scope: Scope, config: Option<&Path>, changes: &[Change]) {
+    let count = |kind: fn(&Change) -> bool| changes.iter().filter(|c| kind(c)).count();
+    append(Run {
+        id: 0,
+        time: backup::now(),
+        command: command.to_owned(),
+        scope,
+        config_hash: config.and_then(|path| webhook::config_hash(path).ok()),
+        added: count(|c| matches!(c, Change::Added(_))),
+        changed: count(|c| matches!(c, Change::Changed { .. })),
+        removed: count(|c| matches!(c, Change::Removed(_))),
+        snapshot: None,
+    });
+}
+
+/// Log a `clean` that removed `removed` values.
+pub fn record_clean(scope: Scope, removed: usize) {
+    append(Run {
+        id: 0,
+        time: backup::now(),
+        command: "clean".to_owned(),
+        scope,
+        config_hash: None,
+        added: 0,
+        changed: 0,
+        removed,
+        snapshot: None,
+    });
+}
+
+/// Log a `restore` or `rollback`, which can touch values edge-profile
+/// doesn't manage, so counts them rather than taking policy changes.
+pub fn record_restore(scope: Scope, added: usize, changed: usize, removed: usize) {
+    append(Run {
+        id: 0,
+        time: backup::now(),
+        command: "restore".to_owned(),
+        scope,
+        config_hash: None,
+        added,
+        changed,
+        removed,
+        snapshot: None,
+    });
+}
+
+/// Print the newest `limit` runs, oldest first, optionally for one hive.
+pub fn show(limit: usize, scope: Option<Scope>) -> Result<()> {
+    let runs: Vec<Run> = load()?
+        .into_iter()
+        .filter(|r| scope.is_none_or(|s| r.scope == s))
+        .collect();
+    if runs.is_empty() {
+        println!("No runs recorded yet.");
+        return Ok(());
+    }
+
+    println!(
+        "{:>5}  {:<20} {:<8} {:<5} {:>6} {:>8} {:>8}  config",
+        "#", "time (UTC)", "command", "hive", "added", "changed", "removed"
+    );
+    for run in &runs[runs.len().saturating_sub(limit)..] {
+        let hash = run
+            .config_hash
+            .as_deref()
+            .map_or("-", |h| &h[..h.len().min(12)]);
+        println!(
+            "{:>5}  {:<20} {:<8} {:<5} {:>6} {:>8} {:>8}  {hash}",
+            run.id,
+            backup::format_time(run.time),
+            run.command,
+            run.scope.to_string(),
+            run.added,
+            run.changed,
+            run.removed
+        );
+    }
+    Ok(())
+}
+
+/// The snapshot `rollback --to <id>` restores.
+pub fn snapshot(id: u64) -> Result<PathBuf> {
+    let runs = load()?;
+    let Some(run) = runs.iter().find(|r| r.id == id) else {
+        bail!("No run #{id} in the history; see `edge-profile history`");
+    };
+    match &run.snapshot {
+        Some(path) if path.exists() => Ok(path.clone()),
+        Some(path) => bail!("The snapshot for run #{id}, {}, is gone", path.display()),
+        None => bail!("Run #{id} changed nothing, so it has no snapshot; pick an earlier run"),
+    }
+}
+
+fn append(mut run: Run) {
+    let result = (|| -> Result<()> {
+        let mut runs = load()?;
+        run.id = runs.last().map_or(1, |r| r.id + 1);
+        if run.added + run.changed + run.removed > 0 {
+            run.snapshot = Some(backup::snapshot(run.scope)?.0);
+        }
+        runs.push(run);
+        let (dropped, keep) = runs.split_at(runs.len().saturating_sub(MAX_RUNS));
+        for path in dropped.iter().filter_map(|r| r.snapshot.as_ref()) {
+            let _ = std::fs::remove_file(path);
+        }
+        let mut text = String::new();
+        for run in keep {
+            text.push_str(&serde_json::to_string(run)?);
+            text.push('\n');
+        }
+        let path = path()?;
+        std::fs::create_dir_all(path.parent().expect("history file has a parent"))?;
+        std::fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+    })();
+    if let Err(e) = result {
+        warn!("Failed to update the run history: {e:#}");
+    }
+}
+
+/// Every logged run, oldest first. Lines that don't parse are skipped.
+fn load() -> Result<Vec<Run>> {
+    let path = path()?;
+    match std::fs::read_to_string(&path) {
+        Ok(text) => Ok(text
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+pub fn path() -> Result<PathBuf> {
+    Ok(config::data_dir()?.join("history.jsonl"))
+}
@@ -0,0 +1,61 @@
+//! User commands from the config's `[hooks]` table, run through `cmd /C`.
+
+use std::io::Write;
+use std::os::windows::process::CommandExt;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use tracing::{debug, warn};
+
+use crate::diff::Change;
+use crate::registry::Scope;
+
+/// Run the pre-apply hooks in order, stopping at the first that fails. They
+/// get the pending changes the same way post-apply hooks get applied ones.
+pub fn pre_apply(hooks: &[String], scope: Scope, changes: &[Change]) -> Result<()> {
+    let summary = summarize(changes);
+    for hook in hooks {
+        run(hook, scope, changes.len(), &summary)
+            .with_context(|| format!("pre_apply hook `{hook}` failed, nothing was written"))?;
+    }
+    Ok(())
+}
+
+/// Run each post-apply hook with the change summary: the count and scope in
+/// `EDGE_PROFILE_CHANGES` / `EDGE_PROFILE_SCOPE`, and one change per line on
+/// stdin. The apply has already happened, so failures are only reported.
+pub fn post_apply(hooks: &[String], scope: Scope, changes: &[Change]) {
+    let summary = summarize(changes);
+    for hook in hooks {
+        if let Err(e) = run(hook, scope, changes.len(), &summary) {
+            warn!("post_apply hook `{hook}`: {e:#}");
+        }
+    }
+}
+
+fn summarize(changes: &[Change]) -> String {
+    changes.iter().map(|c| format!("{c}\n")).collect()
+}
+
+fn run(hook: &str, scope: Scope, changed: usize, stdin: &str) -> Result<()> {
+    debug!("running hook `{hook}`");
+    let mut child = Command::new("cmd")
+        .arg("/C")
+        .raw_arg(hook)
+        .env("EDGE_PROFILE_SCOPE", scope.to_string())
+        .env("EDGE_PROFILE_CHANGES", changed.to_string())
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to start hook")?;
+
+    // A hook that ignores stdin may exit before reading it; that's fine.
+    if let Some(mut pipe) = child.stdin.take() {
+        let _ = pipe.write_all(stdin.as_bytes());
+    }
+
+    let status = child.wait().context("Failed to wait for hook")?;
+    if !status.success() {
+        bail!("exited with {status}");
+    }
+    Ok(())
+}
@@ -0,0 +1,14 @@
+//! HTTP client setup. TLS goes through the OS (SChannel) rather than a
+//! bundled stack, so corporate root certificates are honored.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+pub fn agent() -> Result<ureq::Agent> {
+    let tls = native_tls::TlsConnector::new().context("Failed to initialize TLS")?;
+    Ok(ureq::AgentBuilder::new()
+        .tls_connector(Arc::new(tls))
+        .user_agent(concat!("edge-profile/", env!("CARGO_PKG_VERSION")))
+        .build())
+}
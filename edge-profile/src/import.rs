@@ -0,0 +1,555 @@
+//! `import`: a best-effort config for policies that are already in the
+//! registry, set by hand or by another tool, or set for Chrome.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+
+use crate::catalog::{self, PolicyKind};
+use crate::config::{
+    AccountConfig, AdvancedConfig, AppearanceConfig, BrowserConfig, Config, ContentConfig,
+    DownloadsConfig, ExtensionSetting, FavoritesConfig, FeaturesConfig, IeModeConfig, ImportConfig,
+    LanguageConfig, PerformanceConfig, PrivacyConfig, SearchConfig, SecurityConfig,
+    TelemetryConfig, UrlsConfig,
+};
+use crate::output;
+use crate::policy::{self, PolicyEntry, RegValue};
+use crate::registry::{Backend, Registry, Scope, CHROME_POLICY_PATH};
+
+/// Whose policy key to read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Browser {
+    #[default]
+    Edge,
+    Chrome,
+}
+
+/// Chrome policies whose Edge counterpart has another name but the same
+/// values.
+const CHROME_RENAMES: &[(&str, &str)] = &[
+    ("IncognitoModeAvailability", "InPrivateModeAvailability"),
+    ("PasswordLeakDetectionEnabled", "PasswordMonitorAllowed"),
+    ("SafeBrowsingEnabled", "SmartScreenEnabled"),
+];
+
+pub fn import(scope: Scope, from: Browser, out: Option<&Path>) -> Result<()> {
+    let mut flagged = Vec::new();
+    let current = match from {
+        Browser::Edge => Registry.read_current(scope)?,
+        Browser::Chrome => from_chrome(Registry.read_key(scope, CHROME_POLICY_PATH)?, &mut flagged),
+    };
+    if current.is_empty() && flagged.is_empty() {
+        println!("No {scope} {from:?} policies to import.");
+        return Ok(());
+    }
+
+    let mut cfg = Config::default();
+    let mut lists: Vec<&str> = Vec::new();
+    for entry in &current {
+        if !entry.subkey.is_empty() {
+            if !lists.contains(&entry.subkey.as_str()) {
+                lists.push(&entry.subkey);
+            }
+            continue;
+        }
+        if let Err(reason) = map_value(&mut cfg, entry) {
+            flagged.push(format!("{}: {reason}", entry.path()));
+        }
+    }
+    for list in lists {
+        let items = sorted_items(&current, list);
+        if list == "ExtensionInstallForcelist" {
+            for value in items {
+                match policy::extension_spec(value) {
+                    Some(spec) => {
+                        let id = spec.split_once(':').map_or(spec.as_str(), |(_, id)| id);
+                        cfg.extensions
+                            .get_or_insert_with(Default::default)
+                            .insert(id.to_owned(), spec.clone().into());
+                    }
+                    None => flagged.push(format!(
+                        "{list}: {value} is not from the Edge or Chrome store; skipped"
+                    )),
+                }
+            }
+        } else if list == "RestoreOnStartupURLs" {
+            browser(&mut cfg).startup_urls = Some(items.into_iter().map(str::to_owned).collect());
+        } else if list == "URLBlocklist" || list == "URLAllowlist" {
+            let urls = cfg.urls.get_or_insert_with(UrlsConfig::default);
+            let patterns = Some(items.into_iter().map(str::to_owned).collect());
+            if list == "URLBlocklist" {
+                urls.block = patterns;
+            } else {
+                urls.allow = patterns;
+            }
+        } else if let Some((key, _)) = policy::EXTENSION_LISTS.iter().find(|(_, p)| *p == list) {
+            let items: Vec<toml::Value> = items.into_iter().map(Into::into).collect();
+            cfg.extensions
+                .get_or_insert_with(Default::default)
+                .insert((*key).to_owned(), items.into());
+        } else if list == "SpellcheckLanguage" {
+            language(&mut cfg).spellcheck_languages =
+                Some(items.into_iter().map(str::to_owned).collect());
+        } else if list == "SpellcheckLanguageBlocklist" {
+            language(&mut cfg).spellcheck_language_blocklist =
+                Some(items.into_iter().map(str::to_owned).collect());
+        } else if let Some(urls) = site_list(&mut cfg, list) {
+            *urls = Some(items.into_iter().map(str::to_owned).collect());
+        } else {
+            let items: Vec<toml::Value> = items.into_iter().map(Into::into).collect();
+            raw(&mut cfg).insert(list.to_owned(), items.into());
+        }
+    }
+
+    let text = crate::config::to_toml(&cfg)?;
+    match out {
+        Some(path) => {
+            std::fs::write(path, &text)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => print!("{text}"),
+    }
+    for line in &flagged {
+        anstream::eprintln!("{}", output::paint(output::WARNING, line));
+    }
+    if !flagged.is_empty() {
+        eprintln!(
+            "{} value(s) need a look before applying this config.",
+            flagged.len()
+        );
+    }
+    Ok(())
+}
+
+/// Keep the Chrome policies Edge also has, under their Edge names. The rest
+/// are reported in `flagged` and dropped: Edge would ignore them.
+fn from_chrome(entries: Vec<PolicyEntry>, flagged: &mut Vec<String>) -> Vec<PolicyEntry> {
+    let mut dropped: Vec<String> = Vec::new();
+    let mut kept = Vec::new();
+    for mut entry in entries {
+        let name = if entry.subkey.is_empty() {
+            &mut entry.name
+        } else {
+            &mut entry.subkey
+        };
+        if let Some((_, edge)) = CHROME_RENAMES.iter().find(|(chrome, _)| chrome == name) {
+            *name = (*edge).to_owned();
+        }
+        if catalog::lookup(name).is_some() {
+            kept.push(entry);
+        } else if !dropped.contains(name) {
+            dropped.push(name.clone());
+        }
+    }
+    flagged.extend(
+        dropped
+            .into_iter()
+            .map(|name| format!("{name}: no Edge equivalent in the policy catalog; skipped")),
+    );
+    kept
+}
+
+/// Put a top-level value under its config key, or in `[raw]`. Errors say
+/// why the value was left out.
+fn map_value(cfg: &mut Config, entry: &PolicyEntry) -> Result<(), String> {
+    let name = entry.name.as_str();
+    match (name, &entry.value) {
+        ("DefaultSearchProviderEnabled", RegValue::Dword(1)) => {
+            cfg.search.get_or_insert_with(SearchConfig::default);
+        }
+        ("DefaultSearchProviderName", RegValue::Sz(v)) => search(cfg).provider = Some(v.clone()),
+        ("DefaultSearchProviderSearchURL", RegValue::Sz(v)) => {
+            search(cfg).search_url = Some(v.clone())
+        }
+        ("DefaultSearchProviderSuggestURL", RegValue::Sz(v)) => {
+            search(cfg).suggest_url = Some(v.clone())
+        }
+        ("HomepageLocation", RegValue::Sz(v)) => browser(cfg).homepage = Some(v.clone()),
+        ("NewTabPageLocation", RegValue::Sz(v)) => browser(cfg).new_tab_page = Some(v.clone()),
+        ("DnsOverHttpsMode", RegValue::Sz(v)) => privacy(cfg).dns_over_https = Some(v.clone()),
+        ("DnsOverHttpsTemplates", RegValue::Sz(v)) => {
+            privacy(cfg).dns_over_https_templates =
+                Some(v.split_whitespace().map(str::to_owned).collect())
+        }
+        ("WebRtcLocalhostIpHandling", RegValue::Sz(v)) => {
+            privacy(cfg).webrtc_ip_handling = Some(v.clone())
+        }
+        ("InternetExplorerIntegrationSiteList", RegValue::Sz(v)) => {
+            ie_mode(cfg).site_list = Some(v.clone())
+        }
+        ("SSLVersionMin", RegValue::Sz(v)) => security(cfg).min_tls_version = Some(v.clone()),
+        ("DownloadDirectory", RegValue::Sz(v)) => {
+            downloads(cfg).default_directory = Some(v.clone())
+        }
+        ("ExtensionSettings", RegValue::Sz(v)) => match parse_extension_settings(v) {
+            Some(settings) => cfg.extension_settings = Some(settings),
+            None => {
+                raw(cfg).insert(name.to_owned(), v.clone().into());
+                return Err("uses settings this tool has no keys for; kept in [raw]".to_owned());
+            }
+        },
+        ("ManagedFavorites", RegValue::Sz(v)) => match parse_favorites(v) {
+            Some(favorites) => cfg.favorites = Some(favorites),
+            None => {
+                raw(cfg).insert(name.to_owned(), v.clone().into());
+                return Err("not a favorites list this tool understands; kept in [raw]".to_owned());
+            }
+        },
+        (
+            "RestoreOnStartup"
+            | "TrackingPrevention"
+            | "DownloadRestrictions"
+            | "BrowserSignin"
+            | "SleepingTabsTimeout"
+            | "EfficiencyMode"
+            | "NewTabPageAllowedBackgroundTypes"
+            | "DiagnosticData"
+            | "ExperimentationAndConfigurationServiceControl"
+            | "DeveloperToolsAvailability"
+            | "SearchInSidebarEnabled"
+            | "PasswordProtectionWarningTrigger"
+            | "InPrivateModeAvailability"
+            | "RelaunchNotification"
+            | "InternetExplorerIntegrationLevel"
+            | "DefaultCookiesSetting"
+            | "DefaultJavaScriptSetting"
+            | "DefaultPopupsSetting"
+            | "DefaultNotificationsSetting"
+            | "DefaultGeolocationSetting"
+            | "DefaultImagesSetting",
+            RegValue::Dword(v),
+        ) => {
+            let Some(choice) = catalog::lookup(name).and_then(|d| d.value_name(*v)) else {
+                raw(cfg).insert(name.to_owned(), i64::from(*v).into());
+                return Err(format!("{v} is not a known value; kept in [raw]"));
+            };
+            let choice = Some(choice.to_owned());
+            match name {
+                "RestoreOnStartup" => browser(cfg).restore_on_startup = choice,
+                "InPrivateModeAvailability" => browser(cfg).inprivate = choice,
+                "RelaunchNotification" => browser(cfg).relaunch_notification = choice,
+                "InternetExplorerIntegrationLevel" => ie_mode(cfg).integration_level = choice,
+                "TrackingPrevention" => privacy(cfg).tracking_prevention = choice,
+                "DownloadRestrictions" => downloads(cfg).restrictions = choice,
+                "BrowserSignin" => account(cfg).browser_signin = choice,
+                "SleepingTabsTimeout" => performance(cfg).sleeping_tabs_timeout = choice,
+                "EfficiencyMode" => performance(cfg).efficiency_mode = choice,
+                "NewTabPageAllowedBackgroundTypes" => appearance(cfg).new_tab_background = choice,
+                "DiagnosticData" => telemetry(cfg).diagnostic_data = choice,
+                "ExperimentationAndConfigurationServiceControl" => {
+                    telemetry(cfg).experimentation_and_configuration_service = choice
+                }
+                "DeveloperToolsAvailability" => advanced(cfg).developer_tools = choice,
+                "SearchInSidebarEnabled" => search(cfg).sidebar_search = choice,
+                "PasswordProtectionWarningTrigger" => {
+                    privacy(cfg).password_protection_warning_trigger = choice
+                }
+                "DefaultCookiesSetting" => content(cfg).cookies = choice,
+                "DefaultJavaScriptSetting" => content(cfg).javascript = choice,
+                "DefaultPopupsSetting" => content(cfg).popups = choice,
+                "DefaultNotificationsSetting" => content(cfg).notifications = choice,
+                "DefaultGeolocationSetting" => content(cfg).geolocation = choice,
+                _ => content(cfg).images = choice,
+            }
+        }
+        ("RelaunchNotificationPeriod", RegValue::Dword(v)) => {
+            if *v == 0 || *v % policy::HOUR_MS != 0 {
+                raw(cfg).insert(name.to_owned(), i64::from(*v).into());
+                return Err(format!(
+                    "{v} ms is not a whole number of hours; kept in [raw]"
+                ));
+            }
+            browser(cfg).relaunch_notification_period = Some(*v / policy::HOUR_MS);
+        }
+        ("DefaultBrowserSettingEnabled", RegValue::Dword(0)) => {
+            browser(cfg).default_browser_prompt = Some(false)
+        }
+        ("DefaultBrowserSettingsCampaignEnabled", RegValue::Dword(v @ (0 | 1))) => {
+            let prompt = &mut browser(cfg).default_browser_prompt;
+            // Either policy at 0 means no prompts.
+            if *prompt != Some(false) {
+                *prompt = Some(*v == 1);
+            }
+        }
+        (
+            "ClearBrowsingDataOnExit" | "ClearCachedImagesAndFilesOnExit",
+            RegValue::Dword(v @ (0 | 1)),
+        ) => {
+            let kinds = privacy(cfg).clear_on_exit.get_or_insert_with(Vec::new);
+            if *v == 1 {
+                let (kind, _) = policy::CLEAR_ON_EXIT
+                    .iter()
+                    .find(|(_, policy)| *policy == name)
+                    .expect("both policies have a kind");
+                kinds.push((*kind).to_owned());
+            }
+        }
+        (_, RegValue::Dword(v @ (0 | 1))) if toggle_section(name).is_some() => {
+            let toggle = match toggle_section(name) {
+                Some("features") => cfg
+                    .features
+                    .get_or_insert_with(FeaturesConfig::default)
+                    .toggle_mut(name),
+                _ => cfg
+                    .import
+                    .get_or_insert_with(ImportConfig::default)
+                    .toggle_mut(name),
+            };
+            *toggle.expect("every toggle key has a field") = Some(*v == 1);
+        }
+        (
+            "ShowHomeButton"
+            | "HomepageIsNewTabPage"
+            | "FavoritesBarEnabled"
+            | "HideFirstRunExperience"
+            | "AlwaysOpenPdfExternally"
+            | "RestorePdfView"
+            | "PasswordManagerEnabled"
+            | "AutofillCreditCardEnabled"
+            | "AutofillAddressEnabled"
+            | "PromptForDownloadLocation"
+            | "SmartScreenEnabled"
+            | "SmartScreenPuaEnabled"
+            | "PreventSmartScreenPromptOverride"
+            | "SitePerProcess"
+            | "InsecureFormsWarningsEnabled"
+            | "SSLErrorOverrideAllowed"
+            | "SyncDisabled"
+            | "NonRemovableProfileEnabled"
+            | "ImplicitSignInEnabled"
+            | "SleepingTabsEnabled"
+            | "StartupBoostEnabled"
+            | "BackgroundModeEnabled"
+            | "VerticalTabsAllowed"
+            | "HubsSidebarEnabled"
+            | "NewTabPageContentEnabled"
+            | "NewTabPageQuickLinksEnabled"
+            | "PersonalizationReportingEnabled"
+            | "UserFeedbackAllowed"
+            | "TranslateEnabled"
+            | "SpellcheckEnabled"
+            | "BrowserAddProfileEnabled"
+            | "BrowserGuestModeEnabled"
+            | "CommandLineFlagSecurityWarningsEnabled"
+            | "SearchSuggestEnabled"
+            | "AddressBarMicrosoftSearchInBingProviderEnabled"
+            | "PasswordMonitorAllowed"
+            | "PasswordGeneratorEnabled",
+            RegValue::Dword(v @ (0 | 1)),
+        ) => {
+            let v = Some(*v == 1);
+            match name {
+                "ShowHomeButton" => browser(cfg).show_home_button = v,
+                "HomepageIsNewTabPage" => browser(cfg).homepage_is_new_tab = v,
+                "FavoritesBarEnabled" => browser(cfg).favorites_bar = v,
+                "HideFirstRunExperience" => browser(cfg).hide_first_run = v,
+                "AlwaysOpenPdfExternally" => browser(cfg).always_open_pdf_externally = v,
+                "RestorePdfView" => browser(cfg).restore_pdf_view = v,
+                "PasswordManagerEnabled" => privacy(cfg).password_manager = v,
+                "AutofillCreditCardEnabled" => privacy(cfg).autofill_credit_card = v,
+                "AutofillAddressEnabled" => privacy(cfg).autofill_address = v,
+                "PromptForDownloadLocation" => downloads(cfg).prompt_for_location = v,
+                "SmartScreenEnabled" => security(cfg).smartscreen = v,
+                "SmartScreenPuaEnabled" => security(cfg).smartscreen_pua = v,
+                "PreventSmartScreenPromptOverride" => {
+                    security(cfg).prevent_smartscreen_override = v
+                }
+                "SitePerProcess" => security(cfg).site_per_process = v,
+                "InsecureFormsWarningsEnabled" => security(cfg).insecure_forms_warnings = v,
+                "SSLErrorOverrideAllowed" => security(cfg).ssl_error_override = v,
+                "SyncDisabled" => account(cfg).sync_disabled = v,
+                "NonRemovableProfileEnabled" => account(cfg).non_removable_profile_enabled = v,
+                "ImplicitSignInEnabled" => account(cfg).implicit_signin_enabled = v,
+                "SleepingTabsEnabled" => performance(cfg).sleeping_tabs = v,
+                "StartupBoostEnabled" => performance(cfg).startup_boost = v,
+                "BackgroundModeEnabled" => performance(cfg).background_mode = v,
+                "VerticalTabsAllowed" => appearance(cfg).vertical_tabs = v,
+                "HubsSidebarEnabled" => appearance(cfg).sidebar = v,
+                "NewTabPageContentEnabled" => appearance(cfg).new_tab_content = v,
+                "NewTabPageQuickLinksEnabled" => appearance(cfg).new_tab_quick_links = v,
+                "PersonalizationReportingEnabled" => telemetry(cfg).personalization_reporting = v,
+                "UserFeedbackAllowed" => telemetry(cfg).user_feedback_allowed = v,
+                "TranslateEnabled" => language(cfg).translate = v,
+                "SpellcheckEnabled" => language(cfg).spellcheck = v,
+                "BrowserAddProfileEnabled" => advanced(cfg).add_profile = v,
+                "BrowserGuestModeEnabled" => advanced(cfg).guest_mode = v,
+                "CommandLineFlagSecurityWarningsEnabled" => {
+                    advanced(cfg).command_line_flag_warnings = v
+                }
+                "SearchSuggestEnabled" => search(cfg).suggestions = v,
+                "AddressBarMicrosoftSearchInBingProviderEnabled" => {
+                    search(cfg).bing_suggestions = v
+                }
+                "PasswordMonitorAllowed" => privacy(cfg).password_monitor = v,
+                _ => privacy(cfg).password_generation = v,
+            }
+        }
+        (_, RegValue::Dword(v)) => {
+            let def = catalog::lookup(name);
+            let value = match def.map(|d| d.kind) {
+                Some(PolicyKind::Boolean) if *v <= 1 => toml::Value::Boolean(*v == 1),
+                _ => i64::from(*v).into(),
+            };
+            raw(cfg).insert(name.to_owned(), value);
+            if def.is_none() {
+                return Err("not in the policy catalog; kept in [raw]".to_owned());
+            }
+        }
+        (_, RegValue::Sz(v)) => {
+            raw(cfg).insert(name.to_owned(), v.clone().into());
+            if catalog::lookup(name).is_none() {
+                return Err("not in the policy catalog; kept in [raw]".to_owned());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `ManagedFavorites` JSON back into `[favorites]`: an optional
+/// `toplevel_name` element, then the favorites.
+fn parse_favorites(json: &str) -> Option<FavoritesConfig> {
+    let mut favorites = FavoritesConfig::default();
+    for item in serde_json::from_str::<Vec<serde_json::Value>>(json).ok()? {
+        match item.get("toplevel_name") {
+            Some(name) => favorites.toplevel_name = Some(name.as_str()?.to_owned()),
+            None => favorites.items.push(serde_json::from_value(item).ok()?),
+        }
+    }
+    Some(favorites)
+}
+
+/// `ExtensionSettings` JSON back into `[extension_settings]`, if every
+/// field in it has a config key. Installs from a store become `edge:` and
+/// `chrome:` specs.
+fn parse_extension_settings(json: &str) -> Option<BTreeMap<String, ExtensionSetting>> {
+    const FIELDS: &[&str] = &["installation_mode", "toolbar_state", "update_url"];
+    let objects: BTreeMap<String, serde_json::Map<String, serde_json::Value>> =
+        serde_json::from_str(json).ok()?;
+    let mut settings = BTreeMap::new();
+    for (id, object) in objects {
+        if !object.keys().all(|k| FIELDS.contains(&k.as_str())) {
+            return None;
+        }
+        let mut setting: ExtensionSetting =
+            serde_json::from_value(serde_json::Value::Object(object)).ok()?;
+        // The apply fills the store URL back in for extensions it installs.
+        let installs = matches!(
+            setting.installation_mode.as_deref(),
+            Some("force_installed" | "normal_installed")
+        );
+        let spec = match setting.update_url.as_deref() {
+            Some(url) if installs && id != "*" => {
+                match policy::extension_spec(&format!("{id};{url}")) {
+                    Some(spec) => {
+                        setting.update_url = None;
+                        spec
+                    }
+                    None => id,
+                }
+            }
+            _ => id,
+        };
+        settings.insert(spec, setting);
+    }
+    Some(settings)
+}
+
+/// The section of a policy set by a `toggles()` table, `[features]` or
+/// `[import]`.
+fn toggle_section(policy: &str) -> Option<&'static str> {
+    let (key, _) = policy::CONFIG_KEYS.iter().find(|(_, p)| *p == policy)?;
+    let (section, _) = key.split_once('.')?;
+    matches!(section, "features" | "import").then_some(section)
+}
+
+/// Values of a list subkey in item order.
+fn sorted_items<'a>(entries: &'a [PolicyEntry], subkey: &str) -> Vec<&'a str> {
+    let mut items: Vec<(u32, &str)> = entries
+        .iter()
+        .filter(|e| e.subkey == subkey)
+        .filter_map(|e| match &e.value {
+            RegValue::Sz(s) => Some((e.name.parse().unwrap_or(u32::MAX), s.as_str())),
+            RegValue::Dword(_) => None,
+        })
+        .collect();
+    items.sort_by_key(|(n, _)| *n);
+    items.into_iter().map(|(_, s)| s).collect()
+}
+
+fn search(cfg: &mut Config) -> &mut SearchConfig {
+    cfg.search.get_or_insert_with(SearchConfig::default)
+}
+
+fn browser(cfg: &mut Config) -> &mut BrowserConfig {
+    cfg.browser.get_or_insert_with(BrowserConfig::default)
+}
+
+fn privacy(cfg: &mut Config) -> &mut PrivacyConfig {
+    cfg.privacy.get_or_insert_with(PrivacyConfig::default)
+}
+
+fn downloads(cfg: &mut Config) -> &mut DownloadsConfig {
+    cfg.downloads.get_or_insert_with(DownloadsConfig::default)
+}
+
+fn security(cfg: &mut Config) -> &mut SecurityConfig {
+    cfg.security.get_or_insert_with(SecurityConfig::default)
+}
+
+fn content(cfg: &mut Config) -> &mut ContentConfig {
+    cfg.content.get_or_insert_with(ContentConfig::default)
+}
+
+/// The `[content]` list a `*ForUrls` policy belongs in, if it has one.
+fn site_list<'a>(cfg: &'a mut Config, policy: &str) -> Option<&'a mut Option<Vec<String>>> {
+    let key = policy::config_key(policy)?.strip_prefix("content.")?;
+    let content = content(cfg);
+    match key.split_once('.') {
+        Some(("allow", setting)) => content
+            .allow
+            .get_or_insert_with(Default::default)
+            .list_mut(setting),
+        Some(("block", setting)) => content
+            .block
+            .get_or_insert_with(Default::default)
+            .list_mut(setting),
+        _ if key == "cookies_session_only" => Some(&mut content.cookies_session_only),
+        _ => None,
+    }
+}
+
+fn account(cfg: &mut Config) -> &mut AccountConfig {
+    cfg.account.get_or_insert_with(AccountConfig::default)
+}
+
+fn performance(cfg: &mut Config) -> &mut PerformanceConfig {
+    cfg.performance
+        .get_or_insert_with(PerformanceConfig::default)
+}
+
+fn appearance(cfg: &mut Config) -> &mut AppearanceConfig {
+    cfg.appearance.get_or_insert_with(AppearanceConfig::default)
+}
+
+fn telemetry(cfg: &mut Config) -> &mut TelemetryConfig {
+    cfg.telemetry.get_or_insert_with(TelemetryConfig::default)
+}
+
+fn language(cfg: &mut Config) -> &mut LanguageConfig {
+    cfg.language.get_or_insert_with(LanguageConfig::default)
+}
+
+fn advanced(cfg: &mut Config) -> &mut AdvancedConfig {
+    cfg.advanced.get_or_insert_with(AdvancedConfig::default)
+}
+
+fn ie_mode(cfg: &mut Config) -> &mut IeModeConfig {
+    cfg.ie_mode.get_or_insert_with(IeModeConfig::default)
+}
+
+fn raw(cfg: &mut Config) -> &mut toml::map::Map<String, toml::Value> {
+    cfg.raw.get_or_insert_with(Default::default)
+}
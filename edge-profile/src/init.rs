@@ -0,0 +1,133 @@
+//! `init`: a starter config from a few questions.
+
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use edge_profile_core::PolicySet;
+
+use crate::catalog;
+
+/// (name, search URL, suggest URL)
+const SEARCH_PROVIDERS: &[(&str, &str, &str)] = &[
+    (
+        "DuckDuckGo",
+        "https://duckduckgo.com/?q={searchTerms}",
+        "https://duckduckgo.com/ac/?q={searchTerms}&type=list",
+    ),
+    (
+        "Google",
+        "https://www.google.com/search?q={searchTerms}",
+        "https://www.google.com/complete/search?output=chrome&q={searchTerms}",
+    ),
+    (
+        "Bing",
+        "https://www.bing.com/search?q={searchTerms}",
+        "https://www.bing.com/osjson.aspx?query={searchTerms}",
+    ),
+];
+
+pub fn run(path: &Path, yes: bool) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        bail!("init asks questions and needs a terminal");
+    }
+    if path.exists() && !crate::confirm(&format!("{} exists. Overwrite it?", path.display()), yes)?
+    {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let mut set = PolicySet::new();
+
+    let mut options: Vec<&str> = SEARCH_PROVIDERS.iter().map(|(name, _, _)| *name).collect();
+    options.push("keep Edge's default");
+    let choice = choose("Default search engine", &options)?;
+    if let Some((name, search, suggest)) = SEARCH_PROVIDERS.get(choice) {
+        set = set.search(*name, *search).suggest_url(*suggest);
+    }
+
+    let startup = ["previous_session", "new_tab", "keep Edge's default"];
+    let choice = choose("On startup, open", &startup)?;
+    if choice < 2 {
+        set = set.restore_on_startup(startup[choice]);
+    }
+
+    let levels = values_of("TrackingPrevention");
+    let mut options = levels.clone();
+    options.push("keep Edge's default");
+    let choice = choose("Tracking prevention", &options)?;
+    if let Some(level) = levels.get(choice) {
+        set = set.tracking_prevention(*level);
+    }
+
+    if ask_yes_no("Turn off the built-in password manager and autofill?")? {
+        set = set
+            .password_manager(false)
+            .autofill_credit_card(false)
+            .autofill_address(false);
+    }
+    set = set.hide_first_run(true);
+
+    println!(
+        "Extensions to force-install, one per line as edge:ID or chrome:ID (blank to finish):"
+    );
+    loop {
+        let line = ask("  extension")?;
+        if line.is_empty() {
+            break;
+        }
+        if !line.starts_with("edge:") && !line.starts_with("chrome:") {
+            println!(
+                "  Use edge:ID for the Edge Add-ons store or chrome:ID for the Chrome Web Store."
+            );
+            continue;
+        }
+        set = set.extension(line);
+    }
+
+    let text = set.to_toml()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!(
+        "\nWrote {}. Preview it with `edge-profile apply --dry-run`.",
+        path.display()
+    );
+    Ok(())
+}
+
+fn values_of(policy: &str) -> Vec<&'static str> {
+    catalog::lookup(policy).map_or_else(Vec::new, |d| d.values.iter().map(|(_, n)| *n).collect())
+}
+
+/// Numbered menu; returns the chosen index.
+fn choose(prompt: &str, options: &[&str]) -> Result<usize> {
+    println!("\n{prompt}:");
+    for (i, option) in options.iter().enumerate() {
+        println!("  {}) {option}", i + 1);
+    }
+    loop {
+        let answer = ask(&format!("Choose 1-{}", options.len()))?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => return Ok(n - 1),
+            _ => println!("  Enter a number from 1 to {}.", options.len()),
+        }
+    }
+}
+
+fn ask_yes_no(prompt: &str) -> Result<bool> {
+    println!();
+    Ok(ask(&format!("{prompt} [y/N]"))?.eq_ignore_ascii_case("y"))
+}
+
+fn ask(prompt: &str) -> Result<String> {
+    print!("{prompt}: ");
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input)? == 0 {
+        bail!("Aborted");
+    }
+    Ok(input.trim().to_owned())
+}
@@ -0,0 +1,84 @@
+//! `apply --interactive`: step through the pending changes and pick which
+//! ones to write, like `git add -p`.
+
+use std::io::IsTerminal;
+
+use anyhow::{bail, Result};
+
+use crate::diff::Change;
+use crate::output;
+use crate::policy::PolicyEntry;
+use crate::registry::{Backend, Registry, Scope};
+
+const HELP: &str = "\
+y - apply this change
+n - skip this change
+a - apply this and all remaining changes
+q - skip this and all remaining changes
+? - print help";
+
+/// Ask about each change in turn. Returns the accepted changes and the
+/// skipped ones.
+pub fn select(changes: Vec<Change>) -> Result<(Vec<Change>, Vec<Change>)> {
+    if !std::io::stdin().is_terminal() {
+        bail!("--interactive needs a terminal");
+    }
+
+    let total = changes.len();
+    let mut accepted = Vec::new();
+    let mut skipped = Vec::new();
+    let mut all = None;
+    for (i, change) in changes.into_iter().enumerate() {
+        let take = match all {
+            Some(take) => take,
+            None => {
+                anstream::eprintln!("{}", output::change(&change));
+                loop {
+                    eprint!("({}/{total}) Apply this change [y,n,a,q,?]? ", i + 1);
+                    let mut input = String::new();
+                    // EOF (Ctrl-Z) counts as quit.
+                    if std::io::stdin().read_line(&mut input)? == 0 {
+                        eprintln!();
+                        all = Some(false);
+                        break false;
+                    }
+                    match input.trim() {
+                        "y" => break true,
+                        "n" => break false,
+                        "a" => {
+                            all = Some(true);
+                            break true;
+                        }
+                        "q" => {
+                            all = Some(false);
+                            break false;
+                        }
+                        _ => eprintln!("{HELP}"),
+                    }
+                }
+            }
+        };
+        if take {
+            accepted.push(change);
+        } else {
+            skipped.push(change);
+        }
+    }
+    Ok((accepted, skipped))
+}
+
+/// Write just the given changes, leaving the rest of the key as it is.
+pub fn write(changes: &[Change], scope: Scope) -> Result<()> {
+    let mut set: Vec<PolicyEntry> = Vec::new();
+    let mut remove = Vec::new();
+    for change in changes {
+        match change {
+            Change::Removed(e) => remove.push(e.path()),
+            _ => set.push(change.entry().clone()),
+        }
+    }
+    Registry.set_values(&set, scope)?;
+    let remove: Vec<&str> = remove.iter().map(String::as_str).collect();
+    Registry.remove_values(&remove, scope)?;
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! What the last apply to each hive replaced, so `undo` can put it back.
+//!
+//! `~/.edge-profile/journal.json` holds, per hive, every value the apply
+//! added, changed or removed, with its data before and after. Each apply
+//! that writes something replaces the hive's record; `undo` consumes it.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::backup::{self, Data};
+use crate::config;
+use crate::diff::Change;
+use crate::output;
+use crate::registry::{Backend, Registry, Scope};
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    /// Seconds since the Unix epoch.
+    created: u64,
+    values: Vec<Touched>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Touched {
+    /// Relative to the Edge policy key, e.g. `ExtensionInstallForcelist\1`.
+    path: String,
+    /// `None` when the apply created the value.
+    before: Option<Data>,
+    /// `None` when the apply deleted the value.
+    after: Option<Data>,
+}
+
+/// Record the changes an apply just wrote. Best-effort: a failure only
+/// costs the ability to undo this apply.
+pub fn record(scope: Scope, changes: &[Change]) {
+    if changes.is_empty() {
+        return;
+    }
+    let values = changes
+        .iter()
+        .map(|change| match change {
+            Change::Added(new) => Touched {
+                path: new.path(),
+                before: None,
+                after: Some(Data::from(&new.value)),
+            },
+            Change::Changed { old, new } => Touched {
+                path: new.path(),
+                before: Some(Data::from(&old.value)),
+                after: Some(Data::from(&new.value)),
+            },
+            Change::Removed(old) => Touched {
+                path: old.path(),
+                before: Some(Data::from(&old.value)),
+                after: None,
+            },
+        })
+        .collect();
+    let record = Record {
+        created: backup::now(),
+        values,
+    };
+    let result = load().and_then(|mut journal| {
+        journal.insert(scope.to_string(), record);
+        save(&journal)
+    });
+    if let Err(e) = result {
+        warn!("Failed to write the undo journal: {e:#}");
+    }
+}
+
+/// Put back every value the last apply to `scope` touched, after showing
+/// what would change. Returns false if the user declined.
+pub fn undo(scope: Scope, yes: bool) -> Result<bool> {
+    let mut journal = load()?;
+    let Some(record) = journal.remove(&scope.to_string()) else {
+        println!("No apply to {scope} to undo.");
+        return Ok(true);
+    };
+
+    let mut restore = Vec::new();
+    let mut delete = Vec::new();
+    let mut changes = Vec::new();
+    for touched in record.values {
+        let after = touched.after.map(|d| d.into_entry(&touched.path));
+        match (touched.before.map(|d| d.into_entry(&touched.path)), after) {
+            (Some(before), Some(after)) => {
+                changes.push(Change::Changed {
+                    old: after,
+                    new: before.clone(),
+                });
+                restore.push(before);
+            }
+            (Some(before), None) => {
+                changes.push(Change::Added(before.clone()));
+                restore.push(before);
+            }
+            (None, Some(after)) => {
+                changes.push(Change::Removed(after));
+                delete.push(touched.path);
+            }
+            (None, None) => {}
+        }
+    }
+    for change in &changes {
+        anstream::println!("  {}", output::change(change));
+    }
+    if !crate::confirm(&format!("Undo the last apply to {scope}?"), yes)? {
+        return Ok(false);
+    }
+
+    let paths: Vec<&str> = delete.iter().map(String::as_str).collect();
+    Registry.remove_values(&paths, scope)?;
+    Registry.set_values(&restore, scope)?;
+    save(&journal)?;
+    println!(
+        "Undid the last apply to {scope}: {} value(s) restored, {} removed.",
+        restore.len(),
+        delete.len()
+    );
+    Ok(true)
+}
+
+fn load() -> Result<BTreeMap<String, Record>> {
+    let path = path()?;
+    match std::fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn save(journal: &BTreeMap<String, Record>) -> Result<()> {
+    let path = path()?;
+    std::fs::create_dir_all(path.parent().expect("journal file has a parent"))?;
+    std::fs::write(&path, serde_json::to_string_pretty(journal)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub fn path() -> Result<PathBuf> {
+    Ok(config::data_dir()?.join("journal.json"))
+}
@@ -0,0 +1,85 @@
+//! `list-policies`: every setting the config understands.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::catalog::{self, PolicyKind};
+use crate::{config, output, policy};
+
+pub fn list(config: Option<&Path>, all: bool) -> Result<()> {
+    // A missing or broken config just means nothing shows as set.
+    let entries = config::load(config)
+        .map(|cfg| policy::build_entries(&cfg))
+        .unwrap_or_default();
+    let is_set = |policy: &str| entries.iter().any(|e| e.policy_name() == policy);
+
+    let mut rows: Vec<(String, String, &str, bool)> = policy::CONFIG_KEYS
+        .iter()
+        .map(|(key, policy)| {
+            (
+                key.to_string(),
+                key_type(key, policy),
+                *policy,
+                is_set(policy),
+            )
+        })
+        .collect();
+    if all {
+        for def in catalog::POLICIES {
+            if policy::config_key(def.name).is_none() {
+                let key = format!("raw.{}", def.name);
+                rows.push((key, raw_type(def).to_owned(), def.name, is_set(def.name)));
+            }
+        }
+    }
+
+    let key_w = rows.iter().map(|r| r.0.len()).max().unwrap_or(0);
+    let type_w = rows.iter().map(|r| r.1.len()).max().unwrap_or(0);
+    let header = format!("  {:key_w$}  {:type_w$}  Policy", "Key", "Type");
+    anstream::println!("{}", output::paint(output::HEADER, header));
+    for (key, kind, policy, set) in &rows {
+        let line = format!(
+            "{} {key:key_w$}  {kind:type_w$}  {policy}",
+            if *set { '*' } else { ' ' }
+        );
+        if *set {
+            anstream::println!("{}", output::paint(output::ADDED, line));
+        } else {
+            println!("{line}");
+        }
+    }
+    println!("\n* set in the config");
+    if !all {
+        println!("Any other catalog policy can go under [raw]; --all lists them.");
+    }
+    Ok(())
+}
+
+/// The TOML type a key takes.
+fn key_type(key: &str, policy: &str) -> String {
+    match key {
+        "search" => return "table".to_owned(),
+        "extensions" => return "table of \"edge:ID\" | \"chrome:ID\"".to_owned(),
+        _ => {}
+    }
+    let Some(def) = catalog::lookup(policy) else {
+        return "?".to_owned();
+    };
+    if def.values.is_empty() {
+        return raw_type(def).to_owned();
+    }
+    // Enumerated settings take the value names; `[raw]` takes the DWORD.
+    let names: Vec<String> = def.values.iter().map(|(_, n)| format!("\"{n}\"")).collect();
+    names.join(" | ")
+}
+
+/// The TOML type a policy takes under `[raw]`.
+fn raw_type(def: &catalog::PolicyDef) -> &'static str {
+    match def.kind {
+        PolicyKind::Boolean => "bool",
+        PolicyKind::Integer => "integer",
+        PolicyKind::String | PolicyKind::Dictionary => "string",
+        PolicyKind::List => "array of strings",
+    }
+}
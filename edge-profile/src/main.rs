@@ -1,17 +1,92 @@
-mod config;
-mod policy;
+mod adhoc;
+mod apply;
+mod autostart;
+mod backup;
+mod bootstrap;
+mod compare;
+mod completions;
+mod doctor;
+mod dsc;
+mod edit;
+mod exit;
+mod explain;
+mod export;
+mod fleet;
+mod history;
+mod hooks;
+mod import;
+mod init;
+mod http;
+mod interactive;
+mod journal;
+mod list;
+mod migrate;
+mod notify;
+mod output;
+mod pack;
+mod porcelain;
+mod profile;
+mod purge;
+mod powershell;
 mod registry;
+mod sandbox;
+mod schedule;
+mod search;
+mod service;
+mod state;
+mod status;
+mod tui;
+mod update;
+mod watch;
+mod webhook;
 
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{bail, Context, Result};
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use tracing::{warn, Level};
+
+use edge_profile_core::{catalog, config, diff, edge, policy, validate};
+use exit::Status;
+use registry::{Backend, Registry, Scope};
 
 #[derive(Parser)]
-#[command(name = "edge-profile", about = "Portable Edge settings via HKCU registry policies")]
+#[command(
+    name = "edge-profile",
+    about = "Portable Edge settings via HKCU registry policies",
+    after_help = exit::HELP
+)]
 struct Cli {
     #[command(subcommand)]
     command: Command,
+
+    /// Log more (-v info, -vv debug, -vvv trace)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only log errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Write logs to this file instead of stderr
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Disable colored output (also honors NO_COLOR)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Answer yes to every confirmation prompt
+    #[arg(
+        short,
+        long,
+        visible_alias = "non-interactive",
+        global = true,
+        env = "EDGE_PROFILE_YES"
+    )]
+    yes: bool,
 }
 
 #[derive(Subcommand)]
@@ -22,57 +97,1135 @@ enum Command {
         #[arg(long)]
         dry_run: bool,
 
+        /// Skip writing when the registry already matches the config
+        #[arg(long, conflicts_with = "dry_run")]
+        if_changed: bool,
+
+        /// Raise a toast when drift is corrected or HKLM overrides the config
+        #[arg(long, conflicts_with = "dry_run")]
+        notify: bool,
+
+        /// Print the written changes in a stable, tab-separated format
+        #[arg(long, conflicts_with = "dry_run")]
+        porcelain: bool,
+
+        /// With --dry-run, describe each policy and link to its documentation
+        #[arg(long, requires = "dry_run")]
+        explain: bool,
+
+        /// Choose which pending changes to write, one at a time
+        #[arg(long, conflicts_with_all = ["dry_run", "porcelain"])]
+        interactive: bool,
+
+        /// Compare and write even if nothing has changed since the last apply
+        #[arg(long, conflicts_with = "dry_run")]
+        force: bool,
+
+        /// Apply only these config sections, e.g. privacy,extensions
+        #[arg(long, value_enum, value_delimiter = ',', conflicts_with_all = ["except", "watch"])]
+        only: Vec<config::Section>,
+
+        /// Apply every config section except these
+        #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "watch")]
+        except: Vec<config::Section>,
+
+        /// Keep running and re-apply whenever the config file is saved
+        #[arg(long, conflicts_with_all = ["dry_run", "if_changed", "porcelain", "interactive"])]
+        watch: bool,
+
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Write deprecated policies under their replacement's name
+        #[arg(long)]
+        replace_deprecated: bool,
+
+        /// Hive to write policies to
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Compare the registry with the config without writing anything
+    Verify {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Print differences in a stable, tab-separated format
+        #[arg(long)]
+        porcelain: bool,
+
+        /// Hive to compare against
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Summarize drift, managed values and HKLM overlaps
+    Status {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Hive to check
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Show how the registry differs from the config, with old and new values
+    Diff {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Print differences in a stable, tab-separated format
+        #[arg(long)]
+        porcelain: bool,
+
+        /// Hive to compare against
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Show what switching from one config to another would change
+    Compare {
+        /// Config currently applied
+        from: PathBuf,
+
+        /// Config to switch to
+        to: PathBuf,
+    },
+    /// Browse and edit policies interactively, and apply or clean from there
+    Tui {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Hive to edit
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Download a config, apply it and enable autostart on a new machine
+    Bootstrap {
+        /// URL of the config to install
+        url: String,
+
+        /// Where to save the config (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Hive to write policies to
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Check the config against the policy catalog without writing anything
+    Validate {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Hive the config is meant for
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Open the config in $EDITOR and validate it when the editor exits
+    Edit {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Hive to validate against and apply to
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Rename deprecated policies in the config to their replacements
+    MigrateConfig {
+        /// Show the renames without rewriting the file
+        #[arg(long)]
+        dry_run: bool,
+
         /// Path to config file (default: ~/.edge-profile/config.toml)
         #[arg(long)]
         config: Option<PathBuf>,
     },
-    /// Print current HKCU Edge policies
-    Dump,
+    /// Print the Edge policies currently in the registry
+    Dump {
+        /// Hive to read
+        #[arg(long, value_enum, default_value_t)]
+        hive: registry::DumpHive,
+
+        /// Same as --hive both
+        #[arg(long, conflicts_with = "hive")]
+        compare_hklm: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: registry::DumpFormat,
+
+        /// Only policies whose name matches this pattern, e.g. 'DefaultSearch*'
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Only policies from these config sections
+        #[arg(long, value_enum, value_delimiter = ',')]
+        section: Vec<config::Section>,
+    },
     /// Remove only the policies this tool manages
     Clean {
-        /// Skip confirmation prompt
-        #[arg(short)]
-        y: bool,
+        /// Hive to clean
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+
+        /// Delete the entire Edge policy key, managed or not, after backing it up
+        #[arg(long)]
+        all: bool,
+
+        /// Only clean these config sections, e.g. extensions
+        #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "all")]
+        only: Vec<config::Section>,
+
+        /// List what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check the config, registry access, overriding policies and the tool's
+    /// own setup, with a fix for each problem found
+    Doctor {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Hive the config is applied to
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Save every value under the Edge policy key to a timestamped file
+    Backup {
+        /// Hive to back up
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Replace the Edge policy key with the contents of a backup
+    Restore {
+        /// Backup file written by `backup` or `clean --all`
+        file: PathBuf,
+
+        /// Hive to restore to (default: the one the backup was taken from)
+        #[arg(long, value_enum)]
+        scope: Option<Scope>,
+    },
+    /// Put the registry back the way an earlier run left it
+    Rollback {
+        /// Run number from `history`, or a backup file
+        #[arg(long)]
+        to: String,
+    },
+    /// Put back the values the last apply added, changed or removed
+    Undo {
+        /// Hive whose last apply to undo
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Show when apply and clean last ran and what they changed
+    History {
+        /// How many runs to show, newest last
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// Only runs against this hive
+        #[arg(long, value_enum)]
+        scope: Option<Scope>,
+    },
+    /// Remove managed policies, autostart, the service and the tool's files
+    Purge {
+        /// List what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also delete ~/.edge-profile/config.toml and the profiles
+        #[arg(long)]
+        include_config: bool,
+    },
+    /// Download and install the latest edge-profile release
+    SelfUpdate {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+    /// Switch between named configs in ~/.edge-profile/profiles
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Run `apply --if-changed` at every sign-in
+    Autostart {
+        #[command(subcommand)]
+        action: AutostartAction,
+    },
+    /// Re-apply at sign-in and on an interval from a scheduled task
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    /// Enforce the config from a background Windows service
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+    /// Write a Windows Sandbox file that applies the config and opens Edge
+    Sandbox {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Where to write the .wsb file
+        #[arg(short, long, default_value = "edge-profile.wsb")]
+        output: PathBuf,
+
+        /// Open the sandbox after writing the file
+        #[arg(long)]
+        launch: bool,
+    },
+    /// Describe a policy: its values, Edge versions and config key
+    Explain {
+        /// Policy name or config key, e.g. TrackingPrevention or privacy.tracking_prevention
+        #[arg(value_parser = completions::PolicyName { config_keys: true })]
+        #[arg(hide_possible_values = true)]
+        name: String,
+    },
+    /// Find policies by keyword and show how to set them
+    Search {
+        /// Words to look for in policy names and descriptions
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// Print a shell completion script
+    Completions {
+        /// Shell to generate for
+        #[arg(value_enum)]
+        shell: completions::Shell,
+    },
+    /// Write a single policy without touching the config
+    Set {
+        /// Policy name, e.g. ShowHomeButton
+        #[arg(value_parser = completions::PolicyName { config_keys: false })]
+        #[arg(hide_possible_values = true)]
+        name: String,
+
+        /// Value, typed from the catalog: 1/0 for booleans, a number or value
+        /// name for integers, one argument per item for lists
+        #[arg(required = true)]
+        values: Vec<String>,
+
+        /// Hive to write to
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Show a single policy's value in the registry
+    Get {
+        /// Policy name, e.g. TrackingPrevention
+        #[arg(value_parser = completions::PolicyName { config_keys: false })]
+        #[arg(hide_possible_values = true)]
+        name: String,
+
+        /// Hive to read from
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Delete a single policy value, list, or list item from the registry
+    Remove {
+        /// Policy name, or `Subkey\N` for one item of a list policy
+        #[arg(value_parser = completions::PolicyName { config_keys: false })]
+        #[arg(hide_possible_values = true)]
+        name: String,
+
+        /// Hive to delete from
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// List every config key with its type and the policy it writes
+    ListPolicies {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Include every catalog policy that can be set under [raw]
+        #[arg(long)]
+        all: bool,
+    },
+    /// Write a starter config by answering a few questions
+    Init {
+        /// Where to write the config (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Generate a config from the Edge (or Chrome) policies already in the registry
+    Import {
+        /// Where to write the config (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Browser whose policies to read; Chrome's are translated to Edge's
+        #[arg(long, value_enum, default_value_t)]
+        from: import::Browser,
+
+        /// Hive to read
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Write the policies the config produces in another format
+    Export {
+        /// Output format
+        #[arg(long, value_enum)]
+        format: export::Format,
+
+        /// Where to write the export (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Hive the exported policies target
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Bundle this executable and the config into one double-clickable file
+    Pack {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Where to write the bundle
+        #[arg(short, long, default_value = "edge-setup.exe")]
+        output: PathBuf,
+    },
+    /// Apply the config on other machines
+    Fleet {
+        #[command(subcommand)]
+        action: FleetAction,
+    },
+    /// DSC v3 resource operations (JSON on stdin and stdout)
+    Dsc {
+        #[command(subcommand)]
+        action: DscAction,
     },
 }
 
-fn main() -> Result<()> {
+#[derive(Subcommand)]
+enum ProfileAction {
+    /// List profiles, marking the one in use
+    List,
+    /// Apply a profile and use it as the default config from now on
+    Use {
+        name: String,
+
+        /// Hive to write policies to
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Apply a profile once without switching to it
+    Apply {
+        name: String,
+
+        /// Hive to write policies to
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+}
+
+#[derive(Subcommand)]
+enum AutostartAction {
+    /// Register under the HKCU Run key
+    Enable {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Remove the Run key entry
+    Disable,
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction {
+    /// Register the task, replacing any existing one
+    Install {
+        /// How often to re-apply, e.g. 30m, 1h, 1d
+        #[arg(long, default_value = "1h", value_parser = schedule::parse_interval)]
+        every: u32,
+
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Hive to apply to
+        #[arg(long, value_enum, default_value_t)]
+        scope: Scope,
+    },
+    /// Delete the task
+    Remove,
+    /// Show the task's next and last run
+    Status,
+}
+
+#[derive(Subcommand)]
+enum ServiceAction {
+    /// Register and start the service (requires elevation)
+    Install {
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Stop and remove the service
+    Uninstall,
+    /// Service entry point, invoked by the service control manager
+    #[command(hide = true)]
+    Run {
+        #[arg(long)]
+        config: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum FleetAction {
+    /// Push the config to each host over SSH or WinRM, apply and verify it
+    Apply {
+        /// TOML file with a [[host]] table per machine
+        #[arg(long)]
+        hosts: PathBuf,
+
+        /// Path to config file (default: ~/.edge-profile/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DscAction {
+    /// Report the current state
+    Get,
+    /// Apply the config
+    Set,
+    /// Report whether the registry matches the config
+    Test,
+    /// Print the JSON schema of the resource properties
+    Schema,
+}
+
+fn main() -> ExitCode {
+    // A packed copy launched without arguments (double-clicked) applies its
+    // embedded config.
+    if std::env::args_os().len() == 1 {
+        if let Some(text) = pack::embedded_config() {
+            let _ = init_logging(Level::WARN, None);
+            return match pack::run_embedded(&text) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => failure_code(&e),
+            };
+        }
+    }
+
     let cli = Cli::parse();
+    output::init(cli.no_color);
+    let level = match (cli.quiet, cli.verbose) {
+        (true, _) => Level::ERROR,
+        (false, 0) => Level::WARN,
+        (false, 1) => Level::INFO,
+        (false, 2) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    };
+    if let Err(e) = init_logging(level, cli.log_file.as_deref()) {
+        eprintln!("Error: {e:?}");
+        return ExitCode::FAILURE;
+    }
+    match run(cli) {
+        Ok(status) => ExitCode::from(status.code()),
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            failure_code(&e)
+        }
+    }
+}
+
+fn failure_code(e: &anyhow::Error) -> ExitCode {
+    let status = e
+        .downcast_ref::<Status>()
+        .copied()
+        .or_else(|| e.downcast_ref::<edge_profile_core::Error>().and_then(Status::of));
+    ExitCode::from(status.map_or(1, |s| s.code()))
+}
+
+fn run(cli: Cli) -> Result<Status> {
+    let mut status = Status::Ok;
+    let yes = cli.yes;
 
     match cli.command {
-        Command::Apply { dry_run, config } => {
-            let cfg = config::load(config.as_deref())?;
-            let entries = policy::build_entries(&cfg);
+        Command::Apply {
+            dry_run,
+            if_changed,
+            notify,
+            porcelain,
+            explain,
+            interactive,
+            force,
+            only,
+            except,
+            watch,
+            config,
+            replace_deprecated,
+            scope,
+        } => {
+            if watch {
+                watch::run(config.as_deref(), scope, replace_deprecated)?;
+                return Ok(Status::Ok);
+            }
+
+            // Fast path: same config as the last apply and an untouched key.
+            let partial = !only.is_empty() || !except.is_empty();
+            let path = config::resolve_path(config.as_deref()).ok();
+            let config_key = path
+                .as_deref()
+                .and_then(|path| state::config_key(path, replace_deprecated).ok())
+                .filter(|_| !dry_run && !interactive && !partial);
+            if let Some(ref key) = config_key {
+                if !force && state::is_current(scope, key) {
+                    history::record("apply", scope, path.as_deref(), &[]);
+                    if !porcelain {
+                        println!("{scope} policies already match the config.");
+                    }
+                    return Ok(Status::Ok);
+                }
+            }
+
+            let (cfg, mut entries) = prepare(config.as_deref(), scope, replace_deprecated)?;
+            let current = Registry.read_current(scope)?;
+            if partial {
+                select_sections(&mut entries, &current, &only, &except);
+            }
 
             if dry_run {
-                println!("Dry run — the following policies would be written:\n");
-                for entry in &entries {
-                    println!("  {}", entry);
+                print_dry_run(&entries, &current, explain);
+            } else {
+                let changes = diff::compare(&entries, &current);
+                if if_changed && changes.is_empty() {
+                    if let Some(key) = config_key {
+                        state::record(scope, key);
+                    }
+                    history::record("apply", scope, path.as_deref(), &[]);
+                    if !porcelain {
+                        println!("{scope} policies already match the config.");
+                    }
+                    return Ok(Status::Ok);
                 }
-                println!("\n({} values total)", entries.len());
+                let (changes, skipped) = if interactive {
+                    interactive::select(changes)?
+                } else {
+                    (changes, Vec::new())
+                };
+                if interactive && changes.is_empty() {
+                    println!("Nothing applied.");
+                    return Ok(Status::Ok);
+                }
+                let output = if porcelain {
+                    apply::Output::Porcelain
+                } else {
+                    apply::Output::Changes
+                };
+                let overridden = apply::Apply {
+                    command: "apply",
+                    cfg: &cfg,
+                    entries: &entries,
+                    scope,
+                    config: path.as_deref(),
+                    config_key,
+                    interactive,
+                    output,
+                }
+                .run(&changes, &skipped)?;
+                if !overridden.is_empty() {
+                    status = Status::Conflict;
+                    if notify {
+                        toast(
+                            "Edge policies overridden",
+                            &format!("HKLM takes precedence for: {}", overridden.join(", ")),
+                        );
+                    }
+                }
+                if notify && if_changed {
+                    toast(
+                        "Edge policies restored",
+                        &format!("{} value(s) had drifted from the config.", changes.len()),
+                    );
+                }
+            }
+        }
+        Command::Verify {
+            config,
+            porcelain,
+            scope,
+        } => {
+            let (cfg, entries) = prepare(config.as_deref(), scope, false)?;
+            let current = Registry.read_current(scope)?;
+            let changes = diff::compare(&entries, &current);
+            let overridden = match scope {
+                Scope::User => registry::check_hklm_conflicts(&entries),
+                Scope::Machine => Vec::new(),
+            };
+            if porcelain {
+                print_porcelain(&changes, &overridden);
+            } else {
+                for change in &changes {
+                    anstream::println!("  {}", output::change(change));
+                }
+            }
+            if !overridden.is_empty() {
+                status = Status::Conflict;
+            } else if !changes.is_empty() {
+                if !porcelain {
+                    println!("{} value(s) differ from the config.", changes.len());
+                }
+                status = Status::Drift;
+            } else if !porcelain {
+                println!("{scope} policies match the config.");
+            }
+            report(&cfg, "verify", scope, config.as_deref(), &changes, &overridden)?;
+        }
+        Command::Status { config, scope } => {
+            status = status::run(config.as_deref(), scope)?;
+        }
+        Command::Diff {
+            config,
+            porcelain,
+            scope,
+        } => {
+            let (_, entries) = prepare(config.as_deref(), scope, false)?;
+            let current = Registry.read_current(scope)?;
+            let changes = diff::compare(&entries, &current);
+            if porcelain {
+                print_porcelain(&changes, &[]);
+            } else if changes.is_empty() {
+                println!("{scope} policies match the config.");
             } else {
-                registry::check_hklm_conflicts(&entries);
-                registry::apply(&entries)?;
-                println!("Applied {} policy values.", entries.len());
-            }
-        }
-        Command::Dump => {
-            registry::dump()?;
-        }
-        Command::Clean { y } => {
-            if !y {
-                eprint!("Remove all edge-profile managed policies? [y/N] ");
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-                if !input.trim().eq_ignore_ascii_case("y") {
-                    println!("Aborted.");
-                    return Ok(());
+                for change in &changes {
+                    anstream::println!("  {}", output::change(change));
                 }
+                println!("\n{}", diff::Summary::new(&entries, &changes, 0));
             }
-            registry::clean()?;
+        }
+        Command::Compare { from, to } => {
+            compare::run(&from, &to)?;
+        }
+        Command::Tui { config, scope } => {
+            tui::run(config.as_deref(), scope).context(Status::Registry)?;
+        }
+        Command::Bootstrap {
+            url,
+            config,
+            scope,
+        } => {
+            let path = config::resolve_path(config.as_deref())?;
+            if !bootstrap::run(&url, &path, scope, yes)? {
+                println!("Aborted.");
+            }
+        }
+        Command::Validate { config, scope } => {
+            let cfg = config::load(config.as_deref())?;
+            let entries = policy::build_entries(&cfg);
+            let issues = check_config(&cfg, &entries, scope);
+            report_issues(&issues)?;
+            println!("Config OK ({} values, {} warnings).", entries.len(), issues.len());
+        }
+        Command::Edit { config, scope } => {
+            status = edit::run(config.as_deref(), scope, yes)?;
+        }
+        Command::MigrateConfig { dry_run, config } => {
+            let path = config::resolve_path(config.as_deref())?;
+            migrate::migrate_config(&path, dry_run)?;
+        }
+        Command::Dump {
+            hive,
+            compare_hklm,
+            format,
+            filter,
+            section,
+        } => {
+            let filter = registry::DumpFilter {
+                glob: filter,
+                sections: section,
+            };
+            let hive = if compare_hklm {
+                registry::DumpHive::Both
+            } else {
+                hive
+            };
+            match (format, hive) {
+                (registry::DumpFormat::Json, _) => registry::dump_json(&filter, hive),
+                (_, registry::DumpHive::Both) => registry::compare_hklm(&filter),
+                (_, registry::DumpHive::Hkcu) => registry::dump(&filter, Scope::User),
+                (_, registry::DumpHive::Hklm) => registry::dump(&filter, Scope::Machine),
+            }
+            .context(Status::Registry)?;
+        }
+        Command::Clean {
+            scope,
+            all: true,
+            dry_run,
+            ..
+        } => {
+            // The backup has to hold everything the delete removes, not just
+            // the policies edge-profile reads.
+            let current = registry::read_tree(scope).context(Status::Registry)?;
+            if dry_run {
+                for value in &current.values {
+                    println!("  Would remove {} = {value}", value.path());
+                }
+                println!("{} value(s) would be removed.", current.values.len());
+                return Ok(Status::Ok);
+            }
+            let prompt = format!(
+                "Delete {scope}\\{} and all {} value(s) under it, \
+                 including policies edge-profile doesn't manage?",
+                registry::EDGE_POLICY_PATH,
+                current.values.len()
+            );
+            if !confirm(&prompt, yes)? {
+                println!("Aborted.");
+                return Ok(Status::Ok);
+            }
+            let (backup, count) = backup::snapshot(scope)
+                .context("Not deleting anything without a backup")?;
+            println!("Backed up {count} value(s) to {}", backup.display());
+            if registry::clean_all(scope).context(Status::Registry)? {
+                history::record_clean(scope, count);
+                println!("Removed all {scope} Edge policies.");
+            } else {
+                println!("No {scope} Edge policies found.");
+            }
+        }
+        Command::Doctor { config, scope } => {
+            status = doctor::run(config.as_deref(), scope)?;
+        }
+        Command::Backup { scope } => {
+            let (path, count) = backup::snapshot(scope)?;
+            println!("Backed up {count} value(s) to {}", path.display());
+        }
+        Command::Restore { file, scope } => {
+            if !backup::restore(&file, scope, yes)? {
+                println!("Aborted.");
+            }
+        }
+        Command::Rollback { to } => {
+            let path = match to.parse() {
+                Ok(id) => history::snapshot(id)?,
+                Err(_) => PathBuf::from(to),
+            };
+            if !backup::restore(&path, None, yes)? {
+                println!("Aborted.");
+            }
+        }
+        Command::Undo { scope } => {
+            if !journal::undo(scope, yes)? {
+                println!("Aborted.");
+            }
+        }
+        Command::Clean {
+            scope,
+            all: false,
+            only,
+            dry_run,
+        } => {
+            // Always list first, so the prompt says what it will delete.
+            let count = registry::clean(scope, &only, true).context(Status::Registry)?;
+            if count == 0 {
+                println!("No managed {scope} policies to clean.");
+                return Ok(Status::Ok);
+            }
+            if dry_run {
+                return Ok(Status::Ok);
+            }
+            let prompt = format!("Remove these {count} managed {scope} policies?");
+            if !confirm(&prompt, yes)? {
+                println!("Aborted.");
+                return Ok(Status::Ok);
+            }
+            let removed = registry::clean(scope, &only, false).context(Status::Registry)?;
+            history::record_clean(scope, removed);
             println!("Cleaned managed policies.");
         }
+        Command::History { limit, scope } => history::show(limit, scope)?,
+        Command::Purge {
+            dry_run,
+            include_config,
+        } => {
+            if !purge::run(include_config, dry_run, yes)? {
+                println!("Aborted.");
+            }
+        }
+        Command::SelfUpdate { check } => {
+            if !update::run(check, yes)? {
+                println!("Aborted.");
+            }
+        }
+        Command::Profile { action } => match action {
+            ProfileAction::List => profile::list()?,
+            ProfileAction::Use { name, scope } => profile::switch(&name, scope)?,
+            ProfileAction::Apply { name, scope } => profile::apply(&name, scope)?,
+        },
+        Command::Autostart { action } => match action {
+            AutostartAction::Enable { config } => {
+                autostart::enable(&config::resolve_path(config.as_deref())?)?;
+            }
+            AutostartAction::Disable => autostart::disable()?,
+        },
+        Command::Schedule { action } => match action {
+            ScheduleAction::Install {
+                every,
+                config,
+                scope,
+            } => {
+                schedule::install(&config::resolve_path(config.as_deref())?, every, scope)?;
+            }
+            ScheduleAction::Remove => schedule::remove()?,
+            ScheduleAction::Status => schedule::status()?,
+        },
+        Command::Service { action } => match action {
+            ServiceAction::Install { config } => {
+                service::install(&config::resolve_path(config.as_deref())?)?;
+            }
+            ServiceAction::Uninstall => service::uninstall()?,
+            ServiceAction::Run { config } => service::run(config)?,
+        },
+        Command::Sandbox {
+            config,
+            output,
+            launch,
+        } => {
+            sandbox::generate(&config::resolve_path(config.as_deref())?, &output, launch)?;
+        }
+        Command::Explain { name } => {
+            explain::explain(&name)?;
+        }
+        Command::Search { words } => search::search(&words),
+        Command::Completions { shell } => completions::print(shell, Cli::command()),
+        Command::Set {
+            name,
+            values,
+            scope,
+        } => adhoc::set(&name, &values, scope)?,
+        Command::Get { name, scope } => adhoc::get(&name, scope)?,
+        Command::Remove { name, scope } => adhoc::remove(&name, scope)?,
+        Command::ListPolicies { config, all } => {
+            list::list(config.as_deref(), all)?;
+        }
+        Command::Init { config } => {
+            init::run(&config::resolve_path(config.as_deref())?, yes)?;
+        }
+        Command::Import {
+            output,
+            from,
+            scope,
+        } => {
+            import::import(scope, from, output.as_deref())?;
+        }
+        Command::Export {
+            format,
+            output,
+            config,
+            scope,
+        } => {
+            let (_, entries) = prepare(config.as_deref(), scope, false)?;
+            export::export(&entries, scope, format, output.as_deref())?;
+        }
+        Command::Pack { config, output } => {
+            pack::pack(&config::resolve_path(config.as_deref())?, &output)?;
+        }
+        Command::Fleet { action } => match action {
+            FleetAction::Apply { hosts, config } => {
+                status = fleet::apply(&hosts, &config::resolve_path(config.as_deref())?)?;
+            }
+        },
+        Command::Dsc { action } => match action {
+            DscAction::Get => dsc::get()?,
+            DscAction::Set => dsc::set()?,
+            DscAction::Test => dsc::test()?,
+            DscAction::Schema => dsc::schema()?,
+        },
     }
 
+    Ok(status)
+}
+
+/// Ask a yes/no question on stderr. `--yes` answers it without reading
+/// stdin, and a non-terminal stdin is an error rather than a hang.
+fn confirm(prompt: &str, yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if !std::io::stdin().is_terminal() {
+        bail!("{prompt} Pass --yes (or set EDGE_PROFILE_YES=1) to confirm non-interactively");
+    }
+    eprint!("{prompt} [y/N] ");
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
+
+fn print_porcelain(changes: &[diff::Change], conflicts: &[&str]) {
+    for change in changes {
+        println!("{}", porcelain::change(change));
+    }
+    for name in conflicts {
+        println!("{}", porcelain::conflict(name));
+    }
+}
+
+/// Send the summary to `[report] webhook`, if one is configured.
+fn report(
+    cfg: &config::Config,
+    command: &str,
+    scope: Scope,
+    config: Option<&Path>,
+    changes: &[diff::Change],
+    conflicts: &[&str],
+) -> Result<()> {
+    if let Some(url) = cfg.report.as_ref().and_then(|r| r.webhook.as_deref()) {
+        let path = config::resolve_path(config)?;
+        webhook::send(url, command, scope, &path, changes, conflicts);
+    }
     Ok(())
 }
+
+fn init_logging(level: Level, log_file: Option<&Path>) -> Result<()> {
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false);
+
+    match log_file {
+        Some(path) => {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open log file: {}", path.display()))?;
+            builder
+                .with_ansi(false)
+                .with_writer(std::sync::Mutex::new(file))
+                .init();
+        }
+        None => builder
+            .with_ansi(output::stderr_colored())
+            .without_time()
+            .with_writer(std::io::stderr)
+            .init(),
+    }
+    Ok(())
+}
+
+/// List what apply would write, marking additions (`+`) and changes (`~`,
+/// with the old value) against `current`, then the list items it would prune.
+fn print_dry_run(
+    entries: &[policy::PolicyEntry],
+    current: &[policy::PolicyEntry],
+    explain: bool,
+) {
+    println!("Dry run — the following policies would be written:\n");
+    let changes = diff::compare(entries, current);
+    let mut explained = Vec::new();
+    for entry in entries {
+        match changes.iter().find(|c| c.entry() == entry) {
+            Some(change) => anstream::println!("  {}", output::change(change)),
+            None => println!("    {entry}"),
+        }
+        let name = entry.policy_name();
+        if explain && !explained.contains(&name) {
+            explained.push(name);
+            match catalog::lookup(name) {
+                Some(def) => {
+                    println!("      {}", def.summary);
+                    anstream::println!("      {}", output::paint(output::NOTE, def.docs_url()));
+                }
+                None => {
+                    let hint = format!("Not in the policy catalog{}", catalog::did_you_mean(name));
+                    anstream::println!("      {}", output::paint(output::WARNING, hint));
+                }
+            }
+        }
+    }
+    for change in changes
+        .iter()
+        .filter(|c| matches!(c, diff::Change::Removed(_)))
+    {
+        anstream::println!("  {}", output::change(change));
+    }
+    println!("\n{}", diff::Summary::new(entries, &changes, 0));
+}
+
+/// Load the config and build its entries, failing on validation errors.
+fn prepare(
+    config: Option<&Path>,
+    scope: Scope,
+    replace_deprecated: bool,
+) -> Result<(config::Config, Vec<policy::PolicyEntry>)> {
+    let cfg = config::load(config)?;
+    let mut entries = policy::build_entries(&cfg);
+    if replace_deprecated {
+        for (old, new) in policy::replace_deprecated(&mut entries) {
+            warn!("Replacing deprecated {old} with {new}");
+        }
+    }
+    report_issues(&check_config(&cfg, &entries, scope))?;
+    Ok((cfg, entries))
+}
+
+/// Limit `entries` to the selected config sections. Apply rewrites every
+/// list subkey it manages, so lists from the other sections keep their
+/// current items rather than being emptied.
+fn select_sections(
+    entries: &mut Vec<policy::PolicyEntry>,
+    current: &[policy::PolicyEntry],
+    only: &[config::Section],
+    except: &[config::Section],
+) {
+    let selected = |e: &policy::PolicyEntry| {
+        let section = config::Section::of(e.policy_name());
+        (only.is_empty() || only.contains(&section)) && !except.contains(&section)
+    };
+    entries.retain(selected);
+    entries.extend(
+        current
+            .iter()
+            .filter(|e| !e.subkey.is_empty() && !selected(e))
+            .cloned(),
+    );
+}
+
+/// Catalog validation plus support-range and scope checks against the
+/// installed Edge.
+fn check_config(
+    cfg: &config::Config,
+    entries: &[policy::PolicyEntry],
+    scope: Scope,
+) -> Vec<validate::Issue> {
+    let mut issues = validate::check(cfg, entries);
+    issues.extend(validate::check_scope(entries, scope));
+    if let Some(version) = edge::installed_version() {
+        issues.extend(validate::check_support(entries, version));
+    }
+    issues
+}
+
+/// Print issues to stderr, failing if any of them is an error.
+fn report_issues(issues: &[validate::Issue]) -> Result<()> {
+    for issue in issues {
+        let style = match issue.severity {
+            validate::Severity::Error => output::ERROR,
+            validate::Severity::Warning => output::WARNING,
+        };
+        anstream::eprintln!("{}", output::paint(style, issue));
+    }
+    Ok(validate::ensure_valid(issues)?)
+}
+
+/// Notifications are best-effort; a failed toast shouldn't fail the apply.
+fn toast(title: &str, body: &str) {
+    if let Err(e) = notify::toast(title, body) {
+        warn!("{e:#}");
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use toml_edit::{DocumentMut, Key};
+
+use crate::catalog;
+
+/// Rename deprecated policies in the config's `[raw]` section to their
+/// replacements, preserving comments and formatting.
+pub fn migrate_config(path: &Path, dry_run: bool) -> Result<()> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config: {}", path.display()))?;
+    let mut doc: DocumentMut = text
+        .parse()
+        .with_context(|| format!("Failed to parse config: {}", path.display()))?;
+
+    let Some(raw) = doc.get_mut("raw").and_then(|t| t.as_table_mut()) else {
+        println!("No [raw] section; nothing to migrate.");
+        return Ok(());
+    };
+
+    let mut renamed = 0;
+    let mut items = Vec::new();
+    let names: Vec<String> = raw.iter().map(|(k, _)| k.to_owned()).collect();
+    for name in &names {
+        let key = raw.key(name).cloned().unwrap_or_else(|| Key::new(name));
+        let item = raw.remove(name).expect("key listed above");
+        items.push((key, item));
+    }
+
+    for (key, item) in items {
+        let def = catalog::lookup(key.get());
+        let new = def.and_then(|d| d.drop_in_replacement());
+        let key = match (def.and_then(|d| d.replaced_by), new) {
+            (Some(_), Some(new)) if names.iter().any(|n| n == new.name) => {
+                eprintln!(
+                    "  {}: {} is already set, leaving the deprecated key in place",
+                    key.get(),
+                    new.name
+                );
+                key
+            }
+            (Some(_), Some(new)) => {
+                println!("  {} -> {}", key.get(), new.name);
+                renamed += 1;
+                Key::new(new.name).with_leaf_decor(key.leaf_decor().clone())
+            }
+            (Some(new), None) => {
+                eprintln!(
+                    "  {}: replaced by {new}, which takes a different value type; migrate by hand",
+                    key.get()
+                );
+                key
+            }
+            (None, _) => key,
+        };
+        raw.insert_formatted(&key, item);
+    }
+
+    if renamed == 0 {
+        println!("No deprecated policies to migrate.");
+    } else if dry_run {
+        println!("\n({renamed} policies would be renamed)");
+    } else {
+        std::fs::write(path, doc.to_string())
+            .with_context(|| format!("Failed to write config: {}", path.display()))?;
+        println!("Migrated {renamed} policies in {}.", path.display());
+    }
+
+    Ok(())
+}
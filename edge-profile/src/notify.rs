@@ -0,0 +1,123 @@
+//! Windows toast notifications, shown through PowerShell's WinRT bindings so
+//! no app registration is needed.
+
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+use windows_sys::Win32::System::Environment::{CreateEnvironmentBlock, DestroyEnvironmentBlock};
+use windows_sys::Win32::System::RemoteDesktop::{WTSGetActiveConsoleSessionId, WTSQueryUserToken};
+use windows_sys::Win32::System::Threading::{
+    CreateProcessAsUserW, CREATE_NO_WINDOW, CREATE_UNICODE_ENVIRONMENT, PROCESS_INFORMATION,
+    STARTUPINFOW,
+};
+
+use crate::powershell;
+
+/// PowerShell's AppUserModelID; toasts appear under "Windows PowerShell".
+const APP_ID: &str =
+    r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+
+/// Show a toast to the current user.
+pub fn toast(title: &str, body: &str) -> Result<()> {
+    Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-EncodedCommand"])
+        .arg(encoded_script(title, body))
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .context("Failed to launch PowerShell for toast notification")?;
+    Ok(())
+}
+
+/// Show a toast in the interactive console session. Services run in session
+/// 0, which has no desktop, so the PowerShell process is started with the
+/// signed-in user's token instead.
+pub fn toast_active_user(title: &str, body: &str) -> Result<()> {
+    let mut command_line: Vec<u16> = format!(
+        "powershell.exe -NoProfile -NonInteractive -EncodedCommand {}",
+        encoded_script(title, body)
+    )
+    .encode_utf16()
+    .chain(std::iter::once(0))
+    .collect();
+    let mut desktop: Vec<u16> = "winsta0\\default"
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+
+    // SAFETY: every out-pointer refers to a live local, the command line and
+    // desktop buffers are NUL-terminated and mutable as CreateProcessAsUserW
+    // requires, and every handle/block obtained here is released below.
+    unsafe {
+        let session = WTSGetActiveConsoleSessionId();
+        if session == u32::MAX {
+            bail!("No interactive session to notify");
+        }
+        let mut token: HANDLE = std::ptr::null_mut();
+        if WTSQueryUserToken(session, &mut token) == 0 {
+            return Err(std::io::Error::last_os_error()).context("No signed-in user to notify");
+        }
+
+        let mut env = std::ptr::null_mut();
+        if CreateEnvironmentBlock(&mut env, token, 0) == 0 {
+            env = std::ptr::null_mut();
+        }
+
+        let mut startup: STARTUPINFOW = std::mem::zeroed();
+        startup.cb = std::mem::size_of::<STARTUPINFOW>() as u32;
+        startup.lpDesktop = desktop.as_mut_ptr();
+        let mut process: PROCESS_INFORMATION = std::mem::zeroed();
+
+        let ok = CreateProcessAsUserW(
+            token,
+            std::ptr::null(),
+            command_line.as_mut_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            0,
+            CREATE_NO_WINDOW | CREATE_UNICODE_ENVIRONMENT,
+            env,
+            std::ptr::null(),
+            &startup,
+            &mut process,
+        );
+        let err = std::io::Error::last_os_error();
+
+        if !env.is_null() {
+            DestroyEnvironmentBlock(env);
+        }
+        CloseHandle(token);
+        if ok == 0 {
+            return Err(err).context("Failed to launch toast in user session");
+        }
+        CloseHandle(process.hProcess);
+        CloseHandle(process.hThread);
+    }
+    Ok(())
+}
+
+fn encoded_script(title: &str, body: &str) -> String {
+    let xml = format!(
+        "<toast><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual></toast>",
+        xml_escape(title),
+        xml_escape(body)
+    );
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null\n\
+         [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] | Out-Null\n\
+         $xml = New-Object Windows.Data.Xml.Dom.XmlDocument\n\
+         $xml.LoadXml('{}')\n\
+         $toast = [Windows.UI.Notifications.ToastNotification]::new($xml)\n\
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{APP_ID}').Show($toast)\n",
+        xml.replace('\'', "''")
+    );
+    powershell::encode(&script)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
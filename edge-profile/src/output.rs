@@ -0,0 +1,45 @@
+//! Terminal styling shared by the human-readable output. Styled text must be
+//! printed with `anstream::println!`/`eprintln!`, which strip the escapes
+//! when the stream isn't a terminal, `NO_COLOR` is set or `--no-color` was
+//! given.
+
+use std::fmt::Display;
+
+use anstream::ColorChoice;
+use anstyle::{AnsiColor, Style};
+
+use crate::diff::Change;
+
+pub const ADDED: Style = AnsiColor::Green.on_default();
+pub const CHANGED: Style = AnsiColor::Yellow.on_default();
+pub const REMOVED: Style = AnsiColor::Red.on_default();
+pub const WARNING: Style = AnsiColor::Yellow.on_default().bold();
+pub const ERROR: Style = AnsiColor::Red.on_default().bold();
+pub const HEADER: Style = Style::new().bold();
+pub const NOTE: Style = Style::new().dimmed();
+
+/// Apply `--no-color` for the rest of the process.
+pub fn init(no_color: bool) {
+    if no_color {
+        ColorChoice::Never.write_global();
+    }
+}
+
+/// Whether stderr gets colors, for writers that don't go through anstream.
+pub fn stderr_colored() -> bool {
+    anstream::AutoStream::choice(&std::io::stderr()) != ColorChoice::Never
+}
+
+pub fn paint(style: Style, text: impl Display) -> String {
+    format!("{style}{text}{style:#}")
+}
+
+/// A diff line in its change's color.
+pub fn change(change: &Change) -> String {
+    let style = match change {
+        Change::Added(_) => ADDED,
+        Change::Changed { .. } => CHANGED,
+        Change::Removed(_) => REMOVED,
+    };
+    paint(style, change)
+}
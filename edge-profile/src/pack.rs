@@ -0,0 +1,109 @@
+//! `pack`: a copy of this executable with a config appended, which applies
+//! that config when double-clicked.
+//!
+//! Layout: `<exe> <config bytes> <u64 LE config length> <MAGIC>`. Windows
+//! ignores data past the end of the PE image, so the result still runs.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::registry::{Backend, Registry, Scope};
+use crate::{apply, config, diff, policy, validate};
+
+const MAGIC: &[u8; 16] = b"EDGEPROFILE-PACK";
+const TRAILER_LEN: u64 = 8 + MAGIC.len() as u64;
+
+pub fn pack(config: &Path, out: &Path) -> Result<()> {
+    let text = std::fs::read_to_string(config)
+        .with_context(|| format!("Failed to read config: {}", config.display()))?;
+    let cfg = config::parse(&text)
+        .with_context(|| format!("Failed to parse config: {}", config.display()))?;
+    let entries = policy::build_entries(&cfg);
+    crate::report_issues(&validate::check(&cfg, &entries))?;
+
+    let exe = std::env::current_exe().context("Cannot determine executable path")?;
+    let mut bytes = std::fs::read(&exe).context("Failed to read executable")?;
+    // Packing from a packed copy replaces its config rather than stacking.
+    if let Some((start, _)) = payload_bounds(&exe)? {
+        bytes.truncate(start as usize);
+    }
+    bytes.extend_from_slice(text.as_bytes());
+    bytes.extend_from_slice(&(text.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(MAGIC);
+
+    std::fs::write(out, bytes).with_context(|| format!("Failed to write {}", out.display()))?;
+    println!(
+        "Wrote {} ({} policy values); double-click it to apply.",
+        out.display(),
+        entries.len()
+    );
+    Ok(())
+}
+
+/// The config appended to this executable, if it is a packed copy.
+pub fn embedded_config() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    let (start, len) = payload_bounds(&exe).ok()??;
+    let mut file = File::open(&exe).ok()?;
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut text = String::new();
+    file.take(len).read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+/// Apply the embedded config to the current user, then wait for Enter so the
+/// console window stays open after a double-click.
+pub fn run_embedded(text: &str) -> Result<()> {
+    println!("Applying the packed Edge settings for this user...\n");
+    let result = apply_embedded(text);
+    match result {
+        Ok(()) => println!("\nDone. Restart Edge to pick up the new settings."),
+        Err(ref e) => eprintln!("\nError: {e:?}"),
+    }
+    println!("Press Enter to close.");
+    let _ = std::io::stdin().read_line(&mut String::new());
+    result
+}
+
+fn apply_embedded(text: &str) -> Result<()> {
+    let cfg = config::parse(text).context("Packed config is invalid")?;
+    let entries = policy::build_entries(&cfg);
+    crate::report_issues(&crate::check_config(&cfg, &entries, Scope::User))?;
+    let current = Registry.read_current(Scope::User)?;
+    let changes = diff::compare(&entries, &current);
+    apply::Apply {
+        command: "pack",
+        cfg: &cfg,
+        entries: &entries,
+        scope: Scope::User,
+        config: None,
+        config_key: None,
+        interactive: false,
+        output: apply::Output::Changes,
+    }
+    .run(&changes, &[])?;
+    Ok(())
+}
+
+/// Offset and length of the appended config.
+fn payload_bounds(exe: &Path) -> Result<Option<(u64, u64)>> {
+    let mut file = File::open(exe).context("Failed to open executable")?;
+    let size = file.metadata()?.len();
+    if size < TRAILER_LEN {
+        return Ok(None);
+    }
+    let mut trailer = [0u8; TRAILER_LEN as usize];
+    file.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    file.read_exact(&mut trailer)?;
+    if &trailer[8..] != MAGIC {
+        return Ok(None);
+    }
+    let len = u64::from_le_bytes(trailer[..8].try_into().expect("8 bytes"));
+    match (size - TRAILER_LEN).checked_sub(len) {
+        Some(start) => Ok(Some((start, len))),
+        None => Ok(None),
+    }
+}
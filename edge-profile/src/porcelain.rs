@@ -0,0 +1,50 @@
+//! `--porcelain` output: one record per line, tab-separated, stable across
+//! releases.
+//!
+//! ```text
+//! add       <path>  -           <value>
+//! change    <path>  <old value> <new value>
+//! remove    <path>  <old value> -
+//! conflict  <name>  -           -
+//! ```
+//!
+//! Paths are relative to the Edge policy key, as in `dump`. Values are
+//! `dword:<decimal>` or `sz:<text>`, with backslash, tab, CR and LF escaped
+//! as `\\`, `\t`, `\r` and `\n`; `-` marks a missing value.
+
+use crate::diff::Change;
+use crate::policy::RegValue;
+
+pub fn change(change: &Change) -> String {
+    match change {
+        Change::Added(e) => record("add", &e.path(), None, Some(&e.value)),
+        Change::Changed { old, new } => {
+            record("change", &new.path(), Some(&old.value), Some(&new.value))
+        }
+        Change::Removed(e) => record("remove", &e.path(), Some(&e.value), None),
+    }
+}
+
+/// A top-level value that HKLM overrides.
+pub fn conflict(name: &str) -> String {
+    record("conflict", name, None, None)
+}
+
+fn record(kind: &str, path: &str, old: Option<&RegValue>, new: Option<&RegValue>) -> String {
+    format!("{kind}\t{path}\t{}\t{}", value(old), value(new))
+}
+
+fn value(value: Option<&RegValue>) -> String {
+    match value {
+        None => "-".to_owned(),
+        Some(RegValue::Dword(v)) => format!("dword:{v}"),
+        Some(RegValue::Sz(s)) => format!("sz:{}", escape(s)),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', r"\\")
+        .replace('\t', r"\t")
+        .replace('\r', r"\r")
+        .replace('\n', r"\n")
+}
@@ -0,0 +1,11 @@
+//! Running scripts through `powershell -EncodedCommand`, which sidesteps
+//! every layer of command-line quoting between us and the script.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+/// The `-EncodedCommand` argument for a script: base64 of its UTF-16LE text.
+pub fn encode(script: &str) -> String {
+    let bytes: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    BASE64.encode(bytes)
+}
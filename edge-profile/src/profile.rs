@@ -0,0 +1,82 @@
+//! `profile`: named configs under `~/.edge-profile/profiles/`, one of which
+//! can stand in for the default config.
+
+use anyhow::{bail, Context, Result};
+
+use crate::registry::Scope;
+use crate::{autostart, config, output, schedule, watch};
+
+/// Print every profile, marking the one in use.
+pub fn list() -> Result<()> {
+    let dir = config::profiles_dir()?;
+    let mut names: Vec<String> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|p| Some(p.file_stem()?.to_string_lossy().into_owned()))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    if names.is_empty() {
+        println!("No profiles. Add config files to {}.", dir.display());
+        return Ok(());
+    }
+    names.sort();
+
+    let active = config::active_profile();
+    for name in names {
+        if active.as_ref() == Some(&name) {
+            anstream::println!("* {}", output::paint(output::HEADER, name));
+        } else {
+            println!("  {name}");
+        }
+    }
+    if active.is_none() {
+        println!("(none in use; commands read config.toml)");
+    }
+    Ok(())
+}
+
+/// Apply a profile and make it the config every command reads by default.
+/// Autostart, if enabled, is pointed at it too.
+pub fn switch(name: &str, scope: Scope) -> Result<()> {
+    let path = existing(name)?;
+    watch::apply(&path, scope, false)?;
+
+    let marker = config::active_profile_path()?;
+    std::fs::create_dir_all(marker.parent().expect("marker has a parent"))?;
+    std::fs::write(&marker, format!("{name}\n"))
+        .with_context(|| format!("Failed to write {}", marker.display()))?;
+    println!("Now using profile {name}.");
+
+    if autostart::registered_config().is_some() {
+        autostart::enable(&path)?;
+    }
+    if schedule::installed() {
+        let line =
+            format!("The scheduled task keeps its own config; reinstall it to follow {name}.");
+        anstream::println!("{}", output::paint(output::NOTE, line));
+    }
+    Ok(())
+}
+
+/// Apply a profile once, leaving the one in use as it is.
+pub fn apply(name: &str, scope: Scope) -> Result<()> {
+    watch::apply(&existing(name)?, scope, false)
+}
+
+fn existing(name: &str) -> Result<std::path::PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\', '.']) {
+        bail!("Invalid profile name {name:?}");
+    }
+    let path = config::profile_path(name)?;
+    if !path.exists() {
+        bail!(
+            "No profile named {name}; create {} (e.g. with `edge-profile init --config`)",
+            path.display()
+        );
+    }
+    Ok(path)
+}
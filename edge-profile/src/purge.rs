@@ -0,0 +1,134 @@
+//! `purge`: remove everything the tool has put on this machine.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::registry::{self, Backend, Registry, Scope};
+use crate::{autostart, backup, config, history, journal, policy, schedule, service, state};
+
+/// One piece of the footprint.
+enum Item {
+    /// Managed policy values in a hive, and how many.
+    Policies(Scope, usize),
+    Autostart,
+    Schedule,
+    Service,
+    File(PathBuf),
+    Dir(PathBuf),
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Policies(scope, n) => write!(f, "{n} managed policy value(s) in {scope}"),
+            Item::Autostart => f.write_str("the sign-in entry under the HKCU Run key"),
+            Item::Schedule => write!(f, "the {} scheduled task", schedule::TASK_NAME),
+            Item::Service => write!(f, "the {} Windows service", service::SERVICE_NAME),
+            Item::File(path) | Item::Dir(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// List the footprint, then on confirmation remove it. The default config
+/// file is kept unless `include_config` is set. Returns false if the user
+/// declined.
+pub fn run(include_config: bool, dry_run: bool, yes: bool) -> Result<bool> {
+    let items = footprint(include_config)?;
+    if items.is_empty() {
+        println!("Nothing to remove.");
+        return Ok(true);
+    }
+    println!(
+        "{}",
+        if dry_run {
+            "Would remove:"
+        } else {
+            "This removes:"
+        }
+    );
+    for item in &items {
+        println!("  {item}");
+    }
+    if dry_run {
+        return Ok(true);
+    }
+    if !crate::confirm("Remove all of it?", yes)? {
+        return Ok(false);
+    }
+
+    // The service and task go first so they can't put policies back.
+    for item in &items {
+        match item {
+            Item::Service => service::uninstall()?,
+            Item::Autostart => autostart::disable()?,
+            Item::Schedule => schedule::remove()?,
+            Item::Policies(scope, _) => {
+                registry::clean(*scope, &[], false)
+                    .with_context(|| format!("Failed to remove {scope} policies"))?;
+            }
+            Item::File(path) => std::fs::remove_file(path)
+                .with_context(|| format!("Failed to delete {}", path.display()))?,
+            Item::Dir(path) => std::fs::remove_dir_all(path)
+                .with_context(|| format!("Failed to delete {}", path.display()))?,
+        }
+    }
+    let data_dir = config::data_dir()?;
+    if std::fs::remove_dir(&data_dir).is_ok() {
+        println!("Removed {}", data_dir.display());
+    }
+    println!("Purged edge-profile from this machine.");
+    Ok(true)
+}
+
+fn footprint(include_config: bool) -> Result<Vec<Item>> {
+    let mut items = Vec::new();
+    if service::state().is_some() {
+        items.push(Item::Service);
+    }
+    if autostart::registered_config().is_some() {
+        items.push(Item::Autostart);
+    }
+    if schedule::installed() {
+        items.push(Item::Schedule);
+    }
+    for scope in [Scope::User, Scope::Machine] {
+        let managed = Registry
+            .read_current(scope)?
+            .iter()
+            .filter(|e| {
+                let name = e.policy_name();
+                policy::MANAGED_VALUES.contains(&name) || policy::is_managed_subkey(name)
+            })
+            .count();
+        if managed > 0 {
+            items.push(Item::Policies(scope, managed));
+        }
+    }
+    for path in [
+        state::path()?,
+        journal::path()?,
+        history::path()?,
+        config::active_profile_path()?,
+    ] {
+        if path.exists() {
+            items.push(Item::File(path));
+        }
+    }
+    let backups = backup::dir()?;
+    if backups.exists() {
+        items.push(Item::Dir(backups));
+    }
+    if include_config {
+        let config = config::data_dir()?.join("config.toml");
+        if config.exists() {
+            items.push(Item::File(config));
+        }
+        let profiles = config::profiles_dir()?;
+        if profiles.exists() {
+            items.push(Item::Dir(profiles));
+        }
+    }
+    Ok(items)
+}
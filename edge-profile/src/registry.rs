@@ -1,115 +1,639 @@
+use std::ptr;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use tracing::{debug, warn};
+use windows_sys::Win32::Foundation::{CloseHandle, ERROR_KEY_DELETED, HANDLE, WAIT_OBJECT_0};
+use windows_sys::Win32::System::Registry::{
+    RegNotifyChangeKeyValue, REG_NOTIFY_CHANGE_LAST_SET, REG_NOTIFY_CHANGE_NAME,
+};
+use windows_sys::Win32::System::Threading::{CreateEventW, ResetEvent, WaitForSingleObject};
 use winreg::enums::*;
+use winreg::types::{FromRegValue, ToRegValue};
 use winreg::RegKey;
 
+pub use edge_profile_core::registry::{
+    Backend, Registry, Scope, CHROME_POLICY_PATH, EDGE_POLICY_PATH,
+};
+
+use crate::catalog;
+use crate::config::Section;
+use crate::edge::{self, EdgeVersion};
+use crate::output;
 use crate::policy::{self, PolicyEntry, RegValue};
 
-const EDGE_POLICY_PATH: &str = r"SOFTWARE\Policies\Microsoft\Edge";
+const MDM_DEVICE_PATH: &str = r"SOFTWARE\Microsoft\PolicyManager\current\device";
 
-pub fn apply(entries: &[PolicyEntry]) -> Result<()> {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let (base, _) = hkcu
-        .create_subkey(EDGE_POLICY_PATH)
-        .context("Failed to open/create Edge policy key")?;
+/// Signals when anything under the Edge policy key changes.
+pub struct KeyWatcher {
+    scope: Scope,
+    event: HANDLE,
+    /// The watched key. It stays open between waits, since closing a watched
+    /// key signals the event.
+    key: Option<RegKey>,
+    /// Whether a notification is registered and hasn't fired yet.
+    armed: bool,
+}
 
-    // Delete-then-recreate list subkeys to avoid stale numbered entries
-    for subkey_name in policy::MANAGED_SUBKEYS {
-        let _ = base.delete_subkey_all(subkey_name);
+impl KeyWatcher {
+    pub fn new(scope: Scope) -> Result<Self> {
+        // SAFETY: plain auto-reset event with no name or security attributes.
+        let event = unsafe { CreateEventW(ptr::null(), 0, 0, ptr::null()) };
+        if event.is_null() {
+            return Err(std::io::Error::last_os_error()).context("Failed to create event");
+        }
+        Ok(Self {
+            scope,
+            event,
+            key: None,
+            armed: false,
+        })
     }
 
-    for entry in entries {
-        let key = if entry.subkey.is_empty() {
-            &base
-        } else {
-            &hkcu
-                .create_subkey(&format!(r"{}\{}", EDGE_POLICY_PATH, entry.subkey))
-                .with_context(|| format!("Failed to create subkey: {}", entry.subkey))?
-                .0
-        };
+    /// Block until the policy key changes or `timeout` elapses. Returns
+    /// whether a change was seen.
+    pub fn wait(&mut self, timeout: Duration) -> Result<bool> {
+        // A notification fires once; one still pending from a wait that
+        // timed out keeps watching.
+        if !self.armed {
+            self.arm()?;
+        }
+        let millis = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+        // SAFETY: waiting on an event handle we own.
+        let changed = unsafe { WaitForSingleObject(self.event, millis) } == WAIT_OBJECT_0;
+        if changed {
+            self.armed = false;
+        }
+        Ok(changed)
+    }
 
-        match &entry.value {
-            RegValue::Dword(v) => key
-                .set_value(&entry.name, v)
-                .with_context(|| format!("Failed to set DWORD: {}", entry.name))?,
-            RegValue::Sz(v) => key
-                .set_value(&entry.name, v)
-                .with_context(|| format!("Failed to set SZ: {}", entry.name))?,
+    fn arm(&mut self) -> Result<()> {
+        loop {
+            let key = match self.key.take() {
+                Some(key) => key,
+                None => {
+                    self.scope
+                        .hive()
+                        .create_subkey(EDGE_POLICY_PATH)
+                        .context("Failed to open/create Edge policy key")?
+                        .0
+                }
+            };
+            // SAFETY: the key handle and event stay valid until the watcher
+            // is dropped.
+            let status = unsafe {
+                RegNotifyChangeKeyValue(
+                    key.raw_handle(),
+                    1,
+                    REG_NOTIFY_CHANGE_NAME | REG_NOTIFY_CHANGE_LAST_SET,
+                    self.event,
+                    1,
+                )
+            };
+            match status {
+                0 => {
+                    self.key = Some(key);
+                    self.armed = true;
+                    return Ok(());
+                }
+                ERROR_KEY_DELETED => {
+                    // The key was deleted since the last wait, which already
+                    // reported it; watch the one that replaces it. Closing the
+                    // old handle may signal the event, which isn't a change.
+                    drop(key);
+                    // SAFETY: resetting an event handle we own.
+                    unsafe { ResetEvent(self.event) };
+                }
+                _ => {
+                    return Err(std::io::Error::from_raw_os_error(status as i32))
+                        .context("Failed to watch Edge policy key")
+                }
+            }
         }
     }
+}
 
+impl Drop for KeyWatcher {
+    fn drop(&mut self) {
+        // Close the key while the event it signals still exists.
+        self.key = None;
+        // SAFETY: the event was created in `new` and is closed exactly once.
+        unsafe { CloseHandle(self.event) };
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum DumpFormat {
+    /// Grouped by category, with notes on values Edge ignores
+    #[default]
+    Text,
+    /// The key and its subkeys as nested JSON
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DumpHive {
+    #[default]
+    Hkcu,
+    Hklm,
+    /// Both, side by side, with the value Edge honors
+    Both,
+}
+
+/// Which policies `dump` shows. Empty matches everything.
+#[derive(Debug, Default)]
+pub struct DumpFilter {
+    /// Case-insensitive name pattern; `*` matches any run of characters and
+    /// `?` any one.
+    pub glob: Option<String>,
+    pub sections: Vec<Section>,
+}
+
+impl DumpFilter {
+    fn matches(&self, name: &str) -> bool {
+        let glob = self
+            .glob
+            .as_deref()
+            .is_none_or(|g| glob_match(&g.to_ascii_lowercase(), &name.to_ascii_lowercase()));
+        glob && (self.sections.is_empty() || self.sections.contains(&Section::of(name)))
+    }
+}
+
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let (p, n): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    // Position after the last `*`, and where in `name` it resumed matching.
+    let (mut pi, mut ni, mut star) = (0, 0, None);
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi + 1, ni));
+            pi += 1;
+        } else if let Some((sp, sn)) = star {
+            pi = sp;
+            ni = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[derive(Serialize)]
+struct JsonKey {
+    path: String,
+    /// Whether edge-profile manages this subkey; absent for the policy key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    managed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    values: Vec<JsonValue>,
+    subkeys: Vec<JsonKey>,
+}
+
+#[derive(Serialize)]
+struct JsonValue {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    value: serde_json::Value,
+    /// The catalog's name for an enumerated DWORD, e.g. `strict`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meaning: Option<&'static str>,
+    managed: bool,
+    /// Why Edge ignores the value, or that the catalog doesn't know it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+/// The policy key as JSON: every value with its type and decoded data, and
+/// subkeys nested under their parent. With both hives, an array of the two.
+pub fn dump_json(filter: &DumpFilter, hive: DumpHive) -> Result<()> {
+    let json = match hive {
+        DumpHive::Hkcu => serde_json::to_string_pretty(&json_root(filter, Scope::User))?,
+        DumpHive::Hklm => serde_json::to_string_pretty(&json_root(filter, Scope::Machine))?,
+        DumpHive::Both => serde_json::to_string_pretty(&[
+            json_root(filter, Scope::User),
+            json_root(filter, Scope::Machine),
+        ])?,
+    };
+    println!("{json}");
     Ok(())
 }
 
-pub fn dump() -> Result<()> {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+fn json_root(filter: &DumpFilter, scope: Scope) -> JsonKey {
+    let path = format!(r"{scope}\{EDGE_POLICY_PATH}");
+    match scope.hive().open_subkey(EDGE_POLICY_PATH) {
+        Ok(base) => {
+            let mut root = json_key(&base, path, None, edge::installed_version());
+            root.values.retain(|v| filter.matches(&v.name));
+            root.subkeys.retain(|k| filter.matches(k.path.rsplit('\\').next().unwrap_or("")));
+            root
+        }
+        Err(_) => JsonKey {
+            path,
+            managed: None,
+            note: None,
+            values: Vec::new(),
+            subkeys: Vec::new(),
+        },
+    }
+}
+
+/// `subkey` is the policy a list subkey belongs to, `None` for the policy
+/// key itself, whose values are policies in their own right.
+fn json_key(
+    key: &RegKey,
+    path: String,
+    subkey: Option<&str>,
+    version: Option<EdgeVersion>,
+) -> JsonKey {
+    let values = key
+        .enum_values()
+        .filter_map(|r| r.ok())
+        .map(|(name, value)| {
+            let def = catalog::lookup(&name).filter(|_| subkey.is_none());
+            let data = json_data(key, &name, &value);
+            let meaning = match data {
+                serde_json::Value::Number(ref n) => n
+                    .as_u64()
+                    .and_then(|n| u32::try_from(n).ok())
+                    .and_then(|n| def?.value_name(n)),
+                _ => None,
+            };
+            JsonValue {
+                managed: match subkey {
+                    Some(policy) => policy::is_managed_subkey(policy),
+                    None => policy::MANAGED_VALUES.contains(&name.as_str()),
+                },
+                note: subkey
+                    .is_none()
+                    .then(|| catalog_note(&name, version))
+                    .flatten()
+                    .map(|(note, _)| note),
+                kind: format!("{:?}", value.vtype),
+                value: data,
+                meaning,
+                name,
+            }
+        })
+        .collect();
+    let subkeys = key
+        .enum_keys()
+        .filter_map(|r| r.ok())
+        .filter_map(|name| {
+            let sub = key.open_subkey(&name).ok()?;
+            let policy = subkey.unwrap_or(&name).to_owned();
+            let mut child = json_key(&sub, format!(r"{path}\{name}"), Some(&policy), version);
+            if subkey.is_none() {
+                child.managed = Some(policy::is_managed_subkey(&name));
+                child.note = catalog_note(&name, version).map(|(note, _)| note);
+            }
+            Some(child)
+        })
+        .collect();
+    JsonKey {
+        path,
+        managed: None,
+        note: None,
+        values,
+        subkeys,
+    }
+}
+
+/// A value's data as JSON: numbers for DWORD/QWORD, strings (or an array
+/// for REG_MULTI_SZ), and lowercase hex for anything else.
+fn json_data(key: &RegKey, name: &str, value: &winreg::RegValue) -> serde_json::Value {
+    let decoded = match value.vtype {
+        REG_DWORD => key.get_value::<u32, _>(name).ok().map(Into::into),
+        REG_QWORD => key.get_value::<u64, _>(name).ok().map(Into::into),
+        REG_SZ | REG_EXPAND_SZ => key.get_value::<String, _>(name).ok().map(Into::into),
+        REG_MULTI_SZ => key.get_value::<Vec<String>, _>(name).ok().map(Into::into),
+        _ => None,
+    };
+    decoded.unwrap_or_else(|| {
+        let hex: String = value.bytes.iter().map(|b| format!("{b:02x}")).collect();
+        hex.into()
+    })
+}
 
-    let base = match hkcu.open_subkey(EDGE_POLICY_PATH) {
+pub fn dump(filter: &DumpFilter, scope: Scope) -> Result<()> {
+    let base = match scope.hive().open_subkey(EDGE_POLICY_PATH) {
         Ok(k) => k,
         Err(_) => {
-            println!("No HKCU Edge policies found.");
+            println!("No {scope} Edge policies found.");
             return Ok(());
         }
     };
 
-    println!(r"HKCU\{}", EDGE_POLICY_PATH);
-    println!();
-    dump_key(&base, "")?;
+    let header = format!(r"{scope}\{EDGE_POLICY_PATH}");
+    anstream::println!("{}", output::paint(output::HEADER, header));
+
+    let version = edge::installed_version();
+    let mut flagged = 0;
+    let mut unchecked = 0;
+    // (category, lines) per top-level value or list subkey
+    let mut blocks: Vec<(&str, Vec<String>)> = Vec::new();
+
+    for (name, value) in base.enum_values().filter_map(|r| r.ok()) {
+        if !filter.matches(&name) {
+            continue;
+        }
+        let def = catalog::lookup(&name);
+        let note = catalog_note(&name, version).map(|(note, ignored)| {
+            if ignored {
+                flagged += 1;
+            } else {
+                unchecked += 1;
+            }
+            note
+        });
+        let managed = policy::MANAGED_VALUES.contains(&name.as_str());
+        let display = display_value(&base, &name, &value, def);
+        blocks.push((
+            category_of(def),
+            vec![dump_line("  ", managed, &format!("{name} = {display}"), note)],
+        ));
+    }
 
-    // Enumerate subkeys
+    // List subkeys are named after their policy
     for name in base.enum_keys().filter_map(|r| r.ok()) {
-        if let Ok(sub) = base.open_subkey(&name) {
-            println!("  [{name}]");
-            dump_key(&sub, "    ")?;
+        if !filter.matches(&name) {
+            continue;
+        }
+        let Ok(sub) = base.open_subkey(&name) else {
+            continue;
+        };
+        let def = catalog::lookup(&name);
+        let note = catalog_note(&name, version).map(|(note, ignored)| {
+            if ignored {
+                flagged += 1;
+            } else {
+                unchecked += 1;
+            }
+            note
+        });
+        let managed = policy::is_managed_subkey(&name);
+        let mut lines = vec![dump_line("  ", managed, &format!("[{name}]"), note)];
+        for (item, value) in sub.enum_values().filter_map(|r| r.ok()) {
+            let display = display_value(&sub, &item, &value, None);
+            lines.push(format!("        {item} = {display}"));
         }
+        blocks.push((category_of(def), lines));
+    }
+
+    let unknown = std::iter::once("unknown");
+    for category in catalog::categories().into_iter().chain(unknown) {
+        let mut group = blocks.iter().filter(|(c, _)| *c == category).peekable();
+        if group.peek().is_none() {
+            continue;
+        }
+        println!();
+        anstream::println!("{}", output::paint(output::HEADER, catalog::category_title(category)));
+        for line in group.flat_map(|(_, lines)| lines) {
+            anstream::println!("{line}");
+        }
+    }
+
+    println!();
+    println!("* managed by edge-profile");
+    if flagged > 0 {
+        anstream::println!(
+            "{}",
+            output::paint(
+                output::WARNING,
+                format!("{flagged} value(s) are not honored by Edge and are silently ignored.")
+            )
+        );
+    }
+    if unchecked > 0 {
+        let line =
+            format!("{unchecked} value(s) are not in edge-profile's catalog, so weren't checked.");
+        anstream::println!("{}", output::paint(output::NOTE, line));
     }
 
     Ok(())
 }
 
-fn dump_key(key: &RegKey, indent: &str) -> Result<()> {
-    for (name, value) in key.enum_values().filter_map(|r| r.ok()) {
-        let display = match value.vtype {
-            REG_DWORD => {
-                let v: u32 = key.get_value(&name).unwrap_or(0);
-                format!("DWORD({v})")
-            }
-            REG_SZ | REG_EXPAND_SZ => {
-                let v: String = key.get_value(&name).unwrap_or_default();
-                format!("\"{v}\"")
-            }
-            _ => format!("{:?}", value.bytes),
-        };
-        println!("{indent}{name} = {display}");
+/// Every policy set in either hive side by side, with the hive Edge honors.
+/// Machine policy wins, so HKCU values are ignored wherever HKLM sets the
+/// same policy.
+pub fn compare_hklm(filter: &DumpFilter) -> Result<()> {
+    let matching = |scope| -> Result<Vec<PolicyEntry>> {
+        let mut entries = Registry.read_current(scope)?;
+        entries.retain(|e| filter.matches(e.policy_name()));
+        Ok(entries)
+    };
+    let user = matching(Scope::User)?;
+    let machine = matching(Scope::Machine)?;
+    if user.is_empty() && machine.is_empty() {
+        println!("No Edge policies in HKCU or HKLM.");
+        return Ok(());
+    }
+
+    let mut rows: Vec<CompareRow> = Vec::new();
+    for entry in user.iter().chain(&machine) {
+        let path = entry.path();
+        if rows.iter().any(|r| r.path.eq_ignore_ascii_case(&path)) {
+            continue;
+        }
+        let policy = entry.policy_name();
+        let in_user = user.iter().any(|e| e.policy_name().eq_ignore_ascii_case(policy));
+        let in_machine = machine.iter().any(|e| e.policy_name().eq_ignore_ascii_case(policy));
+        let hkcu = compare_value(&user, &path);
+        let hklm = compare_value(&machine, &path);
+        rows.push(CompareRow {
+            category: category_of(catalog::lookup(policy)),
+            mismatch: in_user && in_machine && hkcu != hklm,
+            honored: if in_machine { "HKLM" } else { "HKCU" },
+            path,
+            hkcu,
+            hklm,
+        });
+    }
+    let categories = catalog::categories();
+    let rank = |c: &str| categories.iter().position(|&x| x == c).unwrap_or(usize::MAX);
+    rows.sort_by(|a, b| (rank(a.category), &a.path).cmp(&(rank(b.category), &b.path)));
+
+    let path_w = rows.iter().map(|r| r.path.len()).chain([6]).max().unwrap_or(0);
+    let hkcu_w = rows.iter().map(|r| r.hkcu.len()).chain([4]).max().unwrap_or(0);
+    let hklm_w = rows.iter().map(|r| r.hklm.len()).chain([4]).max().unwrap_or(0);
+    let header = format!("{:path_w$}  {:hkcu_w$}  {:hklm_w$}  Edge uses", "Policy", "HKCU", "HKLM");
+    anstream::println!("{}", output::paint(output::HEADER, header));
+
+    let mut category = "";
+    for row in &rows {
+        if row.category != category {
+            category = row.category;
+            println!();
+            let title = catalog::category_title(category);
+            anstream::println!("{}", output::paint(output::HEADER, title));
+        }
+        let line = format!(
+            "{:path_w$}  {:hkcu_w$}  {:hklm_w$}  {}",
+            row.path, row.hkcu, row.hklm, row.honored
+        );
+        if row.mismatch {
+            anstream::println!("{}", output::paint(output::CHANGED, line));
+        } else {
+            println!("{line}");
+        }
+    }
+
+    let mismatches = rows.iter().filter(|r| r.mismatch).count();
+    if mismatches > 0 {
+        println!();
+        let warning = format!("{mismatches} value(s) differ between the hives; Edge uses HKLM.");
+        anstream::println!("{}", output::paint(output::WARNING, warning));
     }
     Ok(())
 }
 
-pub fn clean() -> Result<()> {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+struct CompareRow {
+    category: &'static str,
+    path: String,
+    /// Decoded values, `-` when the hive doesn't set this one.
+    hkcu: String,
+    hklm: String,
+    honored: &'static str,
+    mismatch: bool,
+}
+
+/// The value at `path` for the comparison table, or `-` if it isn't set.
+fn compare_value(entries: &[PolicyEntry], path: &str) -> String {
+    match entries.iter().find(|e| e.path().eq_ignore_ascii_case(path)) {
+        Some(entry) => describe(entry),
+        None => "-".to_owned(),
+    }
+}
+
+/// An entry's value decoded like `dump` does, e.g. `DWORD(3) (strict)`.
+pub fn describe(entry: &PolicyEntry) -> String {
+    let def = catalog::lookup(&entry.name).filter(|_| entry.subkey.is_empty());
+    match entry.value {
+        RegValue::Dword(v) => match def.and_then(|d| d.value_name(v)) {
+            Some(meaning) => format!("DWORD({v}) ({meaning})"),
+            None => format!("DWORD({v})"),
+        },
+        RegValue::Sz(_) => entry.value.to_string(),
+    }
+}
+
+fn category_of(def: Option<&catalog::PolicyDef>) -> &'static str {
+    def.map_or("unknown", |d| d.category)
+}
+
+/// `* Name = value  # note`, with the marker for values this tool manages.
+fn dump_line(indent: &str, managed: bool, text: &str, note: Option<String>) -> String {
+    let marker = if managed { '*' } else { ' ' };
+    match note {
+        Some(note) => format!("{indent}{marker} {text}  {}", styled_note(&note)),
+        None => format!("{indent}{marker} {text}"),
+    }
+}
+
+/// A registry value for display, with enumerated DWORDs decoded through the
+/// catalog, e.g. `DWORD(3) (strict)`.
+fn display_value(
+    key: &RegKey,
+    name: &str,
+    value: &winreg::RegValue,
+    def: Option<&catalog::PolicyDef>,
+) -> String {
+    match value.vtype {
+        REG_DWORD => {
+            let v: u32 = key.get_value(name).unwrap_or(0);
+            match def.and_then(|d| d.value_name(v)) {
+                Some(meaning) => format!("DWORD({v}) ({meaning})"),
+                None => format!("DWORD({v})"),
+            }
+        }
+        REG_SZ | REG_EXPAND_SZ => {
+            let v: String = key.get_value(name).unwrap_or_default();
+            format!("\"{v}\"")
+        }
+        _ => format!("{:?}", value.bytes),
+    }
+}
 
-    let base = match hkcu.open_subkey_with_flags(EDGE_POLICY_PATH, KEY_ALL_ACCESS) {
+fn styled_note(note: &str) -> String {
+    output::paint(output::WARNING, format!("# {note}"))
+}
+
+/// A note on a policy the catalog doesn't vouch for, and whether Edge
+/// ignores it: obsolete policies and near misses of a known name are
+/// ignored, while other names may just be missing from the catalog.
+fn catalog_note(name: &str, version: Option<EdgeVersion>) -> Option<(String, bool)> {
+    let Some(def) = catalog::lookup(name) else {
+        return Some(match catalog::suggest(name).first() {
+            Some(close) => (format!("unknown policy (did you mean {close}?)"), true),
+            None => ("not in edge-profile's catalog".to_owned(), false),
+        });
+    };
+    let removed = def.removed?;
+    match version {
+        Some(v) if v.major() < removed => None,
+        _ => Some((format!("obsolete since Edge {removed}"), true)),
+    }
+}
+
+/// Delete the managed values and subkeys, limited to `only` sections when
+/// any are given, printing each one. With `dry_run`, print what would be
+/// deleted and leave the key alone. Returns how many were (or would be)
+/// deleted.
+pub fn clean(scope: Scope, only: &[Section], dry_run: bool) -> Result<usize> {
+    let hkcu = scope.hive();
+
+    // A dry run only reads, so it works unelevated on HKLM too.
+    let access = if dry_run { KEY_READ } else { KEY_ALL_ACCESS };
+    let base = match hkcu.open_subkey_with_flags(EDGE_POLICY_PATH, access) {
         Ok(k) => k,
-        Err(_) => {
-            println!("No HKCU Edge policies found, nothing to clean.");
-            return Ok(());
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(e).with_context(|| format!(r"Failed to open {scope}\{EDGE_POLICY_PATH}"))
         }
     };
+    let selected = |name: &str| only.is_empty() || only.contains(&Section::of(name));
+    let mut count = 0;
 
     // Remove managed top-level values
-    for name in policy::MANAGED_VALUES {
-        match base.delete_value(name) {
-            Ok(()) => println!("  Removed {name}"),
-            Err(_) => {}
+    for name in policy::MANAGED_VALUES.iter().filter(|n| selected(n)) {
+        let Ok(value) = base.get_raw_value(name) else {
+            continue;
+        };
+        count += 1;
+        if dry_run {
+            let display = display_value(&base, name, &value, catalog::lookup(name));
+            println!("  Would remove {name} = {display}");
+        } else if base.delete_value(name).is_ok() {
+            debug!(r"deleted {scope}\{EDGE_POLICY_PATH}\{name}");
+            let line = format!("Removed {name}");
+            anstream::println!("  {}", output::paint(output::REMOVED, line));
         }
     }
 
     // Remove managed subkeys
-    for subkey_name in policy::MANAGED_SUBKEYS {
-        match base.delete_subkey_all(subkey_name) {
-            Ok(()) => println!("  Removed subkey {subkey_name}"),
-            Err(_) => {}
+    for subkey_name in policy::managed_subkeys().filter(|n| selected(n)) {
+        let Ok(sub) = base.open_subkey(subkey_name) else {
+            continue;
+        };
+        count += 1;
+        if dry_run {
+            let items = sub.query_info().map_or(0, |i| i.values);
+            println!("  Would remove subkey {subkey_name} ({items} item(s))");
+        } else if base.delete_subkey_all(subkey_name).is_ok() {
+            debug!(r"deleted {scope}\{EDGE_POLICY_PATH}\{subkey_name}");
+            let line = format!("Removed subkey {subkey_name}");
+            anstream::println!("  {}", output::paint(output::REMOVED, line));
         }
     }
+    if dry_run {
+        return Ok(count);
+    }
 
     // If the Edge policy key is now empty, remove it too
     let has_values = base.enum_values().next().is_some();
@@ -118,21 +642,266 @@ pub fn clean() -> Result<()> {
 
     if !has_values && !has_subkeys {
         let _ = hkcu.delete_subkey(EDGE_POLICY_PATH);
-        println!("  Removed empty Edge policy key");
+        let line = "Removed empty Edge policy key";
+        anstream::println!("  {}", output::paint(output::REMOVED, line));
+    }
+
+    Ok(count)
+}
+
+/// Every registry value type, for reading a type back from its name.
+const REG_TYPES: [RegType; 12] = [
+    REG_NONE,
+    REG_SZ,
+    REG_EXPAND_SZ,
+    REG_BINARY,
+    REG_DWORD,
+    REG_DWORD_BIG_ENDIAN,
+    REG_LINK,
+    REG_MULTI_SZ,
+    REG_RESOURCE_LIST,
+    REG_FULL_RESOURCE_DESCRIPTOR,
+    REG_RESOURCE_REQUIREMENTS_LIST,
+    REG_QWORD,
+];
+
+/// A value anywhere under the Edge policy key, with its type and bytes as
+/// the registry holds them, so it can be written back exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawValue {
+    /// Key relative to the Edge policy key, e.g. `Recommended`; empty for
+    /// the policy key itself.
+    pub key: String,
+    pub name: String,
+    pub kind: RegType,
+    pub bytes: Vec<u8>,
+}
+
+impl RawValue {
+    /// The value written for a policy entry.
+    pub fn from_entry(entry: &PolicyEntry) -> Self {
+        let value = match entry.value {
+            RegValue::Dword(v) => v.to_reg_value(),
+            RegValue::Sz(ref s) => s.to_reg_value(),
+        };
+        RawValue {
+            key: entry.subkey.clone(),
+            name: entry.name.clone(),
+            kind: value.vtype,
+            bytes: value.bytes,
+        }
+    }
+
+    /// Path relative to the Edge policy key, e.g. `Recommended\HomepageLocation`.
+    pub fn path(&self) -> String {
+        if self.key.is_empty() {
+            self.name.clone()
+        } else {
+            format!(r"{}\{}", self.key, self.name)
+        }
+    }
+
+    /// The type's name, e.g. `REG_MULTI_SZ`.
+    pub fn type_name(&self) -> String {
+        format!("{:?}", self.kind)
+    }
+
+    /// The type called `name` by [`RawValue::type_name`].
+    pub fn type_from_name(name: &str) -> Option<RegType> {
+        REG_TYPES.into_iter().find(|t| format!("{t:?}") == name)
+    }
+
+    /// Whether this and `other` are the same value, whatever their data.
+    /// Registry names are case-insensitive.
+    pub fn same_place(&self, other: &RawValue) -> bool {
+        self.key.eq_ignore_ascii_case(&other.key) && self.name.eq_ignore_ascii_case(&other.name)
+    }
+}
+
+impl std::fmt::Display for RawValue {
+    /// The data decoded like `dump` shows it: `DWORD(3)` and `"text"` as for
+    /// policy entries, other types under their own name.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = winreg::RegValue {
+            bytes: self.bytes.clone(),
+            vtype: self.kind.clone(),
+        };
+        match self.kind {
+            REG_DWORD => {
+                if let Ok(v) = u32::from_reg_value(&value) {
+                    return write!(f, "DWORD({v})");
+                }
+            }
+            REG_QWORD => {
+                if let Ok(v) = u64::from_reg_value(&value) {
+                    return write!(f, "QWORD({v})");
+                }
+            }
+            REG_SZ => {
+                if let Ok(v) = String::from_reg_value(&value) {
+                    return write!(f, "\"{v}\"");
+                }
+            }
+            REG_EXPAND_SZ => {
+                if let Ok(v) = String::from_reg_value(&value) {
+                    return write!(f, "EXPAND_SZ(\"{v}\")");
+                }
+            }
+            REG_MULTI_SZ => {
+                if let Ok(v) = Vec::<String>::from_reg_value(&value) {
+                    return write!(f, "MULTI_SZ({v:?})");
+                }
+            }
+            _ => {}
+        }
+        let hex: String = self.bytes.iter().map(|b| format!("{b:02x}")).collect();
+        write!(f, "{}({hex})", self.type_name().trim_start_matches("REG_"))
     }
+}
 
+/// Everything under the Edge policy key: every subkey at any depth, so
+/// empty ones come back too, and every value with its raw type.
+#[derive(Debug, Default)]
+pub struct KeyTree {
+    /// Relative to the Edge policy key, parents before children.
+    pub keys: Vec<String>,
+    pub values: Vec<RawValue>,
+}
+
+/// Read the whole Edge policy key. Unlike `read_current`, which only sees
+/// what edge-profile writes, any value or key that can't be read is an
+/// error rather than skipped, since the result is used to put the key back.
+pub fn read_tree(scope: Scope) -> Result<KeyTree> {
+    let mut tree = KeyTree::default();
+    let base = match scope.hive().open_subkey(EDGE_POLICY_PATH) {
+        Ok(k) => k,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(tree),
+        Err(e) => {
+            return Err(e).with_context(|| format!(r"Failed to open {scope}\{EDGE_POLICY_PATH}"))
+        }
+    };
+    read_subtree(&base, "", &mut tree)
+        .with_context(|| format!(r"Failed to read {scope}\{EDGE_POLICY_PATH}"))?;
+    Ok(tree)
+}
+
+fn read_subtree(key: &RegKey, path: &str, tree: &mut KeyTree) -> std::io::Result<()> {
+    for value in key.enum_values() {
+        let (name, value) = value?;
+        tree.values.push(RawValue {
+            key: path.to_owned(),
+            name,
+            kind: value.vtype,
+            bytes: value.bytes,
+        });
+    }
+    for name in key.enum_keys() {
+        let name = name?;
+        let sub = key.open_subkey(&name)?;
+        let sub_path = if path.is_empty() { name } else { format!(r"{path}\{name}") };
+        tree.keys.push(sub_path.clone());
+        read_subtree(&sub, &sub_path, tree)?;
+    }
     Ok(())
 }
 
+/// Create every key in `tree` under the Edge policy key and write its values
+/// with their original types. Doesn't delete anything first.
+pub fn write_tree(scope: Scope, tree: &KeyTree) -> Result<()> {
+    if tree.keys.is_empty() && tree.values.is_empty() {
+        return Ok(());
+    }
+    let hive = scope.hive();
+    let open = |path: &str| {
+        let full = match path {
+            "" => EDGE_POLICY_PATH.to_owned(),
+            _ => format!(r"{EDGE_POLICY_PATH}\{path}"),
+        };
+        hive.create_subkey(&full)
+            .map(|(key, _)| key)
+            .with_context(|| format!(r"Failed to create {scope}\{full}"))
+    };
+    open("")?;
+    for path in &tree.keys {
+        open(path)?;
+    }
+    for value in &tree.values {
+        let raw = winreg::RegValue {
+            bytes: value.bytes.clone(),
+            vtype: value.kind.clone(),
+        };
+        open(&value.key)?
+            .set_raw_value(&value.name, &raw)
+            .with_context(|| format!("Failed to write {}", value.path()))?;
+        debug!(r"set {scope}\{EDGE_POLICY_PATH}\{} = {value}", value.path());
+    }
+    Ok(())
+}
+
+/// Delete the whole Edge policy key, including policies this tool doesn't
+/// manage. Returns false if there was no key.
+pub fn clean_all(scope: Scope) -> Result<bool> {
+    match scope.hive().delete_subkey_all(EDGE_POLICY_PATH) {
+        Ok(()) => {
+            debug!(r"deleted {scope}\{EDGE_POLICY_PATH}");
+            Ok(true)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e).context("Failed to delete the Edge policy key"),
+    }
+}
+
+/// Whether this process may create and write the Edge policy key, checked
+/// without writing anything: the key itself if it exists, otherwise its
+/// nearest existing parent.
+pub fn can_write(scope: Scope) -> bool {
+    let hive = scope.hive();
+    let mut path = EDGE_POLICY_PATH;
+    loop {
+        match hive.open_subkey_with_flags(path, KEY_SET_VALUE | KEY_CREATE_SUB_KEY) {
+            Ok(_) => return true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => match path.rsplit_once('\\') {
+                Some((parent, _)) => path = parent,
+                None => return false,
+            },
+            Err(_) => return false,
+        }
+    }
+}
+
+/// Edge policy areas delivered by an MDM such as Intune. These land under
+/// the PolicyManager key rather than the policy key and take precedence over
+/// both hives.
+pub fn mdm_policies() -> Vec<String> {
+    let Ok(device) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(MDM_DEVICE_PATH) else {
+        return Vec::new();
+    };
+    device
+        .enum_keys()
+        .filter_map(|r| r.ok())
+        .filter(|name| name.to_ascii_lowercase().contains("edge"))
+        .collect()
+}
+
 /// Warn if any HKLM policies overlap with what we're about to write.
-pub fn check_hklm_conflicts(entries: &[PolicyEntry]) {
+pub fn check_hklm_conflicts(entries: &[PolicyEntry]) -> Vec<&str> {
+    let overridden = hklm_overrides(entries);
+    for name in &overridden {
+        warn!("HKLM policy {name} overrides the config (HKLM takes precedence)");
+    }
+    overridden
+}
+
+/// The top-level entries HKLM also sets, without logging them, for callers
+/// that can't have output on stderr.
+pub fn hklm_overrides(entries: &[PolicyEntry]) -> Vec<&str> {
     let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
     let base = match hklm.open_subkey(EDGE_POLICY_PATH) {
         Ok(k) => k,
-        Err(_) => return,
+        Err(_) => return Vec::new(),
     };
 
-    let mut warned = false;
+    let mut overridden = Vec::new();
     for entry in entries {
         if !entry.subkey.is_empty() {
             continue;
@@ -140,14 +909,8 @@ pub fn check_hklm_conflicts(entries: &[PolicyEntry]) {
         let exists: Result<String, _> = base.get_value(&entry.name);
         let exists_dword: Result<u32, _> = base.get_value(&entry.name);
         if exists.is_ok() || exists_dword.is_ok() {
-            if !warned {
-                eprintln!("Warning: The following HKLM policies overlap (HKLM takes precedence):");
-                warned = true;
-            }
-            eprintln!("  HKLM: {}", entry.name);
+            overridden.push(entry.name.as_str());
         }
     }
-    if warned {
-        eprintln!();
-    }
+    overridden
 }
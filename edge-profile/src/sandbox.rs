@@ -0,0 +1,76 @@
+//! `sandbox`: a Windows Sandbox configuration that applies the config in a
+//! throwaway VM and opens Edge, for trying policy changes safely.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{ensure, Context, Result};
+
+const SANDBOX_BIN: &str = r"C:\edge-profile\bin";
+const SANDBOX_CONFIG: &str = r"C:\edge-profile\config";
+
+/// Write the `.wsb` file, mapping this executable's folder and the config's
+/// folder into the sandbox read-only.
+pub fn generate(config: &Path, out: &Path, launch: bool) -> Result<()> {
+    let config = std::path::absolute(config)
+        .with_context(|| format!("Failed to resolve config path: {}", config.display()))?;
+    ensure!(config.is_file(), "Config not found: {}", config.display());
+    let exe = std::env::current_exe().context("Cannot determine executable path")?;
+
+    let (exe_dir, exe_name) = split(&exe)?;
+    let (config_dir, config_name) = split(&config)?;
+    let command = format!(
+        r#"cmd /c ""{SANDBOX_BIN}\{exe_name}" apply --config "{SANDBOX_CONFIG}\{config_name}" & start msedge""#
+    );
+
+    let wsb = [
+        "<Configuration>".to_owned(),
+        "  <MappedFolders>".to_owned(),
+        mapped_folder(&exe_dir, SANDBOX_BIN),
+        mapped_folder(&config_dir, SANDBOX_CONFIG),
+        "  </MappedFolders>".to_owned(),
+        "  <LogonCommand>".to_owned(),
+        format!("    <Command>{}</Command>", escape(&command)),
+        "  </LogonCommand>".to_owned(),
+        "</Configuration>\n".to_owned(),
+    ]
+    .join("\n");
+    std::fs::write(out, wsb).with_context(|| format!("Failed to write {}", out.display()))?;
+    println!("Wrote {}", out.display());
+
+    if launch {
+        Command::new("cmd")
+            .args(["/c", "start", ""])
+            .arg(out)
+            .status()
+            .context("Failed to launch Windows Sandbox")?;
+    }
+    Ok(())
+}
+
+fn split(path: &Path) -> Result<(PathBuf, String)> {
+    let dir = path.parent().context("Path has no parent folder")?;
+    let name = path.file_name().context("Path has no file name")?;
+    Ok((dir.to_owned(), name.to_string_lossy().into_owned()))
+}
+
+fn mapped_folder(host: &Path, sandbox: &str) -> String {
+    [
+        "    <MappedFolder>".to_owned(),
+        format!(
+            "      <HostFolder>{}</HostFolder>",
+            escape(&host.to_string_lossy())
+        ),
+        format!("      <SandboxFolder>{sandbox}</SandboxFolder>"),
+        "      <ReadOnly>true</ReadOnly>".to_owned(),
+        "    </MappedFolder>".to_owned(),
+    ]
+    .join("\n")
+}
+
+/// Escape text for an XML element.
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,175 @@
+//! `schedule`: a Task Scheduler job that re-applies the config at sign-in
+//! and on an interval, for machines where something (usually a domain GPO
+//! refresh) keeps overwriting the policies.
+
+use std::os::windows::process::CommandExt;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use anyhow::{bail, ensure, Context, Result};
+use windows_sys::Win32::System::Threading::CREATE_NO_WINDOW;
+
+use crate::registry::Scope;
+use crate::sandbox::escape;
+
+pub const TASK_NAME: &str = "edge-profile";
+
+/// Parse an interval like `30m`, `1h`, `1h30m` or `1d` into minutes.
+pub fn parse_interval(s: &str) -> Result<u32, String> {
+    let mut minutes = 0u32;
+    let mut digits = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'm' => 1,
+            'h' => 60,
+            'd' => 24 * 60,
+            _ => return Err(format!("unknown unit '{c}' (use m, h or d)")),
+        };
+        let n: u32 = digits
+            .parse()
+            .map_err(|_| format!("expected a number before '{c}'"))?;
+        minutes = n
+            .checked_mul(unit)
+            .and_then(|m| minutes.checked_add(m))
+            .ok_or("interval is too long")?;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err("missing unit after the last number (use m, h or d)".to_owned());
+    }
+    if minutes == 0 {
+        return Err("interval must be at least 1m".to_owned());
+    }
+    Ok(minutes)
+}
+
+/// Register (or replace) the task: `apply --if-changed --notify` at sign-in
+/// and every `minutes` after.
+pub fn install(config: &Path, minutes: u32, scope: Scope) -> Result<()> {
+    let config = std::path::absolute(config)
+        .with_context(|| format!("Failed to resolve config path: {}", config.display()))?;
+    let exe = std::env::current_exe().context("Cannot determine executable path")?;
+    let mut arguments = format!(
+        "apply --if-changed --notify --config \"{}\"",
+        config.display()
+    );
+    if scope == Scope::Machine {
+        arguments.push_str(" --scope machine");
+    }
+    let user = match (std::env::var("USERDOMAIN"), std::env::var("USERNAME")) {
+        (Ok(domain), Ok(name)) => format!(r"{domain}\{name}"),
+        _ => bail!("Cannot determine the current user"),
+    };
+    // Machine-scope writes need the elevated token, when the user has one.
+    let run_level = match scope {
+        Scope::User => "LeastPrivilege",
+        Scope::Machine => "HighestAvailable",
+    };
+
+    let xml = [
+        r#"<?xml version="1.0" encoding="UTF-16"?>"#.to_owned(),
+        r#"<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">"#
+            .to_owned(),
+        "  <RegistrationInfo>".to_owned(),
+        "    <Description>Re-apply Edge policies with edge-profile</Description>".to_owned(),
+        "  </RegistrationInfo>".to_owned(),
+        "  <Triggers>".to_owned(),
+        "    <LogonTrigger>".to_owned(),
+        format!("      <UserId>{}</UserId>", escape(&user)),
+        "    </LogonTrigger>".to_owned(),
+        "    <TimeTrigger>".to_owned(),
+        format!("      <Repetition><Interval>PT{minutes}M</Interval></Repetition>"),
+        "      <StartBoundary>2000-01-01T00:00:00</StartBoundary>".to_owned(),
+        "    </TimeTrigger>".to_owned(),
+        "  </Triggers>".to_owned(),
+        "  <Principals>".to_owned(),
+        r#"    <Principal id="Author">"#.to_owned(),
+        format!("      <UserId>{}</UserId>", escape(&user)),
+        "      <LogonType>InteractiveToken</LogonType>".to_owned(),
+        format!("      <RunLevel>{run_level}</RunLevel>"),
+        "    </Principal>".to_owned(),
+        "  </Principals>".to_owned(),
+        "  <Settings>".to_owned(),
+        "    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>".to_owned(),
+        "    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>".to_owned(),
+        "    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>".to_owned(),
+        "    <StartWhenAvailable>true</StartWhenAvailable>".to_owned(),
+        "    <ExecutionTimeLimit>PT10M</ExecutionTimeLimit>".to_owned(),
+        "  </Settings>".to_owned(),
+        r#"  <Actions Context="Author">"#.to_owned(),
+        "    <Exec>".to_owned(),
+        format!(
+            "      <Command>{}</Command>",
+            escape(&exe.to_string_lossy())
+        ),
+        format!("      <Arguments>{}</Arguments>", escape(&arguments)),
+        "    </Exec>".to_owned(),
+        "  </Actions>".to_owned(),
+        "</Task>\n".to_owned(),
+    ]
+    .join("\r\n");
+
+    // schtasks reads the definition from a file, in the encoding it declares.
+    let file = std::env::temp_dir().join(format!("{TASK_NAME}-task.xml"));
+    let bytes: Vec<u8> = std::iter::once(0xFEFF)
+        .chain(xml.encode_utf16())
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    std::fs::write(&file, bytes).with_context(|| format!("Failed to write {}", file.display()))?;
+    let output = schtasks(&["/Create", "/F", "/TN", TASK_NAME, "/XML"], Some(&file));
+    let _ = std::fs::remove_file(&file);
+    check(output?, "Failed to register the scheduled task")?;
+
+    println!("Scheduled {TASK_NAME}: at sign-in and every {minutes} minute(s)");
+    println!("  {} {arguments}", exe.display());
+    Ok(())
+}
+
+pub fn remove() -> Result<()> {
+    if !installed() {
+        println!("No scheduled task to remove.");
+        return Ok(());
+    }
+    let output = schtasks(&["/Delete", "/F", "/TN", TASK_NAME], None)?;
+    check(output, "Failed to delete the scheduled task")?;
+    println!("Removed the {TASK_NAME} scheduled task.");
+    Ok(())
+}
+
+/// Print Task Scheduler's summary of the task: next and last run, and the
+/// last result.
+pub fn status() -> Result<()> {
+    let output = schtasks(&["/Query", "/V", "/FO", "LIST", "/TN", TASK_NAME], None)?;
+    if !output.status.success() {
+        println!("No scheduled task installed.");
+        return Ok(());
+    }
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    Ok(())
+}
+
+pub fn installed() -> bool {
+    schtasks(&["/Query", "/TN", TASK_NAME], None).is_ok_and(|o| o.status.success())
+}
+
+fn schtasks(args: &[&str], file: Option<&Path>) -> Result<Output> {
+    let mut command = Command::new("schtasks");
+    command.args(args).creation_flags(CREATE_NO_WINDOW);
+    if let Some(file) = file {
+        command.arg(file);
+    }
+    command.output().context("Failed to run schtasks")
+}
+
+fn check(output: Output, context: &str) -> Result<()> {
+    ensure!(
+        output.status.success(),
+        "{context}: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(())
+}
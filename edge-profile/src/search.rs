@@ -0,0 +1,61 @@
+//! `search`: find policies in the catalog by keyword.
+
+use crate::catalog::{self, PolicyDef};
+use crate::{explain, output};
+
+/// Print every policy whose name, summary, category or value meanings
+/// contain all of `words`, with the config syntax that sets it. Name matches
+/// come first.
+pub fn search(words: &[String]) {
+    let words: Vec<String> = words.iter().map(|w| w.to_ascii_lowercase()).collect();
+    let mut hits: Vec<(bool, &PolicyDef)> = catalog::POLICIES
+        .iter()
+        .filter(|def| {
+            let text = searchable(def);
+            words.iter().all(|w| text.contains(w.as_str()))
+        })
+        .map(|def| {
+            let name = def.name.to_ascii_lowercase();
+            (!words.iter().all(|w| name.contains(w.as_str())), def)
+        })
+        .collect();
+    if hits.is_empty() {
+        println!("No policies match.");
+        if !catalog::FROM_TEMPLATES {
+            let line = format!(
+                "This build only knows edge-profile's {} curated policies; \
+                 any other policy can still be set under [raw].",
+                catalog::POLICIES.len()
+            );
+            anstream::println!("{}", output::paint(output::NOTE, line));
+        }
+        return;
+    }
+    hits.sort_by_key(|(not_in_name, def)| (*not_in_name, def.name));
+
+    for (_, def) in &hits {
+        let title = format!("{} ({})", def.name, catalog::category_title(def.category));
+        anstream::println!("{}", output::paint(output::HEADER, title));
+        println!("  {}", def.summary);
+        println!("  Config: {}", explain::config_syntax(def));
+        if let Some(new) = def.replaced_by {
+            let line = format!("Deprecated; use {new} instead");
+            anstream::println!("  {}", output::paint(output::WARNING, line));
+        }
+    }
+    println!();
+    println!(
+        "{} match(es). `edge-profile explain <name>` shows values and documentation.",
+        hits.len()
+    );
+}
+
+/// Everything a keyword can match, lowercased.
+fn searchable(def: &PolicyDef) -> String {
+    let mut text = format!("{} {} {}", def.name, def.summary, def.category);
+    for (_, meaning) in def.values {
+        text.push(' ');
+        text.push_str(meaning);
+    }
+    text.to_ascii_lowercase()
+}
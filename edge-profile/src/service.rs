@@ -0,0 +1,308 @@
+//! Background enforcement as a Windows service.
+//!
+//! The service runs as LocalSystem, so it has no meaningful HKCU and always
+//! enforces at machine scope. That is also what makes it stick: HKLM policies
+//! can't be changed without elevation.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use windows_service::service::{
+    ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+    ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+use windows_service::{define_windows_service, service_dispatcher};
+use windows_sys::Win32::System::EventLog::{
+    DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+    EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE, REPORT_EVENT_TYPE,
+};
+use winreg::enums::*;
+use winreg::RegKey;
+
+use crate::diff;
+use crate::notify;
+use crate::registry::{Backend, KeyWatcher, Registry, Scope};
+
+pub const SERVICE_NAME: &str = "edge-profile";
+const DISPLAY_NAME: &str = "Edge Profile policy enforcement";
+const EVENT_SOURCE_PATH: &str =
+    r"SYSTEM\CurrentControlSet\Services\EventLog\Application\edge-profile";
+
+/// How long to wait for a registry change before re-checking the config
+/// file and the stop signal.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn install(config: &Path) -> Result<()> {
+    let config = std::path::absolute(config)
+        .with_context(|| format!("Failed to resolve config path: {}", config.display()))?;
+    let exe = std::env::current_exe().context("Cannot determine executable path")?;
+
+    let manager = ServiceManager::local_computer(
+        None::<&str>,
+        ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+    )
+    .context("Failed to connect to the service manager (run elevated)")?;
+
+    let info = ServiceInfo {
+        name: OsString::from(SERVICE_NAME),
+        display_name: OsString::from(DISPLAY_NAME),
+        service_type: ServiceType::OWN_PROCESS,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: exe,
+        launch_arguments: vec![
+            OsString::from("service"),
+            OsString::from("run"),
+            OsString::from("--config"),
+            config.clone().into_os_string(),
+        ],
+        dependencies: vec![],
+        account_name: None,
+        account_password: None,
+    };
+    let service = manager
+        .create_service(&info, ServiceAccess::START)
+        .context("Failed to create service")?;
+
+    register_event_source()?;
+
+    service
+        .start::<&str>(&[])
+        .context("Service created but failed to start")?;
+    println!("Installed and started {SERVICE_NAME} service");
+    println!("  config: {}", config.display());
+    Ok(())
+}
+
+pub fn uninstall() -> Result<()> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .context("Failed to connect to the service manager (run elevated)")?;
+    let service = manager
+        .open_service(
+            SERVICE_NAME,
+            ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE,
+        )
+        .context("Failed to open service (is it installed?)")?;
+
+    if service.query_status()?.current_state != ServiceState::Stopped {
+        let _ = service.stop();
+    }
+    service.delete().context("Failed to delete service")?;
+
+    let _ = RegKey::predef(HKEY_LOCAL_MACHINE).delete_subkey(EVENT_SOURCE_PATH);
+    println!("Removed {SERVICE_NAME} service");
+    Ok(())
+}
+
+/// The service's current state, or `None` if it isn't installed.
+pub fn state() -> Option<ServiceState> {
+    let manager =
+        ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT).ok()?;
+    let service = manager
+        .open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS)
+        .ok()?;
+    Some(service.query_status().ok()?.current_state)
+}
+
+/// Entry point when started by the service control manager.
+pub fn run(config: PathBuf) -> Result<()> {
+    let _ = CONFIG_PATH.set(config);
+    service_dispatcher::start(SERVICE_NAME, ffi_service_main)
+        .context("Failed to start service dispatcher (`service run` is for the SCM only)")?;
+    Ok(())
+}
+
+define_windows_service!(ffi_service_main, service_main);
+
+fn service_main(_arguments: Vec<OsString>) {
+    if let Err(e) = run_service() {
+        log_event(EVENTLOG_ERROR_TYPE, &format!("Service stopped: {e:#}"));
+    }
+}
+
+fn run_service() -> Result<()> {
+    let config = CONFIG_PATH
+        .get()
+        .context("Service started without a config")?;
+
+    let (stop_tx, stop_rx) = mpsc::channel();
+    let handler = service_control_handler::register(SERVICE_NAME, move |control| match control {
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            let _ = stop_tx.send(());
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    })?;
+
+    let status = |state, accept| ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted: accept,
+        exit_code: ServiceExitCode::Win32(0),
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    };
+    handler.set_service_status(status(
+        ServiceState::Running,
+        ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN,
+    ))?;
+    log_event(
+        EVENTLOG_INFORMATION_TYPE,
+        &format!("Enforcing {} at machine scope", config.display()),
+    );
+
+    let mut watcher = KeyWatcher::new(Scope::Machine)?;
+    let mut config_mtime = None;
+    let mut dirty = true;
+    while stop_rx.try_recv().is_err() {
+        let mtime = modified(config);
+        if mtime != config_mtime {
+            config_mtime = mtime;
+            dirty = true;
+        }
+        if dirty {
+            enforce(config);
+        }
+        dirty = watcher.wait(POLL_INTERVAL).unwrap_or_else(|e| {
+            log_event(EVENTLOG_WARNING_TYPE, &format!("{e:#}"));
+            std::thread::sleep(POLL_INTERVAL);
+            false
+        });
+    }
+
+    handler.set_service_status(status(ServiceState::Stopped, ServiceControlAccept::empty()))?;
+    Ok(())
+}
+
+/// Re-apply the config if the registry has drifted from it, logging what
+/// was corrected.
+fn enforce(config: &Path) {
+    let result = reconcile(config);
+    match result {
+        Ok(changes) if changes.is_empty() => {}
+        Ok(changes) => {
+            let lines: Vec<String> = changes.iter().map(|c| format!("  {c}")).collect();
+            log_event(
+                EVENTLOG_WARNING_TYPE,
+                &format!(
+                    "Drift detected, re-applied {} value(s):\n{}",
+                    changes.len(),
+                    lines.join("\n")
+                ),
+            );
+            notify_user(
+                "Edge policies restored",
+                &format!(
+                    "{} value(s) had drifted:\n{}",
+                    changes.len(),
+                    drift_summary(&changes)
+                ),
+            );
+        }
+        Err(e) => {
+            log_event(EVENTLOG_ERROR_TYPE, &format!("Enforcement failed: {e:#}"));
+            notify_user("Edge policy enforcement failed", &format!("{e:#}"));
+        }
+    }
+}
+
+/// The first few changed policy names, for a toast body.
+fn drift_summary(changes: &[diff::Change]) -> String {
+    const SHOWN: usize = 3;
+    let mut names: Vec<&str> = Vec::new();
+    for change in changes {
+        let name = change.entry().policy_name();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    let mut summary = names
+        .iter()
+        .take(SHOWN)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > SHOWN {
+        summary.push_str(&format!(" and {} more", names.len() - SHOWN));
+    }
+    summary
+}
+
+fn notify_user(title: &str, body: &str) {
+    if let Err(e) = notify::toast_active_user(title, body) {
+        log_event(
+            EVENTLOG_WARNING_TYPE,
+            &format!("Toast notification failed: {e:#}"),
+        );
+    }
+}
+
+fn reconcile(config: &Path) -> Result<Vec<diff::Change>> {
+    let (_, entries) = crate::prepare(Some(config), Scope::Machine, false)?;
+    let current = Registry.read_current(Scope::Machine)?;
+    let changes = diff::compare(&entries, &current);
+    if !changes.is_empty() {
+        Registry.apply(&entries, Scope::Machine)?;
+    }
+    Ok(changes)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Register the event source with EventCreate.exe as its message file, whose
+/// event IDs 1-1000 render the inserted string verbatim.
+fn register_event_source() -> Result<()> {
+    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_owned());
+    let (key, _) = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .create_subkey(EVENT_SOURCE_PATH)
+        .context("Failed to register event log source")?;
+    key.set_value(
+        "EventMessageFile",
+        &format!(r"{system_root}\System32\EventCreate.exe"),
+    )?;
+    key.set_value("TypesSupported", &7u32)?;
+    Ok(())
+}
+
+fn log_event(kind: REPORT_EVENT_TYPE, message: &str) {
+    let source = wide(SERVICE_NAME);
+    let message = wide(message);
+    let strings = [message.as_ptr()];
+
+    // SAFETY: all pointers reference NUL-terminated buffers that outlive the
+    // calls, and the handle is deregistered before returning.
+    unsafe {
+        let handle = RegisterEventSourceW(std::ptr::null(), source.as_ptr());
+        if handle.is_null() {
+            return;
+        }
+        ReportEventW(
+            handle,
+            kind,
+            0,
+            1,
+            std::ptr::null_mut(),
+            1,
+            0,
+            strings.as_ptr(),
+            std::ptr::null(),
+        );
+        DeregisterEventSource(handle);
+    }
+}
+
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
@@ -0,0 +1,117 @@
+//! What the last successful apply wrote, so an apply with nothing to do can
+//! exit without reading the config's policies back from the registry.
+//!
+//! `~/.edge-profile/state.json` records, per hive, a key for the config
+//! (its hash, the tool version and the flags that change what gets written)
+//! and a fingerprint of the policy key: the last-write time and counts of
+//! the key and each of its subkeys. Any write to the key, by this tool or
+//! anything else, changes the fingerprint.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use winreg::RegKey;
+
+use crate::config;
+use crate::registry::{Scope, EDGE_POLICY_PATH};
+use crate::webhook;
+
+#[derive(Serialize, Deserialize, PartialEq)]
+struct Applied {
+    config: String,
+    registry: String,
+}
+
+/// Identifies what an apply of this config would write.
+pub fn config_key(config: &Path, replace_deprecated: bool) -> Result<String> {
+    Ok(format!(
+        "{}:{}:{}",
+        env!("CARGO_PKG_VERSION"),
+        u8::from(replace_deprecated),
+        webhook::config_hash(config)?
+    ))
+}
+
+/// Whether the last apply to `scope` used this config and nothing has
+/// touched the policy key since.
+pub fn is_current(scope: Scope, config_key: &str) -> bool {
+    let Ok(state) = load() else {
+        return false;
+    };
+    let current = state.get(&scope.to_string()).is_some_and(|applied| {
+        applied.config == config_key && Some(&applied.registry) == fingerprint(scope).as_ref()
+    });
+    debug!(
+        "state for {scope} is {}",
+        if current { "current" } else { "stale" }
+    );
+    current
+}
+
+/// Record a successful apply. Best-effort: without a state file the next
+/// apply just takes the slow path.
+pub fn record(scope: Scope, config_key: String) {
+    let result = (|| -> Result<()> {
+        let mut state = load().unwrap_or_default();
+        let registry = fingerprint(scope).context("Failed to query the policy key")?;
+        let applied = Applied {
+            config: config_key,
+            registry,
+        };
+        state.insert(scope.to_string(), applied);
+        let path = path()?;
+        std::fs::create_dir_all(path.parent().expect("state file has a parent"))?;
+        std::fs::write(&path, serde_json::to_string_pretty(&state)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    })();
+    if let Err(e) = result {
+        warn!("Failed to save apply state: {e:#}");
+    }
+}
+
+fn load() -> Result<BTreeMap<String, Applied>> {
+    let text = std::fs::read_to_string(path()?)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+pub fn path() -> Result<PathBuf> {
+    Ok(config::data_dir()?.join("state.json"))
+}
+
+/// Cheap summary of the policy key. User-scope applies also depend on HKLM,
+/// whose policies take precedence, so its key is included too.
+fn fingerprint(scope: Scope) -> Option<String> {
+    match scope {
+        Scope::User => Some(format!(
+            "{}|{}",
+            key_fingerprint(Scope::User)?,
+            key_fingerprint(Scope::Machine)?
+        )),
+        Scope::Machine => key_fingerprint(Scope::Machine),
+    }
+}
+
+fn key_fingerprint(scope: Scope) -> Option<String> {
+    let base = match scope.hive().open_subkey(EDGE_POLICY_PATH) {
+        Ok(k) => k,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Some("-".to_owned()),
+        Err(_) => return None,
+    };
+    let mut parts = vec![describe(&base)?];
+    for name in base.enum_keys() {
+        let name = name.ok()?;
+        let sub = base.open_subkey(&name).ok()?;
+        parts.push(format!("{name}={}", describe(&sub)?));
+    }
+    Some(parts.join(";"))
+}
+
+fn describe(key: &RegKey) -> Option<String> {
+    let info = key.query_info().ok()?;
+    let t = &info.last_write_time;
+    let time = (u64::from(t.dwHighDateTime) << 32) | u64::from(t.dwLowDateTime);
+    Some(format!("{time}.{}.{}", info.values, info.sub_keys))
+}
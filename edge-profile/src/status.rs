@@ -0,0 +1,61 @@
+//! `status`: a short answer to "is this machine configured?".
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::exit::Status;
+use crate::registry::{self, Backend, Registry, Scope};
+use crate::{config, diff, output, policy};
+
+pub fn run(config: Option<&Path>, scope: Scope) -> Result<Status> {
+    let path = config::resolve_path(config)?;
+    let (_, entries) = crate::prepare(Some(&path), scope, false)?;
+    let current = Registry.read_current(scope)?;
+    let changes = diff::compare(&entries, &current);
+    let overridden = match scope {
+        Scope::User => registry::check_hklm_conflicts(&entries),
+        Scope::Machine => Vec::new(),
+    };
+
+    let managed = current
+        .iter()
+        .filter(|e| {
+            let name = e.policy_name();
+            policy::MANAGED_VALUES.contains(&name)
+                || policy::is_managed_subkey(name)
+                || entries.iter().any(|d| d.policy_name() == name)
+        })
+        .count();
+
+    println!("Config:    {} ({} value(s))", path.display(), entries.len());
+    println!(
+        "Registry:  {scope}, {} value(s), {managed} managed by edge-profile",
+        current.len()
+    );
+    if changes.is_empty() {
+        anstream::println!("Drift:     {}", output::paint(output::ADDED, "none"));
+    } else {
+        let line = format!("{} value(s) differ from the config", changes.len());
+        anstream::println!("Drift:     {}", output::paint(output::CHANGED, line));
+        for change in &changes {
+            anstream::println!("             {}", output::change(change));
+        }
+    }
+    match scope {
+        Scope::Machine => println!("HKLM:      n/a at machine scope"),
+        Scope::User if overridden.is_empty() => println!("HKLM:      no overlapping policies"),
+        Scope::User => {
+            let line = format!("overrides {}", overridden.join(", "));
+            anstream::println!("HKLM:      {}", output::paint(output::WARNING, line));
+        }
+    }
+
+    Ok(if !overridden.is_empty() {
+        Status::Conflict
+    } else if !changes.is_empty() {
+        Status::Drift
+    } else {
+        Status::Ok
+    })
+}
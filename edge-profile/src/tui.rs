@@ -0,0 +1,620 @@
+//! `tui` command: every catalog policy with its config value, registry value
+//! and effective hive, editable in place like a writable edge://policy. The
+//! whole config can be applied, or the managed policies cleaned, from the
+//! same screen.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use edge_profile_core::PolicySet;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::catalog::{self, PolicyDef, PolicyKind};
+use crate::diff::{self, Change};
+use crate::policy::{self, PolicyEntry, RegValue};
+use crate::registry::{self, Backend, Registry, Scope};
+use crate::{config, history, journal, validate};
+
+/// A registry edit that hasn't been written yet.
+#[derive(Clone)]
+enum Pending {
+    Set(RegValue),
+    Unset,
+}
+
+struct Policy {
+    def: &'static PolicyDef,
+    config: Option<String>,
+    current: Option<String>,
+    /// Raw value at the edited scope, the starting point for edits.
+    value: Option<RegValue>,
+    /// Hive Edge reads the policy from, if it is set at all.
+    source: Option<Scope>,
+    pending: Option<Pending>,
+}
+
+enum Item {
+    Header(&'static str),
+    Policy(usize),
+}
+
+/// An action waiting for its key to be pressed a second time.
+#[derive(Clone, Copy, PartialEq)]
+enum Confirm {
+    Quit,
+    Apply,
+    Clean,
+}
+
+struct App {
+    scope: Scope,
+    config_path: Option<std::path::PathBuf>,
+    policies: Vec<Policy>,
+    /// Table rows: section headers and indexes into `policies`.
+    lines: Vec<Item>,
+    table: TableState,
+    /// Text being typed for the selected policy, while editing.
+    input: Option<String>,
+    status: String,
+    confirm: Option<Confirm>,
+    /// Hide policies that are neither configured, set nor pending.
+    only_set: bool,
+}
+
+pub fn run(config: Option<&Path>, scope: Scope) -> Result<()> {
+    let mut app = App::new(config, scope)?;
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn new(config: Option<&Path>, scope: Scope) -> Result<Self> {
+        let policies = catalog::POLICIES
+            .iter()
+            .map(|def| Policy {
+                def,
+                config: None,
+                current: None,
+                value: None,
+                source: None,
+                pending: None,
+            })
+            .collect();
+
+        let mut app = App {
+            scope,
+            config_path: config.map(Path::to_owned),
+            policies,
+            lines: Vec::new(),
+            table: TableState::default(),
+            input: None,
+            status: String::new(),
+            confirm: None,
+            only_set: false,
+        };
+        app.refresh()?;
+        Ok(app)
+    }
+
+    /// Lay out the table rows again, keeping the selected policy selected
+    /// if it is still shown.
+    fn rebuild(&mut self) {
+        let selected = self.selected();
+        self.lines.clear();
+        let mut category = "";
+        for (i, p) in self.policies.iter().enumerate() {
+            let shown = p.config.is_some() || p.current.is_some() || p.pending.is_some();
+            if self.only_set && !shown {
+                continue;
+            }
+            if p.def.category != category {
+                category = p.def.category;
+                self.lines.push(Item::Header(category));
+            }
+            self.lines.push(Item::Policy(i));
+        }
+        let line = self
+            .lines
+            .iter()
+            .position(|l| matches!(l, Item::Policy(i) if Some(*i) == selected));
+        self.select(line.unwrap_or(0), 1);
+    }
+
+    /// Re-read both hives and the config.
+    fn refresh(&mut self) -> Result<()> {
+        let user = Registry.read_current(Scope::User)?;
+        let machine = Registry.read_current(Scope::Machine)?;
+        let configured = match config::load(self.config_path.as_deref()) {
+            Ok(cfg) => policy::build_entries(&cfg),
+            Err(e) => {
+                self.status = format!("Config not loaded: {e:#}");
+                Vec::new()
+            }
+        };
+        let current = match self.scope {
+            Scope::User => &user,
+            Scope::Machine => &machine,
+        };
+
+        for p in &mut self.policies {
+            let name = p.def.name;
+            p.config = describe(p.def, &configured);
+            p.current = describe(p.def, current);
+            p.value = top_level(name, current);
+            p.source = if describe(p.def, &machine).is_some() {
+                Some(Scope::Machine)
+            } else if describe(p.def, &user).is_some() {
+                Some(Scope::User)
+            } else {
+                None
+            };
+            p.pending = None;
+        }
+        self.rebuild();
+        Ok(())
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|f| self.draw(f))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if let Some(ref mut input) = self.input {
+                match key.code {
+                    KeyCode::Enter => self.commit_input(),
+                    KeyCode::Esc => self.input = None,
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
+            let confirmed = self.confirm.take();
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    if confirmed == Some(Confirm::Quit) || !self.has_pending() {
+                        return Ok(());
+                    }
+                    self.status = "Unwritten changes; press q again to discard them.".into();
+                    self.confirm = Some(Confirm::Quit);
+                }
+                KeyCode::Down | KeyCode::Char('j') => self.step(1),
+                KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+                KeyCode::PageDown => self.step(20),
+                KeyCode::PageUp => self.step(-20),
+                KeyCode::Char(' ') => self.toggle(),
+                KeyCode::Enter => self.start_input(),
+                KeyCode::Char('d') => self.set_pending(Some(Pending::Unset)),
+                KeyCode::Char('u') => self.set_pending(None),
+                KeyCode::Char('w') => match self.write() {
+                    Ok(n) => self.status = format!("Wrote {n} change(s) to {}.", self.scope),
+                    Err(e) => self.status = format!("Write failed: {e:#}"),
+                },
+                KeyCode::Char('r') => match self.refresh() {
+                    Ok(()) => self.status = "Reloaded.".into(),
+                    Err(e) => self.status = format!("Reload failed: {e:#}"),
+                },
+                KeyCode::Char('a') => {
+                    if confirmed == Some(Confirm::Apply) || !self.has_pending() {
+                        self.status = match self.apply() {
+                            Ok(message) => message,
+                            Err(e) => format!("Apply failed: {e:#}"),
+                        };
+                    } else {
+                        self.status = "Unwritten changes; press a again to discard them and \
+                                       apply the config."
+                            .into();
+                        self.confirm = Some(Confirm::Apply);
+                    }
+                }
+                KeyCode::Char('c') => {
+                    if confirmed == Some(Confirm::Clean) {
+                        self.status = match self.clean() {
+                            Ok(n) => format!("Removed {n} managed value(s) from {}.", self.scope),
+                            Err(e) => format!("Clean failed: {e:#}"),
+                        };
+                    } else {
+                        match managed(self.scope) {
+                            Ok(entries) if entries.is_empty() => {
+                                self.status =
+                                    format!("No managed policies in {} to remove.", self.scope);
+                            }
+                            Ok(entries) => {
+                                self.status = format!(
+                                    "Remove {} managed {} value(s)? Press c again to confirm.",
+                                    entries.len(),
+                                    self.scope
+                                );
+                                self.confirm = Some(Confirm::Clean);
+                            }
+                            Err(e) => self.status = format!("Clean failed: {e:#}"),
+                        }
+                    }
+                }
+                KeyCode::Char('f') => {
+                    self.only_set = !self.only_set;
+                    self.rebuild();
+                    self.status = if self.only_set && self.lines.is_empty() {
+                        "No policies are configured or set.".into()
+                    } else if self.only_set {
+                        "Showing configured and set policies.".into()
+                    } else {
+                        "Showing every policy.".into()
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        match self.lines.get(self.table.selected()?)? {
+            Item::Policy(i) => Some(*i),
+            Item::Header(_) => None,
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        self.policies.iter().any(|p| p.pending.is_some())
+    }
+
+    /// Move the selection by `delta` lines, skipping section headers.
+    fn step(&mut self, delta: isize) {
+        if self.lines.is_empty() {
+            return;
+        }
+        let from = self.table.selected().unwrap_or(0) as isize;
+        let to = (from + delta).clamp(0, self.lines.len() as isize - 1) as usize;
+        self.select(to, if delta < 0 { -1 } else { 1 });
+    }
+
+    fn select(&mut self, mut line: usize, direction: isize) {
+        if self.lines.is_empty() {
+            return self.table.select(None);
+        }
+        while let Some(Item::Header(_)) = self.lines.get(line) {
+            match line.checked_add_signed(direction) {
+                Some(next) if next < self.lines.len() => line = next,
+                _ => return self.select(line, -direction),
+            }
+        }
+        self.table.select(Some(line));
+    }
+
+    /// Space: flip booleans and cycle enumerated integers.
+    fn toggle(&mut self) {
+        let Some(i) = self.selected() else { return };
+        let p = &self.policies[i];
+        let value = match p.pending {
+            Some(Pending::Set(ref v)) => Some(v.clone()),
+            Some(Pending::Unset) => None,
+            None => p.value.clone(),
+        };
+        let next = match (p.def.kind, value) {
+            (PolicyKind::Boolean, Some(RegValue::Dword(v))) => u32::from(v == 0),
+            (PolicyKind::Boolean, _) => 1,
+            (PolicyKind::Integer, value) if !p.def.values.is_empty() => {
+                let at = match value {
+                    Some(RegValue::Dword(v)) => p.def.values.iter().position(|(n, _)| *n == v),
+                    _ => None,
+                };
+                let next = at.map_or(0, |at| (at + 1) % p.def.values.len());
+                p.def.values[next].0
+            }
+            _ => return self.start_input(),
+        };
+        self.set_pending(Some(Pending::Set(RegValue::Dword(next))));
+    }
+
+    fn start_input(&mut self) {
+        let Some(i) = self.selected() else { return };
+        let p = &self.policies[i];
+        if p.def.kind == PolicyKind::List {
+            self.status = format!(
+                "{} is a list policy; set it from the config instead.",
+                p.def.name
+            );
+            return;
+        }
+        self.input = Some(match p.value {
+            Some(RegValue::Dword(v)) => v.to_string(),
+            Some(RegValue::Sz(ref s)) => s.clone(),
+            None => String::new(),
+        });
+    }
+
+    fn commit_input(&mut self) {
+        let (Some(i), Some(text)) = (self.selected(), self.input.take()) else {
+            return;
+        };
+        let def = self.policies[i].def;
+        let value = match def.kind {
+            PolicyKind::Boolean | PolicyKind::Integer => {
+                match text
+                    .trim()
+                    .parse()
+                    .ok()
+                    .or_else(|| def.value_of(text.trim()))
+                {
+                    Some(v) => RegValue::Dword(v),
+                    None => {
+                        self.status = format!("{} takes a DWORD, not {text:?}.", def.name);
+                        return;
+                    }
+                }
+            }
+            _ => RegValue::Sz(text),
+        };
+        if let Some(error) = check_value(def, &value, self.scope) {
+            self.status = format!("Not set: {error}");
+            return;
+        }
+        self.set_pending(Some(Pending::Set(value)));
+    }
+
+    fn set_pending(&mut self, pending: Option<Pending>) {
+        if let Some(i) = self.selected() {
+            if self.policies[i].def.kind == PolicyKind::List && pending.is_some() {
+                return self.start_input();
+            }
+            self.policies[i].pending = pending;
+        }
+    }
+
+    fn write(&mut self) -> Result<usize> {
+        for p in &self.policies {
+            if let Some(Pending::Set(ref value)) = p.pending {
+                if let Some(error) = check_value(p.def, value, self.scope) {
+                    bail!("{error}");
+                }
+            }
+        }
+
+        let mut set = Vec::new();
+        let mut unset = Vec::new();
+        let mut changes = Vec::new();
+        for p in &self.policies {
+            let entry = |value: &RegValue| PolicyEntry {
+                subkey: String::new(),
+                name: p.def.name.to_owned(),
+                value: value.clone(),
+            };
+            match (&p.pending, &p.value) {
+                (Some(Pending::Set(new)), old) => {
+                    changes.push(match old {
+                        Some(old) => Change::Changed {
+                            old: entry(old),
+                            new: entry(new),
+                        },
+                        None => Change::Added(entry(new)),
+                    });
+                    set.push(entry(new));
+                }
+                (Some(Pending::Unset), old) => {
+                    changes.extend(old.as_ref().map(|old| Change::Removed(entry(old))));
+                    unset.push(p.def.name);
+                }
+                (None, _) => {}
+            }
+        }
+        Registry
+            .set_values(&set, self.scope)
+            .context("Failed to write policies")?;
+        Registry
+            .remove_values(&unset, self.scope)
+            .context("Failed to remove policies")?;
+        journal::record(self.scope, &changes);
+        history::record("tui", self.scope, None, &changes);
+        self.refresh()?;
+        Ok(set.len() + unset.len())
+    }
+
+    /// `a`: write the whole config, like `apply`, and say what changed.
+    /// Refuses a config with errors rather than printing them over the
+    /// screen; `validate` shows the details.
+    fn apply(&mut self) -> Result<String> {
+        let path = config::resolve_path(self.config_path.as_deref())?;
+        let cfg = config::load(Some(&path))?;
+        let entries = policy::build_entries(&cfg);
+        let errors = crate::check_config(&cfg, &entries, self.scope)
+            .iter()
+            .filter(|i| i.severity == validate::Severity::Error)
+            .count();
+        if errors > 0 {
+            bail!("the config has {errors} error(s); run `edge-profile validate` for details");
+        }
+
+        let current = Registry.read_current(self.scope)?;
+        let changes = diff::compare(&entries, &current);
+        if changes.is_empty() {
+            history::record("apply", self.scope, Some(&path), &[]);
+            self.refresh()?;
+            return Ok(format!("{} policies already match the config.", self.scope));
+        }
+        let overridden = match self.scope {
+            // Logging would land on top of the screen.
+            Scope::User => registry::hklm_overrides(&entries).len(),
+            Scope::Machine => 0,
+        };
+        Registry
+            .apply(&entries, self.scope)
+            .context("Failed to apply the config")?;
+        journal::record(self.scope, &changes);
+        history::record("apply", self.scope, Some(&path), &changes);
+        self.refresh()?;
+        let summary = diff::Summary::new(&entries, &changes, overridden);
+        Ok(format!("Applied to {}: {summary}.", self.scope))
+    }
+
+    /// `c`: delete every value edge-profile manages, like `clean`. Recorded
+    /// in the journal, so `edge-profile undo` brings them back.
+    fn clean(&mut self) -> Result<usize> {
+        let removed: Vec<Change> = managed(self.scope)?
+            .into_iter()
+            .map(Change::Removed)
+            .collect();
+        let paths: Vec<String> = removed.iter().map(|c| c.entry().path()).collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        Registry
+            .remove_values(&paths, self.scope)
+            .context("Failed to remove policies")?;
+        journal::record(self.scope, &removed);
+        history::record_clean(self.scope, removed.len());
+        self.refresh()?;
+        Ok(removed.len())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, detail_area, status_area] = Layout::vertical([
+            Constraint::Min(5),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let rows = self.lines.iter().map(|line| match *line {
+            Item::Header(category) => Row::new([Cell::from(catalog::category_title(category))])
+                .style(Style::new().bold().underlined()),
+            Item::Policy(i) => {
+                let p = &self.policies[i];
+                let current = match p.pending {
+                    Some(Pending::Set(ref v)) => format!("* {}", show(p.def, v)),
+                    Some(Pending::Unset) => "* (unset)".to_owned(),
+                    None => p.current.clone().unwrap_or_default(),
+                };
+                let source = p.source.map(|s| s.to_string()).unwrap_or_default();
+                let row = Row::new([
+                    Cell::from(format!("  {}", p.def.name)),
+                    Cell::from(p.config.clone().unwrap_or_default()),
+                    Cell::from(current),
+                    Cell::from(source),
+                ]);
+                if p.pending.is_some() {
+                    row.yellow()
+                } else if p.config.is_some() && p.config != p.current {
+                    row.red()
+                } else {
+                    row
+                }
+            }
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(40),
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Length(6),
+            ],
+        )
+        .header(
+            Row::new([
+                "Policy".to_owned(),
+                "Config".to_owned(),
+                self.scope.to_string(),
+                "Source".to_owned(),
+            ])
+            .style(Style::new().add_modifier(Modifier::REVERSED)),
+        )
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title(" edge-profile "));
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let detail = match (self.selected(), &self.input) {
+            (Some(i), Some(input)) => {
+                Line::from(format!("{} = {input}_", self.policies[i].def.name))
+            }
+            (Some(i), None) => {
+                let def = self.policies[i].def;
+                let mut text = format!("{} ({})", def.summary, def.kind.registry_type());
+                if !def.values.is_empty() {
+                    text.push_str(&format!(": {}", def.describe_values()));
+                }
+                Line::from(text)
+            }
+            (None, _) => Line::default(),
+        };
+        frame.render_widget(Paragraph::new(detail).block(Block::bordered()), detail_area);
+
+        let status = if self.status.is_empty() {
+            "space toggle  enter edit  d unset  u undo  w write  a apply  c clean  f filter  \
+             r reload  q quit"
+        } else {
+            &self.status
+        };
+        frame.render_widget(Paragraph::new(status).dim(), status_area);
+    }
+}
+
+/// The values in `scope` that `clean` would remove.
+fn managed(scope: Scope) -> Result<Vec<PolicyEntry>> {
+    let current = Registry.read_current(scope)?;
+    Ok(current
+        .into_iter()
+        .filter(|e| {
+            if e.subkey.is_empty() {
+                policy::MANAGED_VALUES.contains(&e.name.as_str())
+            } else {
+                policy::is_managed_subkey(&e.subkey)
+            }
+        })
+        .collect())
+}
+
+/// The policy's value in `entries` for display: the value itself, or an item
+/// count for list policies.
+fn describe(def: &PolicyDef, entries: &[PolicyEntry]) -> Option<String> {
+    if def.kind == PolicyKind::List {
+        let items = entries.iter().filter(|e| e.subkey == def.name).count();
+        return (items > 0).then(|| format!("[{items} items]"));
+    }
+    top_level(def.name, entries).map(|v| show(def, &v))
+}
+
+fn top_level(name: &str, entries: &[PolicyEntry]) -> Option<RegValue> {
+    entries
+        .iter()
+        .find(|e| e.subkey.is_empty() && e.name == name)
+        .map(|e| e.value.clone())
+}
+
+fn show(def: &PolicyDef, value: &RegValue) -> String {
+    match (value, def.kind) {
+        (RegValue::Dword(v), PolicyKind::Boolean) => (*v != 0).to_string(),
+        (RegValue::Dword(v), _) => match def.value_name(*v) {
+            Some(name) => format!("{v} ({name})"),
+            None => v.to_string(),
+        },
+        (RegValue::Sz(s), _) => s.clone(),
+    }
+}
+
+/// The first error in `value` for `def`, checked like `set` checks the same
+/// value under `[raw]`.
+fn check_value(def: &PolicyDef, value: &RegValue, scope: Scope) -> Option<String> {
+    let value = match value {
+        RegValue::Dword(v) => toml::Value::Integer(i64::from(*v)),
+        RegValue::Sz(s) => toml::Value::String(s.clone()),
+    };
+    PolicySet::new()
+        .raw(def.name, value)
+        .check(scope)
+        .into_iter()
+        .find(|i| i.severity == validate::Severity::Error)
+        .map(|i| i.message)
+}
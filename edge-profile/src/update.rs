@@ -0,0 +1,137 @@
+//! `self-update`: replace this binary with the latest GitHub release.
+//!
+//! Releases are tagged `edge-profile-v<version>` and carry one executable
+//! per architecture, `edge-profile-<arch>.exe`, next to a
+//! `edge-profile-<arch>.exe.sha256` file holding its hex SHA-256.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::http;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/jack-work/window-pains/releases";
+const TAG_PREFIX: &str = "edge-profile-v";
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    draft: bool,
+    prerelease: bool,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Install the newest release if it is newer than this build. With `check`,
+/// only say whether there is one. Returns false if the user declined.
+pub fn run(check: bool, yes: bool) -> Result<bool> {
+    let exe = std::env::current_exe().context("Failed to locate this executable")?;
+    // Left behind by the previous update, which couldn't delete a running file.
+    let _ = std::fs::remove_file(old_path(&exe));
+
+    let current = env!("CARGO_PKG_VERSION");
+    let Some((version, release)) = latest()? else {
+        bail!("No edge-profile release found at {RELEASES_URL}");
+    };
+    if parse_version(&version) <= parse_version(current) {
+        println!("edge-profile {current} is up to date.");
+        return Ok(true);
+    }
+    if check {
+        println!("edge-profile {version} is available (this is {current}).");
+        return Ok(true);
+    }
+
+    let name = format!("edge-profile-{}.exe", std::env::consts::ARCH);
+    let asset = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name == name)
+            .with_context(|| format!("Release {} has no {name}", release.tag_name))
+    };
+    let binary = asset(&name)?;
+    let checksum = asset(&format!("{name}.sha256"))?;
+    if !crate::confirm(&format!("Update edge-profile {current} to {version}?"), yes)? {
+        return Ok(false);
+    }
+
+    let bytes = download(&binary.browser_download_url)?;
+    let expected = String::from_utf8(download(&checksum.browser_download_url)?)
+        .context("Checksum file is not text")?;
+    let expected = expected.split_whitespace().next().unwrap_or_default();
+    let actual: String = Sha256::digest(&bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    if !actual.eq_ignore_ascii_case(expected) {
+        bail!("Checksum mismatch for {name}: expected {expected}, got {actual}");
+    }
+
+    replace(&exe, &bytes)?;
+    println!("Updated edge-profile {current} to {version}.");
+    Ok(true)
+}
+
+/// The newest published edge-profile release and its version.
+fn latest() -> Result<Option<(String, Release)>> {
+    let text = http::agent()?
+        .get(RELEASES_URL)
+        .set("Accept", "application/vnd.github+json")
+        .call()
+        .with_context(|| format!("Failed to fetch {RELEASES_URL}"))?
+        .into_string()
+        .context("Failed to read response body")?;
+    let releases: Vec<Release> =
+        serde_json::from_str(&text).context("Failed to parse the release list")?;
+    Ok(releases
+        .into_iter()
+        .filter(|r| !r.draft && !r.prerelease)
+        .filter_map(|r| Some((r.tag_name.strip_prefix(TAG_PREFIX)?.to_owned(), r)))
+        .max_by_key(|(version, _)| parse_version(version)))
+}
+
+fn download(url: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    http::agent()?
+        .get(url)
+        .call()
+        .with_context(|| format!("Failed to download {url}"))?
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("Failed to download {url}"))?;
+    Ok(bytes)
+}
+
+/// Swap the new binary in. Windows won't overwrite a running executable but
+/// will rename it, so the old one moves aside and is deleted next time.
+fn replace(exe: &Path, bytes: &[u8]) -> Result<()> {
+    let old = old_path(exe);
+    std::fs::rename(exe, &old)
+        .with_context(|| format!("Failed to move {} aside", exe.display()))?;
+    if let Err(e) = std::fs::write(exe, bytes) {
+        let _ = std::fs::rename(&old, exe);
+        return Err(e).with_context(|| format!("Failed to write {}", exe.display()));
+    }
+    Ok(())
+}
+
+fn old_path(exe: &Path) -> PathBuf {
+    exe.with_extension("exe.old")
+}
+
+/// `1.2.3` as comparable numbers; anything unparseable counts as zero.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
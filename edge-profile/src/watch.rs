@@ -0,0 +1,132 @@
+//! `apply --watch`: re-apply whenever the config file is saved.
+
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use windows_sys::Win32::Foundation::{HANDLE, INVALID_HANDLE_VALUE, WAIT_OBJECT_0};
+use windows_sys::Win32::Storage::FileSystem::{
+    FindCloseChangeNotification, FindFirstChangeNotificationW, FindNextChangeNotification,
+    FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE,
+};
+use windows_sys::Win32::System::Threading::{WaitForSingleObject, INFINITE};
+
+use crate::registry::{Backend, Registry, Scope};
+use crate::{apply, config, diff, history, output, state};
+
+/// How long to let an editor finish saving before reading the file. Many
+/// write a temporary file and rename it over the original.
+const SETTLE: Duration = Duration::from_millis(250);
+
+/// Apply once, then again each time the config's contents change. Runs
+/// until interrupted; a config that fails to load or validate is reported
+/// and the previous policies are left in place.
+pub fn run(config: Option<&Path>, scope: Scope, replace_deprecated: bool) -> Result<()> {
+    let path = config::resolve_path(config)?;
+    let dir = path
+        .parent()
+        .context("Config path has no parent directory")?;
+    let watcher = DirWatcher::new(dir)?;
+
+    let mut last = std::fs::read(&path).ok();
+    apply_logged(&path, scope, replace_deprecated);
+    println!("Watching {} for changes (Ctrl+C to stop).", path.display());
+
+    loop {
+        watcher.wait()?;
+        std::thread::sleep(SETTLE);
+        let text = std::fs::read(&path).ok();
+        if text == last {
+            continue;
+        }
+        last = text;
+        println!();
+        if last.is_none() {
+            println!(
+                "{} was removed; waiting for it to come back.",
+                path.display()
+            );
+            continue;
+        }
+        println!("{} changed.", path.display());
+        apply_logged(&path, scope, replace_deprecated);
+    }
+}
+
+fn apply_logged(path: &Path, scope: Scope, replace_deprecated: bool) {
+    if let Err(e) = apply(path, scope, replace_deprecated) {
+        anstream::eprintln!("{}", output::paint(output::ERROR, format!("Error: {e:#}")));
+    }
+}
+
+/// One pass of `apply`: write the config and print what changed.
+pub fn apply(path: &Path, scope: Scope, replace_deprecated: bool) -> Result<()> {
+    let (cfg, entries) = crate::prepare(Some(path), scope, replace_deprecated)?;
+    let current = Registry.read_current(scope)?;
+    let changes = diff::compare(&entries, &current);
+    if changes.is_empty() {
+        history::record("apply", scope, Some(path), &[]);
+        println!("{scope} policies already match the config.");
+        return Ok(());
+    }
+
+    apply::Apply {
+        command: "apply",
+        cfg: &cfg,
+        entries: &entries,
+        scope,
+        config: Some(path),
+        config_key: state::config_key(path, replace_deprecated).ok(),
+        interactive: false,
+        output: apply::Output::Changes,
+    }
+    .run(&changes, &[])?;
+    Ok(())
+}
+
+/// Signals when a file in a directory is written, created, renamed or
+/// deleted.
+struct DirWatcher {
+    handle: HANDLE,
+}
+
+impl DirWatcher {
+    fn new(dir: &Path) -> Result<Self> {
+        let wide: Vec<u16> = dir.as_os_str().encode_wide().chain([0]).collect();
+        // SAFETY: `wide` is a NUL-terminated path that outlives the call.
+        let handle = unsafe {
+            FindFirstChangeNotificationW(
+                wide.as_ptr(),
+                0,
+                FILE_NOTIFY_CHANGE_LAST_WRITE | FILE_NOTIFY_CHANGE_FILE_NAME,
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(std::io::Error::last_os_error())
+                .with_context(|| format!("Failed to watch {}", dir.display()));
+        }
+        Ok(Self { handle })
+    }
+
+    /// Block until something in the directory changes.
+    fn wait(&self) -> Result<()> {
+        // SAFETY: waiting on a change handle we own.
+        let result = unsafe { WaitForSingleObject(self.handle, INFINITE) };
+        if result != WAIT_OBJECT_0 {
+            return Err(std::io::Error::last_os_error()).context("Failed to wait for changes");
+        }
+        // SAFETY: re-arms the handle created in `new`.
+        if unsafe { FindNextChangeNotification(self.handle) } == 0 {
+            return Err(std::io::Error::last_os_error()).context("Failed to keep watching");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DirWatcher {
+    fn drop(&mut self) {
+        // SAFETY: the handle was created in `new` and is closed exactly once.
+        unsafe { FindCloseChangeNotification(self.handle) };
+    }
+}
@@ -0,0 +1,104 @@
+//! JSON summary of an apply or verify, POSTed to `[report] webhook`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::diff::Change;
+use crate::http;
+use crate::registry::Scope;
+
+#[derive(Serialize)]
+struct Summary<'a> {
+    host: String,
+    command: &'a str,
+    scope: String,
+    config_hash: String,
+    changes: Vec<ChangeJson>,
+    conflicts: &'a [&'a str],
+}
+
+#[derive(Serialize)]
+struct ChangeJson {
+    kind: &'static str,
+    path: String,
+    old: Option<String>,
+    new: Option<String>,
+}
+
+/// Post the summary. Reporting is best-effort: failures are logged and the
+/// command's own outcome stands.
+pub fn send(
+    url: &str,
+    command: &str,
+    scope: Scope,
+    config: &Path,
+    changes: &[Change],
+    conflicts: &[&str],
+) {
+    if let Err(e) = post(url, command, scope, config, changes, conflicts) {
+        warn!("Webhook report to {url} failed: {e:#}");
+    }
+}
+
+fn post(
+    url: &str,
+    command: &str,
+    scope: Scope,
+    config: &Path,
+    changes: &[Change],
+    conflicts: &[&str],
+) -> Result<()> {
+    let summary = Summary {
+        host: std::env::var("COMPUTERNAME").unwrap_or_default(),
+        command,
+        scope: scope.to_string(),
+        config_hash: config_hash(config)?,
+        changes: changes.iter().map(change_json).collect(),
+        conflicts,
+    };
+    let body = serde_json::to_string(&summary)?;
+    debug!("POST {url}: {body}");
+    http::agent()?
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .context("Request failed")?;
+    Ok(())
+}
+
+/// SHA-256 of the config file, hex-encoded.
+pub fn config_hash(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read config: {}", path.display()))?;
+    Ok(Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+fn change_json(change: &Change) -> ChangeJson {
+    match change {
+        Change::Added(e) => ChangeJson {
+            kind: "added",
+            path: e.path(),
+            old: None,
+            new: Some(e.value.to_string()),
+        },
+        Change::Changed { old, new } => ChangeJson {
+            kind: "changed",
+            path: new.path(),
+            old: Some(old.value.to_string()),
+            new: Some(new.value.to_string()),
+        },
+        Change::Removed(e) => ChangeJson {
+            kind: "removed",
+            path: e.path(),
+            old: Some(e.value.to_string()),
+            new: None,
+        },
+    }
+}
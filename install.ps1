@@ -253,6 +253,9 @@ if (Test-Path (Join-Path $edgeProfileCrate "Cargo.toml"))
     if ($LASTEXITCODE -eq 0)
     {
       Write-Host "  INSTALL: edge-profile.exe" -ForegroundColor Green
+      # DSC discovers resource manifests next to the executable
+      $edgeProfileBin = Split-Path (Get-Command edge-profile).Source
+      Copy-Item (Join-Path $edgeProfileCrate "edge-profile.dsc.resource.json") $edgeProfileBin -Force
       Write-Host "  Applying Edge policies..." -ForegroundColor Gray
       edge-profile apply 2>&1 | ForEach-Object { Write-Host "  $_" -ForegroundColor Gray }
       if ($LASTEXITCODE -eq 0)